@@ -3,14 +3,44 @@ use std::path::PathBuf;
 #[path = "src/cli.rs"]
 mod cli;
 
+// `cli.rs` uses its value parsers
+#[path = "src/duration.rs"]
+mod duration;
+
+// `cli.rs` only uses its parser
+#[allow(dead_code)]
+#[path = "src/format_string.rs"]
+mod format_string;
+
 fn main() {
     println!("cargo:rerun-if-changed=src/cli.rs");
+    println!("cargo:rerun-if-changed=src/duration.rs");
+    println!("cargo:rerun-if-changed=src/format_string.rs");
 
     // Write to OUT_DIR so `cargo publish` verification passes (build scripts
     // must not modify files outside OUT_DIR).
     let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap());
 
-    let cmd = <cli::Cli as clap::CommandFactory>::command();
+    let mut cmd = <cli::Cli as clap::CommandFactory>::command();
+
+    // clap_mangen lists subcommands without their aliases; note them in the
+    // one-line description, as `--help` does.
+    let aliased: Vec<(String, String)> = cmd
+        .get_subcommands()
+        .filter_map(|sc| {
+            let aliases: Vec<&str> = sc.get_visible_aliases().collect();
+            if aliases.is_empty() {
+                return None;
+            }
+            let about = sc.get_about().map(|a| a.to_string()).unwrap_or_default();
+            let about = format!("{} [aliases: {}]", about, aliases.join(", "));
+            Some((sc.get_name().to_string(), about))
+        })
+        .collect();
+    for (name, about) in aliased {
+        cmd = cmd.mut_subcommand(name, |sc| sc.about(about));
+    }
+
     let man = clap_mangen::Man::new(cmd);
     let mut buffer = Vec::new();
     man.render(&mut buffer).unwrap();
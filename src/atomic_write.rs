@@ -0,0 +1,137 @@
+//! Writing files so that a crash or a full disk never leaves half of one behind
+//!
+//! The contents go to `<file>.tmp` first and are synced to disk; only then is
+//! the temporary file moved to the real name. A failed write removes the
+//! temporary file, so readers see either the old file or the complete new one.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// Replace `path` with `contents`
+pub fn write(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let tmp = tmp_path(path);
+    write_tmp(&tmp, contents, false)?;
+    let result = fs::rename(&tmp, path);
+    finish(path, &tmp, result)
+}
+
+/// Create `path` with `contents`, failing with [`io::ErrorKind::AlreadyExists`]
+/// if it (or another writer's temporary file for it) already exists
+pub fn write_new(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if path.exists() {
+        return Err(io::ErrorKind::AlreadyExists.into());
+    }
+    let tmp = tmp_path(path);
+    write_tmp(&tmp, contents, true)?;
+    // A hard link never replaces an existing file, unlike a rename
+    let result = match fs::hard_link(&tmp, path) {
+        Ok(()) => fs::remove_file(&tmp),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => Err(e),
+        // Filesystems without hard links: the check above has to do
+        Err(_) if !path.exists() => fs::rename(&tmp, path),
+        Err(e) => Err(e),
+    };
+    finish(path, &tmp, result)
+}
+
+/// `backup-1.txt` -> `backup-1.txt.tmp`
+fn tmp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+/// Write and sync the temporary file, removing it again if that fails.
+/// With `create_new`, an existing temporary file belongs to another writer
+/// and is left alone.
+fn write_tmp(tmp: &Path, contents: &[u8], create_new: bool) -> io::Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true);
+    if create_new {
+        options.create_new(true);
+    } else {
+        options.create(true).truncate(true);
+    }
+    let mut file = options.open(tmp)?;
+    let result = file.write_all(contents).and_then(|()| file.sync_all());
+    if result.is_err() {
+        let _ = fs::remove_file(tmp);
+    }
+    result
+}
+
+/// Clean up after the move to the real name, and make the new name durable
+fn finish(path: &Path, tmp: &Path, result: io::Result<()>) -> io::Result<()> {
+    if result.is_err() {
+        let _ = fs::remove_file(tmp);
+        return result;
+    }
+    // The rename itself lives in the directory; sync it too where that's possible
+    #[cfg(unix)]
+    if let Some(dir) = path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        let _ = fs::File::open(dir).and_then(|d| d.sync_all());
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn write_replaces_the_file() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("config.toml");
+        fs::write(&path, "old").unwrap();
+
+        write(&path, b"new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(entries(dir.path()), vec!["config.toml"]);
+    }
+
+    #[test]
+    fn write_new_never_replaces() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("backup-1.txt");
+
+        write_new(&path, b"first").unwrap();
+        let err = write_new(&path, b"second").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first");
+        assert_eq!(entries(dir.path()), vec!["backup-1.txt"]);
+
+        // Another writer is still busy with this name
+        let other = dir.path().join("backup-2.txt");
+        fs::write(tmp_path(&other), "half").unwrap();
+        let err = write_new(&other, b"mine").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read_to_string(tmp_path(&other)).unwrap(), "half");
+    }
+
+    #[test]
+    fn failed_move_leaves_no_temporary_file() {
+        let dir = TempDir::new().unwrap();
+        // A non-empty directory can't be replaced by a file
+        let path = dir.path().join("config.toml");
+        fs::create_dir(&path).unwrap();
+        fs::write(path.join("keep"), "").unwrap();
+
+        assert!(write(&path, b"new").is_err());
+        assert_eq!(entries(dir.path()), vec!["config.toml"]);
+        assert!(path.join("keep").exists());
+    }
+}
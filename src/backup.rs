@@ -2,13 +2,83 @@
 
 use anyhow::{Context, Result};
 use chrono::{DateTime, Utc};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use sha2::{Digest, Sha256};
 use std::collections::HashMap;
 use std::fs;
-use std::io::BufRead;
+use std::io::Read;
 use std::path::{Path, PathBuf};
-use std::process::Command;
+use std::sync::atomic::{AtomicU64, Ordering};
 
+use crate::atomic_write;
+use crate::branch::Branch;
+use crate::cli::BackupSort;
 use crate::config::Config;
+use crate::perf;
+
+/// Header line recording the repository name in a backup file
+pub const REPOSITORY_HEADER: &str = "# Repository:";
+
+/// Header line recording the repository toplevel path in a backup file
+pub const REPO_PATH_HEADER: &str = "# Repository path:";
+
+/// Header line recording the `clean --label` note in a backup file
+pub const LABEL_HEADER: &str = "# Label:";
+
+/// Largest backup file read when `backups.max_file_size_mb` is not set
+pub const DEFAULT_MAX_FILE_SIZE_MB: u64 = 16;
+
+/// Longest line parsed as an entry; longer lines are reported as skipped
+const MAX_LINE_LEN: usize = 16 * 1024;
+
+/// Skipped lines kept for display; beyond this only their number is counted
+const MAX_SKIPPED_LINES: usize = 200;
+
+/// How much of a file is checked for NUL bytes before treating it as text
+const BINARY_SNIFF_LEN: usize = 1024;
+
+static MAX_FILE_BYTES: AtomicU64 = AtomicU64::new(DEFAULT_MAX_FILE_SIZE_MB * 1024 * 1024);
+
+/// Set the largest backup file that is read (from `backups.max_file_size_mb`)
+pub fn set_max_file_size_mb(mb: u64) {
+    MAX_FILE_BYTES.store(mb.saturating_mul(1024 * 1024), Ordering::Relaxed);
+}
+
+/// Read a backup file as text, refusing files over the size limit and files
+/// that aren't text at all (anything copied into the backup directory by mistake)
+fn read_backup(path: &Path) -> Result<String, RestoreError> {
+    let limit = MAX_FILE_BYTES.load(Ordering::Relaxed);
+    let file = fs::File::open(path).map_err(|e| RestoreError::Other(e.into()))?;
+    let mut bytes = Vec::new();
+    // Read one byte past the limit so a file that grew since it was listed is still caught
+    file.take(limit.saturating_add(1))
+        .read_to_end(&mut bytes)
+        .map_err(|e| RestoreError::Other(e.into()))?;
+    if bytes.len() as u64 > limit {
+        return Err(RestoreError::BackupCorrupted {
+            message: format!(
+                "file is larger than {} MB (raise backups.max_file_size_mb to read it)",
+                limit / (1024 * 1024)
+            ),
+        });
+    }
+    backup_text(bytes)
+}
+
+/// Check that raw file contents look like a text backup and decode them
+fn backup_text(bytes: Vec<u8>) -> Result<String, RestoreError> {
+    if bytes[..bytes.len().min(BINARY_SNIFF_LEN)].contains(&0) {
+        return Err(RestoreError::BackupCorrupted {
+            message: "not a text backup (binary data at the start of the file)".to_string(),
+        });
+    }
+    String::from_utf8(bytes).map_err(|e| RestoreError::BackupCorrupted {
+        message: format!(
+            "not a text backup (invalid UTF-8 at byte {})",
+            e.utf8_error().valid_up_to()
+        ),
+    })
+}
 
 /// Information about a backup file
 #[derive(Debug, Clone)]
@@ -16,27 +86,36 @@ pub struct BackupInfo {
     /// Path to the backup file
     pub path: PathBuf,
     /// Repository name (used for grouping and display)
-    #[allow(dead_code)]
     repo_name: String,
     /// Timestamp when backup was created
     pub timestamp: DateTime<Utc>,
     /// Number of branches in the backup
     pub branch_count: usize,
+    /// Repository toplevel path recorded when the backup was made (None for legacy backups)
+    pub repo_path: Option<PathBuf>,
+    /// Label given with `clean --label`
+    pub label: Option<String>,
+    /// Result of checking the trailing `# SHA256:` line
+    pub checksum: ChecksumStatus,
 }
 
 impl BackupInfo {
     /// Parse a backup file and extract its info
     fn from_path(path: PathBuf, repo_name: &str) -> Result<Self> {
-        let file = fs::File::open(&path)
-            .with_context(|| format!("Failed to open backup file: {}", path.display()))?;
-        let reader = std::io::BufReader::new(file);
+        let content = read_backup(&path)
+            .with_context(|| format!("Failed to read backup file: {}", path.display()))?;
 
         let mut timestamp: Option<DateTime<Utc>> = None;
         let mut branch_count = 0;
+        let mut marker_count = 0;
+        let mut repo_path: Option<PathBuf> = None;
+        let mut label: Option<String> = None;
 
-        for line in reader.lines() {
-            let line = line?;
+        let lines: Vec<&str> = content.lines().collect();
+        let version = backup_format_version(&lines).unwrap_or(1);
+        let checksum = verify_checksum(&content, version);
 
+        for line in lines {
             // Parse header for timestamp
             if line.starts_with("# Created:") {
                 if let Some(date_str) = line.strip_prefix("# Created:") {
@@ -47,11 +126,28 @@ impl BackupInfo {
                 }
             }
 
-            // Count branch entries (lines starting with "git branch")
+            if let Some(path_str) = line.strip_prefix(REPO_PATH_HEADER) {
+                let path_str = path_str.trim();
+                if !path_str.is_empty() {
+                    repo_path = Some(PathBuf::from(path_str));
+                }
+            }
+
+            if let Some(text) = line.strip_prefix(LABEL_HEADER) {
+                label = Some(text.trim().to_string()).filter(|l| !l.is_empty());
+            }
+
+            // Count branch entries (lines starting with "git branch", or the
+            // marker lines of a templated backup)
             if line.starts_with("git branch") {
                 branch_count += 1;
+            } else if line.starts_with(ENTRY_MARKER) {
+                marker_count += 1;
             }
         }
+        if marker_count > 0 {
+            branch_count = marker_count;
+        }
 
         // If no timestamp in file, try to parse from filename
         let timestamp = timestamp
@@ -62,9 +158,17 @@ impl BackupInfo {
             repo_name: repo_name.to_string(),
             timestamp,
             branch_count,
+            repo_path,
+            label,
+            checksum,
         })
     }
 
+    /// Whole days since the backup was taken
+    pub fn age_days(&self) -> i64 {
+        Utc::now().signed_duration_since(self.timestamp).num_days()
+    }
+
     /// Format the age of the backup as a human-readable string
     pub fn format_age(&self) -> String {
         let now = Utc::now();
@@ -93,6 +197,11 @@ impl BackupInfo {
         }
     }
 
+    /// Repository the backup was listed under
+    pub fn repo_name(&self) -> &str {
+        &self.repo_name
+    }
+
     /// Get just the filename without the full path
     pub fn filename(&self) -> String {
         self.path
@@ -101,17 +210,74 @@ impl BackupInfo {
             .unwrap_or("unknown")
             .to_string()
     }
+
+    /// Chronological sort key: the creation time, then the `-N` suffix of a
+    /// backup made within the same second as an earlier one. The path comes
+    /// last so that the order never depends on directory order.
+    pub fn order_key(&self) -> (DateTime<Utc>, u32, &Path) {
+        (self.timestamp, filename_sequence(&self.path), &self.path)
+    }
+}
+
+/// JSON shape for `backup list --format json`
+impl Serialize for BackupInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut info = serializer.serialize_struct("BackupInfo", 6)?;
+        info.serialize_field("filename", &self.filename())?;
+        info.serialize_field("path", &self.path)?;
+        info.serialize_field("repo", &self.repo_name)?;
+        info.serialize_field("timestamp", &self.timestamp.to_rfc3339())?;
+        info.serialize_field("branch_count", &self.branch_count)?;
+        info.serialize_field("label", &self.label)?;
+        info.end()
+    }
+}
+
+/// Write a new backup file named `backup-YYYYMMDD-HHMMSS.txt` in `dir`.
+///
+/// A backup from the same second is never overwritten: the name gets a
+/// `-2`, `-3`, ... suffix instead. The file only appears under its name once
+/// it is complete, so an interrupted write can't leave a truncated backup.
+pub fn write_new_backup(dir: &Path, created: DateTime<Utc>, contents: &[u8]) -> Result<PathBuf> {
+    let stem = format!("backup-{}", created.format("%Y%m%d-%H%M%S"));
+    for n in 1.. {
+        let filename = match n {
+            1 => format!("{}.txt", stem),
+            n => format!("{}-{}.txt", stem, n),
+        };
+        let path = dir.join(filename);
+        match atomic_write::write_new(&path, contents) {
+            Ok(()) => return Ok(path),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e).with_context(|| format!("Failed to write {}", path.display())),
+        }
+    }
+    unreachable!("ran out of backup file suffixes")
 }
 
-/// Parse timestamp from backup filename (backup-YYYYMMDD-HHMMSS.txt)
+/// Position of a backup among those written in the same second: 1 for
+/// `backup-YYYYMMDD-HHMMSS.txt`, N for its `-N` suffixed successors
+fn filename_sequence(path: &Path) -> u32 {
+    path.file_stem()
+        .and_then(|stem| {
+            let suffix = stem.to_str()?.strip_prefix("backup-")?.split('-').nth(2)?;
+            suffix.parse().ok()
+        })
+        .unwrap_or(1)
+}
+
+/// Parse timestamp from backup filename (backup-YYYYMMDD-HHMMSS.txt, or
+/// backup-YYYYMMDD-HHMMSS-N.txt for a later backup within the same second)
 fn parse_timestamp_from_filename(path: &Path) -> Option<DateTime<Utc>> {
     let filename = path.file_stem()?.to_str()?;
     let timestamp_part = filename.strip_prefix("backup-")?;
 
-    // Parse YYYYMMDD-HHMMSS format
+    // Parse YYYYMMDD-HHMMSS format, with an optional numeric suffix
     let parts: Vec<&str> = timestamp_part.split('-').collect();
-    if parts.len() != 2 {
-        return None;
+    match parts.len() {
+        2 => {}
+        3 if !parts[2].is_empty() && parts[2].bytes().all(|b| b.is_ascii_digit()) => {}
+        _ => return None,
     }
 
     let date_str = parts[0]; // YYYYMMDD
@@ -143,28 +309,17 @@ pub fn list_all_backups() -> Result<HashMap<String, Vec<BackupInfo>>> {
         return Ok(result);
     }
 
-    // Each subdirectory is a repository
-    let entries = fs::read_dir(&backups_dir).with_context(|| {
-        format!(
-            "Failed to read backups directory: {}",
-            backups_dir.display()
-        )
-    })?;
-
-    for entry in entries {
-        let entry = entry?;
-        let path = entry.path();
-
-        if !path.is_dir() {
-            continue;
+    // Each subdirectory is a repository; submodules are nested one level
+    // below their superproject's directory (`parent/vendor`)
+    for repo_name in subdirectory_names(&backups_dir)? {
+        for nested in subdirectory_names(&Config::repo_backup_dir(&repo_name)?)? {
+            let nested = format!("{}/{}", repo_name, nested);
+            let backups = list_repo_backups(&nested)?;
+            if !backups.is_empty() {
+                result.insert(nested, backups);
+            }
         }
 
-        let repo_name = path
-            .file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
         let backups = list_repo_backups(&repo_name)?;
         if !backups.is_empty() {
             result.insert(repo_name, backups);
@@ -174,6 +329,82 @@ pub fn list_all_backups() -> Result<HashMap<String, Vec<BackupInfo>>> {
     Ok(result)
 }
 
+/// Names of the directories directly inside `dir`
+fn subdirectory_names(dir: &Path) -> Result<Vec<String>> {
+    let entries = fs::read_dir(dir)
+        .with_context(|| format!("Failed to read backups directory: {}", dir.display()))?;
+
+    let mut names = Vec::new();
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            names.push(
+                path.file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or("unknown")
+                    .to_string(),
+            );
+        }
+    }
+    Ok(names)
+}
+
+/// Move backups recorded for the repository at `toplevel` but filed under
+/// another name (e.g. one derived from a symlinked path) into `repo_name`'s
+/// directory. Returns how many were moved from each other directory.
+///
+/// Only backups whose `# Repository path:` resolves to `toplevel` are moved, so
+/// a different repository that happens to share a name is left alone.
+pub fn adopt_split_backups(repo_name: &str, toplevel: &Path) -> Result<Vec<(String, usize)>> {
+    let target_dir = Config::repo_backup_dir(repo_name)?;
+    let mut moved = Vec::new();
+
+    for (other, backups) in list_all_backups()? {
+        if other == repo_name {
+            continue;
+        }
+        let mut count = 0;
+        for backup in backups {
+            let ours = backup
+                .repo_path
+                .as_deref()
+                .is_some_and(|recorded| same_path(recorded, toplevel));
+            if !ours {
+                continue;
+            }
+            let target = unused_backup_path(&target_dir, &backup.filename());
+            fs::create_dir_all(&target_dir)?;
+            fs::rename(&backup.path, &target)
+                .with_context(|| format!("Failed to move backup {}", backup.path.display()))?;
+            count += 1;
+        }
+        if count > 0 {
+            // Only succeeds once nothing is left behind
+            let _ = fs::remove_dir(Config::repo_backup_dir(&other)?);
+            moved.push((other, count));
+        }
+    }
+
+    moved.sort();
+    Ok(moved)
+}
+
+/// `filename` in `dir`, or if a backup there already has that name, the next
+/// free `-N` name for the same second
+fn unused_backup_path(dir: &Path, filename: &str) -> PathBuf {
+    let path = dir.join(filename);
+    if !path.exists() {
+        return path;
+    }
+    // backup-YYYYMMDD-HHMMSS, without any suffix
+    let stem: Vec<&str> = filename.trim_end_matches(".txt").splitn(4, '-').collect();
+    let stem = stem[..stem.len().min(3)].join("-");
+    (2..)
+        .map(|n| dir.join(format!("{}-{}.txt", stem, n)))
+        .find(|path| !path.exists())
+        .expect("ran out of backup file suffixes")
+}
+
 /// List backups for a specific repository
 pub fn list_repo_backups(repo_name: &str) -> Result<Vec<BackupInfo>> {
     let repo_backup_dir = Config::repo_backup_dir(repo_name)?;
@@ -209,17 +440,90 @@ pub fn list_repo_backups(repo_name: &str) -> Result<Vec<BackupInfo>> {
             Ok(info) => backups.push(info),
             Err(e) => {
                 // Log warning but continue with other files
-                eprintln!("Warning: Could not parse backup file: {}", e);
+                eprintln!("Warning: Could not parse backup file: {:#}", e);
             }
         }
     }
 
-    // Sort by timestamp, newest first
-    backups.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    // Newest first
+    backups.sort_by(|a, b| b.order_key().cmp(&a.order_key()));
 
     Ok(backups)
 }
 
+/// Filters for narrowing down `backup list` output; all set filters must match
+#[derive(Debug, Clone, Default)]
+pub struct BackupListFilter {
+    /// Only keep backups created at or after this time
+    pub since: Option<DateTime<Utc>>,
+    /// Only keep backups created before this time
+    pub before: Option<DateTime<Utc>>,
+    /// Only keep backups containing a branch that matches this glob pattern
+    pub contains: Option<String>,
+    /// Only keep backups whose label contains this text (case-insensitive)
+    pub label: Option<String>,
+    /// Keep at most this many backups (the first ones in `sort` order)
+    pub limit: Option<usize>,
+    /// Order of the returned backups
+    pub sort: BackupSort,
+}
+
+impl BackupListFilter {
+    /// Whether any filter is set
+    pub fn is_active(&self) -> bool {
+        self.since.is_some()
+            || self.before.is_some()
+            || self.contains.is_some()
+            || self.label.is_some()
+            || self.limit.is_some()
+    }
+
+    /// Apply the filters, then sort and truncate to `limit`
+    pub fn apply(&self, backups: Vec<BackupInfo>) -> Vec<BackupInfo> {
+        let mut filtered: Vec<BackupInfo> = backups
+            .into_iter()
+            .filter(|b| self.since.is_none_or(|since| b.timestamp >= since))
+            .filter(|b| self.before.is_none_or(|before| b.timestamp < before))
+            .filter(|b| match &self.label {
+                Some(text) => b
+                    .label
+                    .as_ref()
+                    .is_some_and(|l| l.to_lowercase().contains(&text.to_lowercase())),
+                None => true,
+            })
+            .filter(|b| match &self.contains {
+                Some(pattern) => backup_contains_branch(&b.path, pattern),
+                None => true,
+            })
+            .collect();
+
+        match self.sort {
+            BackupSort::Age => filtered.sort_by(|a, b| b.order_key().cmp(&a.order_key())),
+            BackupSort::Branches => filtered.sort_by(|a, b| {
+                (b.branch_count, b.order_key()).cmp(&(a.branch_count, a.order_key()))
+            }),
+        }
+
+        if let Some(limit) = self.limit {
+            filtered.truncate(limit);
+        }
+
+        filtered
+    }
+}
+
+/// Check whether a backup file has an entry whose branch name matches a glob pattern
+fn backup_contains_branch(path: &Path, pattern: &str) -> bool {
+    parse_backup_file(path)
+        .map(|parsed| {
+            parsed
+                .entries
+                .iter()
+                .any(|entry| Branch::glob_match(pattern, &entry.name))
+        })
+        .unwrap_or(false)
+}
+
 /// Information about a branch entry in a backup file
 #[derive(Debug, Clone)]
 pub struct BackupBranchEntry {
@@ -227,6 +531,54 @@ pub struct BackupBranchEntry {
     pub name: String,
     /// The commit SHA the branch pointed to
     pub commit_sha: String,
+    /// Per-branch git config (`branch.<name>.<key>`) as `(key, value)` pairs
+    pub config: Vec<(String, String)>,
+    /// The remote the branch was deleted from, for remote-tracking branches
+    pub remote: Option<String>,
+}
+
+impl BackupBranchEntry {
+    /// The branch description (`branch.<name>.description`), if one was recorded
+    pub fn description(&self) -> Option<&str> {
+        self.config
+            .iter()
+            .find(|(key, _)| key == "description")
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Format a `branch.<name>.<key>` config entry as a backup comment line.
+/// Backslashes and newlines in the value are escaped so it stays on one line.
+pub fn format_config_comment(branch_name: &str, key: &str, value: &str) -> String {
+    let escaped = value.replace('\\', "\\\\").replace('\n', "\\n");
+    format!("# branch.{}.{} = {}", branch_name, key, escaped)
+}
+
+/// Parse a config comment line written by [`format_config_comment`].
+/// Returns the `(key, value)` pair with the value unescaped.
+fn parse_config_comment(line: &str) -> Option<(String, String)> {
+    let rest = line.strip_prefix("# branch.")?;
+    let (full_key, escaped) = rest.split_once(" = ")?;
+    let (_, key) = full_key.rsplit_once('.')?;
+    if key.is_empty() {
+        return None;
+    }
+
+    let mut value = String::with_capacity(escaped.len());
+    let mut chars = escaped.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') => value.push('\n'),
+                Some(other) => value.push(other),
+                None => value.push('\\'),
+            }
+        } else {
+            value.push(c);
+        }
+    }
+
+    Some((key.to_string(), value))
 }
 
 /// Information about a skipped/corrupted line in a backup file
@@ -243,8 +595,17 @@ pub struct SkippedLine {
 pub struct ParsedBackup {
     /// Successfully parsed branch entries
     pub entries: Vec<BackupBranchEntry>,
-    /// Lines that were skipped due to corruption/malformation
+    /// Lines that were skipped due to corruption/malformation (the first
+    /// few hundred; see `skipped_line_count` for the total)
     pub skipped_lines: Vec<SkippedLine>,
+    /// How many lines were skipped in all
+    pub skipped_line_count: usize,
+    /// Repository name recorded in the header (`# Repository:`)
+    pub repository: Option<String>,
+    /// Repository toplevel path recorded in the header (`# Repository path:`)
+    pub repo_path: Option<PathBuf>,
+    /// Label recorded in the header (`# Label:`)
+    pub label: Option<String>,
 }
 
 /// Result of a successful restore operation
@@ -258,6 +619,13 @@ pub struct RestoreResult {
     pub commit_sha: String,
     /// Whether an existing branch was overwritten
     pub overwrote_existing: bool,
+    /// Per-branch config keys that were re-applied
+    pub restored_config: Vec<String>,
+    /// Per-branch config keys skipped because what they reference no longer exists
+    pub skipped_config: Vec<String>,
+    /// `(remote, branch)` the backed-up branch was deleted from, when it was
+    /// a remote branch
+    pub deleted_from: Option<(String, String)>,
 }
 
 /// Error type for restore failures
@@ -270,16 +638,37 @@ pub enum RestoreError {
         branch_name: String,
         commit_sha: String,
     },
+    /// The recorded SHA names an object that isn't a commit (e.g. a tag)
+    NotACommit {
+        branch_name: String,
+        commit_sha: String,
+        object_type: String,
+    },
     /// Branch not found in the backup file
     BranchNotInBackup {
         branch_name: String,
         available_branches: Vec<BackupBranchEntry>,
         skipped_lines: Vec<SkippedLine>,
+        skipped_line_count: usize,
+    },
+    /// No branch in the backup matches a `--matching` pattern
+    NoBranchesMatch {
+        pattern: String,
+        available_branches: Vec<BackupBranchEntry>,
+        skipped_lines: Vec<SkippedLine>,
+        skipped_line_count: usize,
     },
     /// No backups exist for the repository
     NoBackupsFound { repo_name: String },
     /// Backup file is corrupted or invalid
     BackupCorrupted { message: String },
+    /// Backup file was written in a newer format than this version understands
+    UnsupportedFormat { version: u32 },
+    /// Backup file was recorded for a different repository
+    RepoMismatch {
+        backup_repo: String,
+        current_repo: String,
+    },
     /// Other git or IO errors
     Other(anyhow::Error),
 }
@@ -300,15 +689,46 @@ impl std::fmt::Display for RestoreError {
                     branch_name, commit_sha
                 )
             }
+            RestoreError::NotACommit {
+                branch_name,
+                commit_sha,
+                object_type,
+            } => {
+                write!(
+                    f,
+                    "Cannot restore '{}': {} is a {}, not a commit",
+                    branch_name, commit_sha, object_type
+                )
+            }
             RestoreError::BranchNotInBackup { branch_name, .. } => {
                 write!(f, "Branch '{}' not found in backup", branch_name)
             }
+            RestoreError::NoBranchesMatch { pattern, .. } => {
+                write!(f, "No branches in backup match '{}'", pattern)
+            }
             RestoreError::NoBackupsFound { repo_name } => {
                 write!(f, "No backups found for repository '{}'", repo_name)
             }
             RestoreError::BackupCorrupted { message } => {
                 write!(f, "Backup file is corrupted: {}", message)
             }
+            RestoreError::UnsupportedFormat { version } => {
+                write!(
+                    f,
+                    "Backup file uses format version {}, but this deadbranch only understands up to version {}",
+                    version, BACKUP_FORMAT_VERSION
+                )
+            }
+            RestoreError::RepoMismatch {
+                backup_repo,
+                current_repo,
+            } => {
+                write!(
+                    f,
+                    "Backup belongs to '{}', not the current repository '{}'",
+                    backup_repo, current_repo
+                )
+            }
             RestoreError::Other(e) => write!(f, "{}", e),
         }
     }
@@ -316,6 +736,42 @@ impl std::fmt::Display for RestoreError {
 
 impl std::error::Error for RestoreError {}
 
+impl RestoreError {
+    /// The variant's name, for `--error-format json`; part of the output
+    /// scripts rely on, so never rename one
+    pub fn kind(&self) -> &'static str {
+        match self {
+            RestoreError::BranchExists { .. } => "BranchExists",
+            RestoreError::CommitNotFound { .. } => "CommitNotFound",
+            RestoreError::NotACommit { .. } => "NotACommit",
+            RestoreError::BranchNotInBackup { .. } => "BranchNotInBackup",
+            RestoreError::NoBranchesMatch { .. } => "NoBranchesMatch",
+            RestoreError::NoBackupsFound { .. } => "NoBackupsFound",
+            RestoreError::BackupCorrupted { .. } => "BackupCorrupted",
+            RestoreError::UnsupportedFormat { .. } => "UnsupportedFormat",
+            RestoreError::RepoMismatch { .. } => "RepoMismatch",
+            RestoreError::Other(_) => "Other",
+        }
+    }
+
+    /// The process exit status for this error, following on from
+    /// [`crate::error::DeadbranchError::exit_code`]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            RestoreError::BranchExists { .. } => 10,
+            RestoreError::CommitNotFound { .. } => 11,
+            RestoreError::NotACommit { .. } => 12,
+            RestoreError::BranchNotInBackup { .. } => 13,
+            RestoreError::NoBranchesMatch { .. } => 14,
+            RestoreError::NoBackupsFound { .. } => 15,
+            RestoreError::BackupCorrupted { .. } => 16,
+            RestoreError::UnsupportedFormat { .. } => 17,
+            RestoreError::RepoMismatch { .. } => 18,
+            RestoreError::Other(_) => 1,
+        }
+    }
+}
+
 /// Result of a cleanup operation
 #[derive(Debug)]
 pub struct CleanResult {
@@ -335,12 +791,49 @@ pub struct BackupToDelete {
 }
 
 impl BackupToDelete {
+    /// Wrap a backup, reading its size from disk
+    pub fn from_info(info: BackupInfo) -> Self {
+        let size_bytes = fs::metadata(&info.path).map(|m| m.len()).unwrap_or(0);
+        BackupToDelete { info, size_bytes }
+    }
+
     /// Format the size as human-readable string
     pub fn format_size(&self) -> String {
         format_bytes(self.size_bytes)
     }
 }
 
+impl Serialize for BackupToDelete {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut entry = serializer.serialize_struct("BackupToDelete", 5)?;
+        entry.serialize_field("filename", &self.info.filename())?;
+        entry.serialize_field("age", &self.info.format_age())?;
+        entry.serialize_field("age_days", &self.info.age_days())?;
+        entry.serialize_field("branch_count", &self.info.branch_count)?;
+        entry.serialize_field("size_bytes", &self.size_bytes)?;
+        entry.end()
+    }
+}
+
+/// What `backup clean --dry-run --format json` reports
+pub struct CleanPlan<'a> {
+    pub repo: &'a str,
+    pub keep: usize,
+    pub to_delete: &'a [BackupToDelete],
+}
+
+impl Serialize for CleanPlan<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let total_bytes: u64 = self.to_delete.iter().map(|b| b.size_bytes).sum();
+        let mut plan = serializer.serialize_struct("CleanPlan", 4)?;
+        plan.serialize_field("repo", self.repo)?;
+        plan.serialize_field("keep", &self.keep)?;
+        plan.serialize_field("to_delete", self.to_delete)?;
+        plan.serialize_field("total_bytes", &total_bytes)?;
+        plan.end()
+    }
+}
+
 /// Format bytes as human-readable string (e.g., "1.2 KB")
 pub fn format_bytes(bytes: u64) -> String {
     const KB: u64 = 1024;
@@ -356,14 +849,40 @@ pub fn format_bytes(bytes: u64) -> String {
 }
 
 /// Storage statistics for a single repository
-#[derive(Debug)]
+#[derive(Debug, serde::Serialize)]
 pub struct RepoStats {
     /// Repository name
+    #[serde(rename = "repo")]
     pub repo_name: String,
     /// Number of backup files
     pub backup_count: usize,
     /// Total size in bytes
     pub total_bytes: u64,
+    /// Whether the repository the backups came from still exists
+    pub status: RepoStatus,
+}
+
+/// Whether the repository a backup directory belongs to still exists on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RepoStatus {
+    /// The recorded repository path is still a git repository
+    Present,
+    /// The recorded repository path is gone or no longer a git repository
+    Orphaned,
+    /// No backup recorded a repository path (legacy backups)
+    Unknown,
+}
+
+impl RepoStatus {
+    /// Determine the status from the newest backup that recorded a repository path
+    fn from_backups(backups: &[BackupInfo]) -> Self {
+        match backups.iter().find_map(|b| b.repo_path.as_deref()) {
+            Some(path) if path.join(".git").exists() => RepoStatus::Present,
+            Some(_) => RepoStatus::Orphaned,
+            None => RepoStatus::Unknown,
+        }
+    }
 }
 
 /// Aggregated backup storage statistics
@@ -385,6 +904,30 @@ impl BackupStats {
     pub fn total_bytes(&self) -> u64 {
         self.repos.iter().map(|r| r.total_bytes).sum()
     }
+
+    /// Repositories whose recorded path no longer exists
+    pub fn orphaned_repos(&self) -> impl Iterator<Item = &RepoStats> {
+        self.repos
+            .iter()
+            .filter(|r| r.status == RepoStatus::Orphaned)
+    }
+
+    /// Size that could be reclaimed by removing backups of orphaned repositories
+    pub fn orphaned_bytes(&self) -> u64 {
+        self.orphaned_repos().map(|r| r.total_bytes).sum()
+    }
+}
+
+/// JSON shape for `backup stats --format json`: per-repository figures plus totals
+impl Serialize for BackupStats {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut stats = serializer.serialize_struct("BackupStats", 4)?;
+        stats.serialize_field("backups_dir", &self.backups_dir)?;
+        stats.serialize_field("repos", &self.repos)?;
+        stats.serialize_field("total_backups", &self.total_backups())?;
+        stats.serialize_field("total_bytes", &self.total_bytes())?;
+        stats.end()
+    }
 }
 
 /// Gather backup storage statistics across all repositories
@@ -404,6 +947,7 @@ pub fn get_backup_stats() -> Result<BackupStats> {
                 repo_name,
                 backup_count: backups.len(),
                 total_bytes,
+                status: RepoStatus::from_backups(&backups),
             }
         })
         .collect();
@@ -415,28 +959,45 @@ pub fn get_backup_stats() -> Result<BackupStats> {
 
 /// Identify backups to delete for a repository
 ///
-/// Returns backups that should be deleted (older ones beyond the keep count),
-/// sorted by timestamp (oldest first, i.e., first to delete).
-pub fn get_backups_to_clean(repo_name: &str, keep: usize) -> Result<Vec<BackupToDelete>> {
+/// See [`select_backups_for_cleanup`] for how `keep` and `keep_days` combine.
+/// Returned newest first, like `list_repo_backups`.
+pub fn get_backups_to_clean(
+    repo_name: &str,
+    keep: usize,
+    keep_days: Option<u32>,
+) -> Result<Vec<BackupToDelete>> {
     let backups = list_repo_backups(repo_name)?;
 
-    if backups.len() <= keep {
-        return Ok(Vec::new());
-    }
-
-    // Backups are already sorted newest-first, so skip the first `keep` entries
-    let to_delete: Vec<BackupToDelete> = backups
+    let to_delete: Vec<BackupToDelete> = select_backups_for_cleanup(backups, keep, keep_days)
         .into_iter()
-        .skip(keep)
-        .map(|info| {
-            let size_bytes = fs::metadata(&info.path).map(|m| m.len()).unwrap_or(0);
-            BackupToDelete { info, size_bytes }
-        })
+        .map(BackupToDelete::from_info)
         .collect();
 
     Ok(to_delete)
 }
 
+/// Choose which backups a cleanup removes
+///
+/// A backup is deleted only if it is beyond the `keep_count` newest AND older
+/// than `keep_days` days (when set), so either rule alone is enough to keep it.
+/// Backups are ordered by [`BackupInfo::order_key`], so the selection does not
+/// depend on directory order.
+pub fn select_backups_for_cleanup(
+    mut backups: Vec<BackupInfo>,
+    keep_count: usize,
+    keep_days: Option<u32>,
+) -> Vec<BackupInfo> {
+    backups.sort_by(|a, b| b.order_key().cmp(&a.order_key()));
+
+    let cutoff = keep_days.map(|days| Utc::now() - chrono::Duration::days(days as i64));
+
+    backups
+        .into_iter()
+        .skip(keep_count)
+        .filter(|b| cutoff.is_none_or(|cutoff| b.timestamp < cutoff))
+        .collect()
+}
+
 /// Delete backup files
 ///
 /// # Arguments
@@ -446,6 +1007,8 @@ pub fn get_backups_to_clean(repo_name: &str, keep: usize) -> Result<Vec<BackupTo
 /// * `Ok(CleanResult)` with deletion statistics
 /// * `Err` if deletion fails
 pub fn delete_backups(backups: &[BackupToDelete]) -> Result<CleanResult> {
+    crate::readonly::ensure_writable("delete backups")?;
+
     let mut deleted_count = 0;
     let mut bytes_freed = 0;
 
@@ -466,43 +1029,287 @@ pub fn delete_backups(backups: &[BackupToDelete]) -> Result<CleanResult> {
     })
 }
 
+/// First line of every backup file
+pub const BACKUP_HEADER: &str = "# deadbranch backup";
+
+/// Header line declaring the backup format version (absent in version 1 files)
+pub const FORMAT_HEADER: &str = "# Format:";
+
+/// Trailing line holding the SHA-256 of everything above it (format 3 on)
+pub const CHECKSUM_HEADER: &str = "# SHA256:";
+
+/// Prefix of the per-branch marker line rendered from a custom backup template
+pub const ENTRY_MARKER: &str = "# deadbranch-entry:";
+
+/// The line every `backups.template` must contain so restores can find each branch
+pub const TEMPLATE_MARKER_LINE: &str = "# deadbranch-entry: {name} {sha}";
+
+/// A custom per-branch block for backup files, read from `backups.template`.
+///
+/// `{name}`, `{sha}` and `{remote}` are substituted for each branch. The
+/// template must contain [`TEMPLATE_MARKER_LINE`]; restores read branches from
+/// the rendered marker lines and ignore everything else the template produced.
+#[derive(Debug, Clone)]
+pub struct BackupTemplate {
+    body: String,
+}
+
+impl BackupTemplate {
+    /// Read and check a template file
+    pub fn load(path: &Path) -> Result<Self> {
+        let body = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read backup template: {}", path.display()))?;
+        Self::new(body).with_context(|| format!("Invalid backup template: {}", path.display()))
+    }
+
+    fn new(body: String) -> Result<Self> {
+        if !body.lines().any(|l| l.trim_end() == TEMPLATE_MARKER_LINE) {
+            anyhow::bail!(
+                "it must contain the line `{}` so branches can be restored from it",
+                TEMPLATE_MARKER_LINE
+            );
+        }
+        Ok(Self { body })
+    }
+
+    /// Render the block for one branch, ending with a newline.
+    /// `remote` is empty for local branches without a configured remote.
+    pub fn render(&self, name: &str, sha: &str, remote: &str) -> String {
+        let mut rendered = String::with_capacity(self.body.len() + 64);
+        let mut rest = self.body.as_str();
+        // Substitute in one pass so placeholders inside a branch name stay literal
+        while let Some(start) = rest.find('{') {
+            rendered.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let value = [("{name}", name), ("{sha}", sha), ("{remote}", remote)]
+                .into_iter()
+                .find(|(placeholder, _)| rest.starts_with(placeholder));
+            match value {
+                Some((placeholder, value)) => {
+                    rendered.push_str(value);
+                    rest = &rest[placeholder.len()..];
+                }
+                None => {
+                    rendered.push('{');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        rendered.push_str(rest);
+        if !rendered.ends_with('\n') {
+            rendered.push('\n');
+        }
+        rendered
+    }
+}
+
+/// Backup format version written by this version of deadbranch.
+///
+/// - 1: header, `# <name>` comments and `git branch <name> <sha>` lines
+/// - 2: adds the `# Format:` line and `# branch.<name>.<key> = <value>` config lines
+/// - 3: adds the trailing `# SHA256: <hex>` checksum line
+pub const BACKUP_FORMAT_VERSION: u32 = 3;
+
+/// First format version whose files must end with a checksum line
+const CHECKSUM_FORMAT_VERSION: u32 = 3;
+
+/// Result of checking a backup file against its trailing checksum
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    /// The checksum matches the file contents
+    Valid,
+    /// Written before checksums existed, so there is nothing to check
+    NotRecorded,
+    /// The checksum line is missing or cut short: the file lost its tail
+    Truncated,
+    /// The checksum no longer matches: the file was changed after writing
+    Modified,
+}
+
+impl ChecksumStatus {
+    /// Why the file fails verification, if it does
+    pub fn problem(self) -> Option<&'static str> {
+        match self {
+            ChecksumStatus::Valid | ChecksumStatus::NotRecorded => None,
+            ChecksumStatus::Truncated => Some(
+                "checksum line is missing or incomplete; the file appears truncated (e.g. written to a full disk)",
+            ),
+            ChecksumStatus::Modified => {
+                Some("checksum does not match; the file was modified after it was written")
+            }
+        }
+    }
+}
+
+/// Build the trailing checksum line for the exact bytes written above it
+pub fn checksum_line(content: &[u8]) -> String {
+    format!("{} {}\n", CHECKSUM_HEADER, sha256_hex(content))
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// Check the trailing `# SHA256:` line of a backup file's contents.
+///
+/// The hash covers every byte before the checksum line, newlines included.
+fn verify_checksum(content: &str, version: u32) -> ChecksumStatus {
+    let without_newline = content.strip_suffix('\n').unwrap_or(content);
+    let (body, last_line) = match without_newline.rfind('\n') {
+        Some(i) => (&content[..=i], &without_newline[i + 1..]),
+        None => ("", without_newline),
+    };
+
+    let Some(recorded) = last_line.strip_prefix(CHECKSUM_HEADER) else {
+        // Content after the checksum line means the file was appended to
+        if content.lines().any(|l| l.starts_with(CHECKSUM_HEADER)) {
+            return ChecksumStatus::Modified;
+        }
+        return if version >= CHECKSUM_FORMAT_VERSION {
+            ChecksumStatus::Truncated
+        } else {
+            ChecksumStatus::NotRecorded
+        };
+    };
+
+    let recorded = recorded.trim();
+    if recorded == sha256_hex(body.as_bytes()) {
+        ChecksumStatus::Valid
+    } else if recorded.len() < 64 {
+        ChecksumStatus::Truncated
+    } else {
+        ChecksumStatus::Modified
+    }
+}
+
 /// Parse a backup file and extract branch entries
 ///
 /// The backup format has lines like:
 /// ```
 /// # feature/old-api
+/// # branch.feature/old-api.description = Rework the old API
 /// git branch feature/old-api a1b2c3d4...
 /// ```
 ///
 /// Lines that don't match the expected format (but aren't comments/empty) are
 /// tracked as skipped lines rather than causing a parse failure.
 pub fn parse_backup_file(path: &Path) -> Result<ParsedBackup, RestoreError> {
-    let file = fs::File::open(path).map_err(|e| RestoreError::Other(e.into()))?;
-    let reader = std::io::BufReader::new(file);
+    let content = read_backup(path)?;
+    parse_backup_content(&content)
+}
+
+/// Parse the contents of a backup file, dispatching on its declared format version
+fn parse_backup_content(content: &str) -> Result<ParsedBackup, RestoreError> {
+    let lines: Vec<&str> = content.lines().collect();
+
+    let Some(first) = lines.first() else {
+        return Err(RestoreError::BackupCorrupted {
+            message: "Empty or invalid backup file".to_string(),
+        });
+    };
+
+    // Check for valid header on the first line
+    if !first.starts_with(BACKUP_HEADER) {
+        return Err(RestoreError::BackupCorrupted {
+            message: format!(
+                "Invalid header at line 1. Expected '{}', found: '{}'",
+                BACKUP_HEADER, first
+            ),
+        });
+    }
+
+    let version = backup_format_version(&lines)?;
+    if version > BACKUP_FORMAT_VERSION {
+        return Err(RestoreError::UnsupportedFormat { version });
+    }
 
+    if let Some(problem) = verify_checksum(content, version).problem() {
+        return Err(RestoreError::BackupCorrupted {
+            message: problem.to_string(),
+        });
+    }
+
+    Ok(parse_entries(&lines, version >= 2))
+}
+
+/// Read the `# Format: N` line from the leading comment block (version 1 if absent)
+fn backup_format_version(lines: &[&str]) -> Result<u32, RestoreError> {
+    for line in lines.iter().skip(1).take_while(|l| l.starts_with('#')) {
+        if let Some(version) = line.strip_prefix(FORMAT_HEADER) {
+            let version = version.trim();
+            return version.parse().map_err(|_| RestoreError::BackupCorrupted {
+                message: format!("Invalid format version: '{}'", version),
+            });
+        }
+    }
+    Ok(1)
+}
+
+/// Parse branch entries after the header line. Config comment lines are only
+/// recognised from format version 2 on; in version 1 they are plain comments.
+///
+/// Backups rendered from a custom template are read from their
+/// `# deadbranch-entry: <name> <sha>` marker lines alone; the template's own
+/// commands are not ours to check.
+fn parse_entries(lines: &[&str], with_config: bool) -> ParsedBackup {
+    let templated = lines.iter().any(|l| l.starts_with(ENTRY_MARKER));
     let mut entries = Vec::new();
-    let mut skipped_lines = Vec::new();
-    let mut found_header = false;
-
-    for (line_num, line) in reader.lines().enumerate() {
-        let line = line.map_err(|e| RestoreError::Other(e.into()))?;
-
-        // Check for valid header on first non-empty line
-        if line_num == 0 {
-            if !line.starts_with("# deadbranch backup") {
-                return Err(RestoreError::BackupCorrupted {
-                    message: format!(
-                        "Invalid header at line 1. Expected '# deadbranch backup', found: '{}'",
-                        line
-                    ),
-                });
-            }
-            found_header = true;
-            continue;
+    let mut skipped = SkippedLines::default();
+    // Config comments precede the `git branch` line they belong to, and the
+    // `# <full name>` comment precedes both
+    let mut pending_config = Vec::new();
+    let mut pending_full_name = None;
+
+    // Repository metadata lives in the leading comment block
+    let mut repository = None;
+    let mut repo_path = None;
+    let mut label = None;
+    for line in lines.iter().skip(1).take_while(|l| l.starts_with('#')) {
+        if let Some(name) = line.strip_prefix(REPOSITORY_HEADER) {
+            repository = Some(name.trim().to_string()).filter(|n| !n.is_empty());
+        } else if let Some(path) = line.strip_prefix(REPO_PATH_HEADER) {
+            repo_path = Some(PathBuf::from(path.trim())).filter(|p| !p.as_os_str().is_empty());
+        } else if let Some(text) = line.strip_prefix(LABEL_HEADER) {
+            label = Some(text.trim().to_string()).filter(|l| !l.is_empty());
         }
+    }
 
-        // Skip comments and empty lines
-        if line.starts_with('#') || line.trim().is_empty() {
+    for (line_num, line) in lines.iter().enumerate().skip(1) {
+        if line.len() > MAX_LINE_LEN {
+            skipped.push(line_num + 1, line);
+            continue;
+        }
+
+        if with_config {
+            if let Some(entry) = parse_config_comment(line) {
+                pending_config.push(entry);
+                continue;
+            }
+        }
+
+        if let Some(entry) = line.strip_prefix(ENTRY_MARKER) {
+            let parts: Vec<&str> = entry.split_whitespace().collect();
+            if let [name, sha] = parts[..] {
+                entries.push(BackupBranchEntry {
+                    name: name.to_string(),
+                    commit_sha: sha.to_string(),
+                    config: std::mem::take(&mut pending_config),
+                    remote: entry_remote(pending_full_name.take(), name),
+                });
+            } else {
+                skipped.push(line_num + 1, line);
+            }
+            continue;
+        }
+
+        // Skip comments and empty lines (and a template's own lines)
+        if let Some(comment) = line.strip_prefix("# ") {
+            pending_full_name = Some(comment.trim()).filter(|c| !c.contains(char::is_whitespace));
+        }
+        if line.starts_with('#') || line.trim().is_empty() || templated {
             continue;
         }
 
@@ -514,33 +1321,232 @@ pub fn parse_backup_file(path: &Path) -> Result<ParsedBackup, RestoreError> {
                 entries.push(BackupBranchEntry {
                     name: parts[2].to_string(),
                     commit_sha: parts[3].to_string(),
+                    config: std::mem::take(&mut pending_config),
+                    remote: entry_remote(pending_full_name.take(), parts[2]),
                 });
             } else {
                 // Malformed "git branch" line - track as skipped
-                skipped_lines.push(SkippedLine {
-                    line_number: line_num + 1,
-                    content: line,
-                });
+                skipped.push(line_num + 1, line);
             }
         } else {
             // Line doesn't match expected format - track as skipped
-            skipped_lines.push(SkippedLine {
-                line_number: line_num + 1,
-                content: line,
+            skipped.push(line_num + 1, line);
+        }
+    }
+
+    ParsedBackup {
+        entries,
+        skipped_lines: skipped.lines,
+        skipped_line_count: skipped.count,
+        repository,
+        repo_path,
+        label,
+    }
+}
+
+/// The remote an entry was deleted from, from the `# origin/feature` comment
+/// written above a remote branch's entry (a local branch's comment is just
+/// its name)
+fn entry_remote(full_name: Option<&str>, name: &str) -> Option<String> {
+    let remote = full_name?.strip_suffix(name)?.strip_suffix('/')?;
+    (!remote.is_empty()).then(|| remote.to_string())
+}
+
+/// Skipped lines collected while parsing, keeping only the first few hundred
+#[derive(Default)]
+struct SkippedLines {
+    lines: Vec<SkippedLine>,
+    count: usize,
+}
+
+impl SkippedLines {
+    fn push(&mut self, line_number: usize, line: &str) {
+        self.count += 1;
+        if self.lines.len() < MAX_SKIPPED_LINES {
+            // Keep enough of an overlong line to recognise it, not all of it
+            let end = (0..=line.len().min(200))
+                .rev()
+                .find(|&i| line.is_char_boundary(i))
+                .unwrap_or(0);
+            self.lines.push(SkippedLine {
+                line_number,
+                content: line[..end].to_string(),
             });
         }
     }
+}
 
-    if !found_header {
-        return Err(RestoreError::BackupCorrupted {
-            message: "Empty or invalid backup file".to_string(),
-        });
+/// Outcome of migrating a single backup file
+#[derive(Debug)]
+pub enum MigrateOutcome {
+    /// The file was rewritten; the original was kept alongside as `<file>.bak`
+    Migrated,
+    /// The file already uses the current format
+    AlreadyCurrent,
+}
+
+/// Outcome of `backup verify` for a single file
+#[derive(Debug)]
+pub struct VerifyResult {
+    /// The backup that was checked
+    pub info: BackupInfo,
+    /// Why the backup cannot be used for restores (checksum or parse failure)
+    pub error: Option<String>,
+}
+
+/// Check that a backup is intact and can be parsed for restores
+pub fn verify_backup(info: BackupInfo) -> VerifyResult {
+    let error = parse_backup_file(&info.path).err().map(|e| e.to_string());
+    VerifyResult { info, error }
+}
+
+/// Rewrite an older backup file in the current format, keeping the original as `<file>.bak`
+pub fn migrate_backup_file(path: &Path) -> Result<MigrateOutcome> {
+    let content = read_backup(path)
+        .with_context(|| format!("Failed to read backup file: {}", path.display()))?;
+
+    // Validate the file and find its version before touching anything
+    parse_backup_content(&content)?;
+    let lines: Vec<&str> = content.lines().collect();
+    let version = backup_format_version(&lines)?;
+    if version == BACKUP_FORMAT_VERSION {
+        return Ok(MigrateOutcome::AlreadyCurrent);
     }
 
-    Ok(ParsedBackup {
-        entries,
-        skipped_lines,
-    })
+    // Declare the current format right after the header line (replacing any
+    // older declaration), then seal the result with a checksum
+    let mut migrated = String::with_capacity(content.len() + 96);
+    for (i, line) in lines.iter().enumerate() {
+        if line.starts_with(FORMAT_HEADER) {
+            continue;
+        }
+        migrated.push_str(line);
+        migrated.push('\n');
+        if i == 0 {
+            migrated.push_str(&format!("{} {}\n", FORMAT_HEADER, BACKUP_FORMAT_VERSION));
+        }
+    }
+    let checksum = checksum_line(migrated.as_bytes());
+    migrated.push_str(&checksum);
+
+    crate::readonly::ensure_writable(&format!("migrate {}", path.display()))?;
+
+    let mut backup_name = path.as_os_str().to_owned();
+    backup_name.push(".bak");
+    let backup_path = PathBuf::from(backup_name);
+    fs::copy(path, &backup_path)
+        .with_context(|| format!("Failed to write {}", backup_path.display()))?;
+    atomic_write::write(path, migrated.as_bytes())
+        .with_context(|| format!("Failed to write backup file: {}", path.display()))?;
+
+    Ok(MigrateOutcome::Migrated)
+}
+
+/// Resolve which backup file to use for a repository.
+///
+/// Accepts `latest`, a 1-based index matching the numbering of
+/// `backup list --current` (newest first), a bare filename looked up in the
+/// repo's backup directory, or an absolute/existing path used as-is. With no
+/// file given, the most recent backup is used.
+pub fn resolve_backup_path(
+    repo_name: &str,
+    backup_file: Option<&str>,
+) -> Result<PathBuf, RestoreError> {
+    match backup_file {
+        None | Some("latest") => {
+            // Use most recent backup
+            let backups = list_repo_backups(repo_name).map_err(RestoreError::Other)?;
+
+            backups
+                .into_iter()
+                .next()
+                .map(|info| info.path)
+                .ok_or_else(|| RestoreError::NoBackupsFound {
+                    repo_name: repo_name.to_string(),
+                })
+        }
+        Some(index) if !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()) => {
+            let backups = list_repo_backups(repo_name).map_err(RestoreError::Other)?;
+            if backups.is_empty() {
+                return Err(RestoreError::NoBackupsFound {
+                    repo_name: repo_name.to_string(),
+                });
+            }
+
+            let count = backups.len();
+            index
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| n.checked_sub(1))
+                .and_then(|i| backups.into_iter().nth(i))
+                .map(|info| info.path)
+                .ok_or_else(|| {
+                    RestoreError::Other(anyhow::anyhow!(
+                        "No backup #{} for repository '{}' ({} available, see 'deadbranch backup list --current')",
+                        index,
+                        repo_name,
+                        count
+                    ))
+                })
+        }
+        Some(filename) => {
+            // If it's just a filename, look in the repo's backup directory
+            let path = PathBuf::from(filename);
+            if path.is_absolute() || path.exists() {
+                Ok(path)
+            } else {
+                // Look in the repo's backup directory
+                let backup_dir = Config::repo_backup_dir(repo_name).map_err(RestoreError::Other)?;
+                Ok(backup_dir.join(filename))
+            }
+        }
+    }
+}
+
+/// Check that a backup taken from outside the repo's backup directory was
+/// recorded for the current repository.
+///
+/// The recorded toplevel path is compared when available, otherwise the
+/// recorded repository name. Backups without metadata cannot be checked and pass.
+fn check_repo_match(
+    repo_name: &str,
+    backup_path: &Path,
+    parsed: &ParsedBackup,
+) -> Result<(), RestoreError> {
+    let in_repo_dir = Config::repo_backup_dir(repo_name)
+        .ok()
+        .zip(backup_path.parent())
+        .is_some_and(|(dir, parent)| same_path(&dir, parent));
+    if in_repo_dir {
+        return Ok(());
+    }
+
+    let mismatch = match (&parsed.repo_path, crate::git::get_repo_dir()) {
+        (Some(recorded), Some(current)) => {
+            (!same_path(recorded, &current)).then(|| recorded.display().to_string())
+        }
+        _ => parsed
+            .repository
+            .as_ref()
+            .filter(|recorded| *recorded != repo_name)
+            .cloned(),
+    };
+
+    match mismatch {
+        Some(backup_repo) => Err(RestoreError::RepoMismatch {
+            backup_repo,
+            current_repo: repo_name.to_string(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Compare two paths, resolving symlinks and relative components when possible
+fn same_path(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a == b,
+    }
 }
 
 /// Restore a branch from a backup
@@ -550,6 +1556,8 @@ pub fn parse_backup_file(path: &Path) -> Result<ParsedBackup, RestoreError> {
 /// * `backup_file` - Optional path to a specific backup file. If None, uses most recent backup.
 /// * `target_name` - Optional alternate name for the restored branch (--as flag)
 /// * `force` - Whether to overwrite an existing branch
+/// * `source_repo` - Repository whose backups to search (--repo); the current one if None
+/// * `ignore_repo_mismatch` - Restore even if the backup was recorded for another repository
 ///
 /// # Returns
 /// * `Ok(RestoreResult)` on success
@@ -559,47 +1567,23 @@ pub fn restore_branch(
     backup_file: Option<&str>,
     target_name: Option<&str>,
     force: bool,
+    source_repo: Option<&str>,
+    ignore_repo_mismatch: bool,
 ) -> Result<RestoreResult, RestoreError> {
     let repo_name = Config::get_repo_name();
+    let backup_repo = check_source_repo(&repo_name, source_repo, ignore_repo_mismatch)?;
 
     // Determine the final branch name
     let final_branch_name = target_name.unwrap_or(branch_name);
 
-    // Check if branch already exists
-    let branch_exists = check_branch_exists(final_branch_name);
-
-    if branch_exists && !force {
+    // Fail before reading any backup when the branch already exists
+    if !force && check_branch_exists(final_branch_name) {
         return Err(RestoreError::BranchExists {
             branch_name: final_branch_name.to_string(),
         });
     }
 
-    // Determine which backup file to use
-    let backup_path = if let Some(filename) = backup_file {
-        // If it's just a filename, look in the repo's backup directory
-        let path = PathBuf::from(filename);
-        if path.is_absolute() || path.exists() {
-            path
-        } else {
-            // Look in the repo's backup directory
-            let backup_dir = Config::repo_backup_dir(&repo_name).map_err(RestoreError::Other)?;
-            backup_dir.join(filename)
-        }
-    } else {
-        // Use most recent backup
-        let backups = list_repo_backups(&repo_name).map_err(RestoreError::Other)?;
-
-        backups
-            .into_iter()
-            .next()
-            .map(|info| info.path)
-            .ok_or_else(|| RestoreError::NoBackupsFound {
-                repo_name: repo_name.clone(),
-            })?
-    };
-
-    // Parse the backup file
-    let parsed = parse_backup_file(&backup_path)?;
+    let (_, parsed) = open_backup(&repo_name, backup_repo, backup_file, ignore_repo_mismatch)?;
 
     // Find the branch in the backup
     let entry = parsed
@@ -610,53 +1594,251 @@ pub fn restore_branch(
             branch_name: branch_name.to_string(),
             available_branches: parsed.entries.clone(),
             skipped_lines: parsed.skipped_lines.clone(),
+            skipped_line_count: parsed.skipped_line_count,
         })?;
 
-    // Check if the commit exists
-    if !commit_exists(&entry.commit_sha) {
-        return Err(RestoreError::CommitNotFound {
-            branch_name: branch_name.to_string(),
-            commit_sha: entry.commit_sha.clone(),
+    restore_entry(entry, final_branch_name, force)
+}
+
+/// Find the entries of a backup whose names match `pattern` (a glob, as in
+/// `branches.protected`), for `backup restore --matching`. A name recorded
+/// twice, like a local branch and its remote copy, is selected once, as
+/// [`restore_branch`] would pick it.
+///
+/// Returns the backup file and the entries in the order they were recorded.
+pub fn find_matching_entries(
+    pattern: &str,
+    backup_file: Option<&str>,
+    source_repo: Option<&str>,
+    ignore_repo_mismatch: bool,
+) -> Result<(PathBuf, Vec<BackupBranchEntry>), RestoreError> {
+    crate::branch::parse_glob(pattern).map_err(|reason| {
+        RestoreError::Other(anyhow::anyhow!("Invalid pattern '{}': {}", pattern, reason))
+    })?;
+
+    let repo_name = Config::get_repo_name();
+    let backup_repo = check_source_repo(&repo_name, source_repo, ignore_repo_mismatch)?;
+    let (backup_path, parsed) =
+        open_backup(&repo_name, backup_repo, backup_file, ignore_repo_mismatch)?;
+
+    let mut entries: Vec<BackupBranchEntry> = Vec::new();
+    for entry in &parsed.entries {
+        if Branch::glob_match(pattern, &entry.name) && !entries.iter().any(|e| e.name == entry.name)
+        {
+            entries.push(entry.clone());
+        }
+    }
+
+    if entries.is_empty() {
+        return Err(RestoreError::NoBranchesMatch {
+            pattern: pattern.to_string(),
+            available_branches: parsed.entries,
+            skipped_lines: parsed.skipped_lines,
+            skipped_line_count: parsed.skipped_line_count,
+        });
+    }
+    Ok((backup_path, entries))
+}
+
+/// Refuse another repository's backups (`--repo`) unless the mismatch is
+/// accepted, since they would restore its commits into this one. Returns the
+/// repository whose backups to read.
+fn check_source_repo<'a>(
+    repo_name: &'a str,
+    source_repo: Option<&'a str>,
+    ignore_repo_mismatch: bool,
+) -> Result<&'a str, RestoreError> {
+    let backup_repo = source_repo.unwrap_or(repo_name);
+    if backup_repo != repo_name && !ignore_repo_mismatch {
+        return Err(RestoreError::RepoMismatch {
+            backup_repo: backup_repo.to_string(),
+            current_repo: repo_name.to_string(),
         });
     }
+    Ok(backup_repo)
+}
+
+/// Resolve and parse the backup to restore from, checking that it was
+/// recorded for the current repository unless the mismatch is accepted
+fn open_backup(
+    repo_name: &str,
+    backup_repo: &str,
+    backup_file: Option<&str>,
+    ignore_repo_mismatch: bool,
+) -> Result<(PathBuf, ParsedBackup), RestoreError> {
+    let backup_path = resolve_backup_path(backup_repo, backup_file)?;
+    let parsed = parse_backup_file(&backup_path)?;
+
+    if !ignore_repo_mismatch {
+        check_repo_match(repo_name, &backup_path, &parsed)?;
+    }
+    Ok((backup_path, parsed))
+}
+
+/// Recreate the branch a backup entry records, as `target_name`, and
+/// re-apply its config. An existing branch is only overwritten with `force`.
+pub fn restore_entry(
+    entry: &BackupBranchEntry,
+    target_name: &str,
+    force: bool,
+) -> Result<RestoreResult, RestoreError> {
+    let branch_exists = check_branch_exists(target_name);
+    if branch_exists && !force {
+        return Err(RestoreError::BranchExists {
+            branch_name: target_name.to_string(),
+        });
+    }
+
+    // Check if the commit exists
+    let commit = match resolve_object(&entry.commit_sha) {
+        None => {
+            return Err(RestoreError::CommitNotFound {
+                branch_name: entry.name.clone(),
+                commit_sha: entry.commit_sha.clone(),
+            })
+        }
+        Some((_, object_type)) if object_type != "commit" => {
+            return Err(RestoreError::NotACommit {
+                branch_name: entry.name.clone(),
+                commit_sha: entry.commit_sha.clone(),
+                object_type,
+            })
+        }
+        Some((full, _)) => full,
+    };
 
     // Create or update the branch
-    create_branch(final_branch_name, &entry.commit_sha, force).map_err(RestoreError::Other)?;
+    create_branch(target_name, &commit, force).map_err(RestoreError::Other)?;
+
+    let (restored_config, skipped_config) = apply_branch_config(target_name, &entry.config);
+
+    // Older backups kept the remote in the name (`origin/feature`)
+    let deleted_from = match &entry.remote {
+        Some(remote) => Some((remote.clone(), entry.name.clone())),
+        None => crate::git::split_remote_name(
+            &entry.name,
+            &crate::git::list_remotes().unwrap_or_default(),
+        )
+        .map(|(remote, name)| (remote.to_string(), name.to_string())),
+    };
 
     Ok(RestoreResult {
-        original_name: branch_name.to_string(),
-        restored_name: final_branch_name.to_string(),
+        original_name: entry.name.clone(),
+        restored_name: target_name.to_string(),
         commit_sha: entry.commit_sha.clone(),
         overwrote_existing: branch_exists && force,
+        restored_config,
+        skipped_config,
+        deleted_from,
     })
 }
 
-/// Check if a local branch exists
-fn check_branch_exists(branch_name: &str) -> bool {
-    Command::new("git")
-        .args([
-            "rev-parse",
-            "--verify",
-            &format!("refs/heads/{}", branch_name),
-        ])
+/// Re-apply recorded `branch.<name>.*` config to a restored branch.
+///
+/// Keys referencing a remote that is no longer configured (`remote`,
+/// `pushremote`) or an upstream ref that no longer exists (`merge`) are
+/// skipped. Returns `(applied_keys, skipped_keys)`.
+fn apply_branch_config(
+    branch_name: &str,
+    config: &[(String, String)],
+) -> (Vec<String>, Vec<String>) {
+    let mut applied = Vec::new();
+    let mut skipped = Vec::new();
+
+    let upstream_remote = config
+        .iter()
+        .find(|(key, _)| key == "remote")
+        .map(|(_, value)| value.as_str());
+
+    for (key, value) in config {
+        let still_valid = match key.as_str() {
+            "remote" | "pushremote" => value == "." || remote_exists(value),
+            "merge" => upstream_exists(upstream_remote, value),
+            _ => true,
+        };
+
+        if still_valid && set_branch_config(branch_name, key, value) {
+            applied.push(key.clone());
+        } else {
+            skipped.push(key.clone());
+        }
+    }
+
+    (applied, skipped)
+}
+
+/// Check if a remote is configured
+fn remote_exists(remote: &str) -> bool {
+    perf::git()
+        .args(["remote", "get-url", remote])
         .output()
         .map(|output| output.status.success())
         .unwrap_or(false)
 }
 
-/// Check if a commit exists in the repository
-fn commit_exists(sha: &str) -> bool {
-    Command::new("git")
-        .args(["cat-file", "-t", sha])
+/// Check if the ref named by `branch.<name>.merge` still resolves.
+fn upstream_exists(remote: Option<&str>, merge_ref: &str) -> bool {
+    let full_ref = crate::git::upstream_ref(remote, merge_ref);
+
+    perf::git()
+        .args(["show-ref", "--verify", "--quiet", &full_ref])
         .output()
-        .map(|output| {
-            output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "commit"
-        })
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+/// Set a single `branch.<name>.<key>` config value
+fn set_branch_config(branch_name: &str, key: &str, value: &str) -> bool {
+    if crate::readonly::is_enabled() {
+        return false;
+    }
+    crate::git::run_local_git(&["config", &format!("branch.{}.{}", branch_name, key), value])
+        .map(|output| output.status.success())
         .unwrap_or(false)
 }
 
+/// Check if a local branch exists, comparing names case-sensitively.
+///
+/// Looking the ref up directly can find `Feature/X` when asked for `feature/x`
+/// on case-insensitive filesystems, so the stored names are compared instead.
+fn check_branch_exists(branch_name: &str) -> bool {
+    crate::git::ref_names("refs/heads/")
+        .is_ok_and(|names| names.iter().any(|name| name == branch_name))
+}
+
+/// The full SHA and type (`commit`, `tag`, ...) of the object `sha` names,
+/// if it exists.
+///
+/// A tag or branch called like an abbreviated SHA takes precedence in git's
+/// lookup, so the result must actually start with `sha` to count.
+fn resolve_object(sha: &str) -> Option<(String, String)> {
+    let git = |args: &[&str]| {
+        perf::git()
+            .args(args)
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+    };
+
+    let full = git(&[
+        "rev-parse",
+        "--verify",
+        "--quiet",
+        "--end-of-options",
+        &format!("{}^{{object}}", sha),
+    ])?;
+    if !full.starts_with(&sha.to_ascii_lowercase()) {
+        return None;
+    }
+    let object_type = git(&["cat-file", "-t", &full])?;
+    Some((full, object_type))
+}
+
 /// Create a branch at a specific commit
 fn create_branch(branch_name: &str, commit_sha: &str, force: bool) -> Result<()> {
+    crate::readonly::ensure_writable(&format!("create branch '{}'", branch_name))?;
+
     let mut args = vec!["branch"];
     if force {
         args.push("-f");
@@ -664,10 +1846,7 @@ fn create_branch(branch_name: &str, commit_sha: &str, force: bool) -> Result<()>
     args.push(branch_name);
     args.push(commit_sha);
 
-    let output = Command::new("git")
-        .args(&args)
-        .output()
-        .context("Failed to run git branch command")?;
+    let output = crate::git::run_local_git(&args).context("Failed to run git branch command")?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -684,6 +1863,7 @@ fn create_branch(branch_name: &str, commit_sha: &str, force: bool) -> Result<()>
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
     use std::io::Write;
     use tempfile::TempDir;
 
@@ -705,6 +1885,38 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_timestamp_from_suffixed_filename() {
+        let path = PathBuf::from("/some/path/backup-20260201-143022-2.txt");
+        let ts = parse_timestamp_from_filename(&path).unwrap();
+        assert_eq!(
+            ts.format("%Y-%m-%d %H:%M:%S").to_string(),
+            "2026-02-01 14:30:22"
+        );
+
+        let path = PathBuf::from("/some/path/backup-20260201-143022-x.txt");
+        assert!(parse_timestamp_from_filename(&path).is_none());
+    }
+
+    #[test]
+    fn test_write_new_backup_never_overwrites() {
+        let temp_dir = TempDir::new().unwrap();
+        let created = Utc.with_ymd_and_hms(2026, 2, 1, 14, 30, 22).unwrap();
+
+        let first = write_new_backup(temp_dir.path(), created, b"first").unwrap();
+        let second = write_new_backup(temp_dir.path(), created, b"second").unwrap();
+        let third = write_new_backup(temp_dir.path(), created, b"third").unwrap();
+
+        assert!(first.ends_with("backup-20260201-143022.txt"));
+        assert!(second.ends_with("backup-20260201-143022-2.txt"));
+        assert!(third.ends_with("backup-20260201-143022-3.txt"));
+        // Nothing NTFS refuses in a file name (`:` in particular)
+        let name = first.file_name().unwrap().to_str().unwrap();
+        assert!(!name.contains(['<', '>', ':', '"', '/', '\\', '|', '?', '*']));
+        assert_eq!(fs::read_to_string(&first).unwrap(), "first");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "second");
+    }
+
     #[test]
     fn test_parse_timestamp_invalid_filename() {
         let path = PathBuf::from("/some/path/not-a-backup.txt");
@@ -736,6 +1948,52 @@ git branch bugfix/login e5f6g7h8
         assert_eq!(info.timestamp.format("%Y-%m-%d").to_string(), "2026-02-01");
     }
 
+    #[test]
+    fn test_parse_backup_file_with_branch_config() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = format!(
+            "# deadbranch backup\n# Format: 2\n\n# feature/auth\n{}\n{}\ngit branch feature/auth a1b2c3d4\n\n# plain\ngit branch plain e5f6a7b8\n",
+            format_config_comment("feature/auth", "description", "Auth rework\nsecond line"),
+            format_config_comment("feature/auth", "merge", "refs/heads/feature/auth"),
+        );
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", &content);
+
+        let parsed = parse_backup_file(&path).unwrap();
+        assert_eq!(parsed.entries.len(), 2);
+        assert!(parsed.skipped_lines.is_empty());
+
+        let auth = &parsed.entries[0];
+        assert_eq!(auth.description(), Some("Auth rework\nsecond line"));
+        assert_eq!(
+            auth.config[1],
+            ("merge".to_string(), "refs/heads/feature/auth".to_string())
+        );
+
+        // Config must not leak into the following entry
+        assert!(parsed.entries[1].config.is_empty());
+    }
+
+    #[test]
+    fn test_parse_backup_records_the_remote_of_remote_entries() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "# deadbranch backup\n# Format: 2\n\n# upstream/team/api\ngit branch team/api a1b2c3d4\n\n# team/local\ngit branch team/local e5f6a7b8\n\ngit branch bare 9f8e7d6c\n";
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", content);
+
+        let parsed = parse_backup_file(&path).unwrap();
+        let remotes: Vec<Option<&str>> =
+            parsed.entries.iter().map(|e| e.remote.as_deref()).collect();
+        assert_eq!(remotes, [Some("upstream"), None, None]);
+    }
+
+    #[test]
+    fn test_config_comment_round_trip_escapes() {
+        let line = format_config_comment("release.1.0", "description", "a\\b\nc");
+        assert!(!line.contains('\n'));
+        let (key, value) = parse_config_comment(&line).unwrap();
+        assert_eq!(key, "description");
+        assert_eq!(value, "a\\b\nc");
+    }
+
     #[test]
     fn test_backup_info_format_age() {
         let info = BackupInfo {
@@ -743,12 +2001,162 @@ git branch bugfix/login e5f6g7h8
             repo_name: "test".to_string(),
             timestamp: Utc::now() - chrono::Duration::hours(2),
             branch_count: 5,
+            repo_path: None,
+            label: None,
+            checksum: ChecksumStatus::NotRecorded,
         };
 
         let age = info.format_age();
         assert!(age.contains("hour"));
     }
 
+    fn info_at(filename: &str, days_ago: i64) -> BackupInfo {
+        BackupInfo {
+            path: PathBuf::from(format!("/backups/{}", filename)),
+            repo_name: "test".to_string(),
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            branch_count: 1,
+            repo_path: None,
+            label: None,
+            checksum: ChecksumStatus::NotRecorded,
+        }
+    }
+
+    fn selected_names(selected: &[BackupInfo]) -> Vec<String> {
+        selected.iter().map(|b| b.filename()).collect()
+    }
+
+    #[test]
+    fn test_unused_backup_path_adds_a_suffix() {
+        let dir = TempDir::new().unwrap();
+        let dir = dir.path();
+        let name = "backup-20250101-120000.txt";
+        assert_eq!(unused_backup_path(dir, name), dir.join(name));
+        fs::write(dir.join(name), "").unwrap();
+        fs::write(dir.join("backup-20250101-120000-2.txt"), "").unwrap();
+        assert_eq!(
+            unused_backup_path(dir, name),
+            dir.join("backup-20250101-120000-3.txt")
+        );
+        assert_eq!(
+            unused_backup_path(dir, "backup-20250101-120000-2.txt"),
+            dir.join("backup-20250101-120000-3.txt")
+        );
+    }
+
+    #[test]
+    fn test_same_second_backups_order_by_suffix() {
+        let at = Utc::now();
+        let backups = [
+            "backup-20250101-120000-2.txt",
+            "backup-20250101-120000-10.txt",
+            "backup-20250101-120000.txt",
+            "backup-20250101-120000-3.txt",
+        ]
+        .into_iter()
+        .map(|name| BackupInfo {
+            timestamp: at,
+            ..info_at(name, 0)
+        })
+        .collect();
+        let selected = select_backups_for_cleanup(backups, 1, None);
+        assert_eq!(
+            selected_names(&selected),
+            vec![
+                "backup-20250101-120000-3.txt",
+                "backup-20250101-120000-2.txt",
+                "backup-20250101-120000.txt"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_select_for_cleanup_no_backups() {
+        assert!(select_backups_for_cleanup(Vec::new(), 10, None).is_empty());
+        assert!(select_backups_for_cleanup(Vec::new(), 0, Some(30)).is_empty());
+    }
+
+    #[test]
+    fn test_select_for_cleanup_exactly_keep_count() {
+        let backups = vec![info_at("a", 100), info_at("b", 200), info_at("c", 300)];
+        assert!(select_backups_for_cleanup(backups.clone(), 3, None).is_empty());
+        assert!(select_backups_for_cleanup(backups, 3, Some(30)).is_empty());
+    }
+
+    #[test]
+    fn test_select_for_cleanup_count_only() {
+        // Input order does not matter; the newest two are kept
+        let backups = vec![info_at("old", 30), info_at("new", 1), info_at("mid", 10)];
+        let selected = select_backups_for_cleanup(backups, 2, None);
+        assert_eq!(selected_names(&selected), vec!["old"]);
+    }
+
+    #[test]
+    fn test_select_for_cleanup_all_older() {
+        let backups = vec![
+            info_at("a", 100),
+            info_at("b", 110),
+            info_at("c", 120),
+            info_at("d", 130),
+        ];
+        let selected = select_backups_for_cleanup(backups, 1, Some(90));
+        assert_eq!(selected_names(&selected), vec!["b", "c", "d"]);
+    }
+
+    #[test]
+    fn test_select_for_cleanup_all_newer() {
+        let backups = vec![info_at("a", 1), info_at("b", 2), info_at("c", 3)];
+        // Beyond the keep count, but still inside the age window
+        assert!(select_backups_for_cleanup(backups, 1, Some(90)).is_empty());
+    }
+
+    #[test]
+    fn test_select_for_cleanup_requires_both_criteria() {
+        let backups = vec![
+            info_at("a", 1),
+            info_at("b", 50),
+            info_at("c", 100),
+            info_at("d", 200),
+        ];
+        // "c" is old enough but within the 3 newest; only "d" fails both rules
+        let selected = select_backups_for_cleanup(backups.clone(), 3, Some(90));
+        assert_eq!(selected_names(&selected), vec!["d"]);
+
+        // "b" is beyond the keep count but newer than 90 days
+        let selected = select_backups_for_cleanup(backups, 1, Some(90));
+        assert_eq!(selected_names(&selected), vec!["c", "d"]);
+    }
+
+    #[test]
+    fn test_select_for_cleanup_keep_zero() {
+        let backups = vec![info_at("a", 1), info_at("b", 100)];
+        assert_eq!(
+            selected_names(&select_backups_for_cleanup(backups.clone(), 0, None)),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            selected_names(&select_backups_for_cleanup(backups, 0, Some(90))),
+            vec!["b"]
+        );
+    }
+
+    #[test]
+    fn test_select_for_cleanup_ties_on_timestamp() {
+        let ts = Utc::now() - chrono::Duration::days(100);
+        let mut backups = vec![info_at("b", 0), info_at("c", 0), info_at("a", 0)];
+        for b in &mut backups {
+            b.timestamp = ts;
+        }
+
+        // Ties are broken by filename, so the same backup survives in any input order
+        let selected = select_backups_for_cleanup(backups.clone(), 1, Some(90));
+        assert_eq!(selected_names(&selected), vec!["b", "a"]);
+
+        backups.reverse();
+        let selected = select_backups_for_cleanup(backups, 1, Some(90));
+        assert_eq!(selected_names(&selected), vec!["b", "a"]);
+    }
+
     #[test]
     fn test_backup_info_filename() {
         let info = BackupInfo {
@@ -756,8 +2164,504 @@ git branch bugfix/login e5f6g7h8
             repo_name: "test".to_string(),
             timestamp: Utc::now(),
             branch_count: 5,
+            repo_path: None,
+            label: None,
+            checksum: ChecksumStatus::NotRecorded,
         };
 
         assert_eq!(info.filename(), "backup-20260201-143022.txt");
     }
+
+    fn backup_info_with(
+        dir: &Path,
+        filename: &str,
+        created: &str,
+        branches: &[&str],
+    ) -> BackupInfo {
+        let mut content = format!("# deadbranch backup\n# Created: {}\n\n", created);
+        for name in branches {
+            content.push_str(&format!("# {}\ngit branch {} abc1234\n\n", name, name));
+        }
+        let path = create_test_backup(dir, filename, &content);
+        BackupInfo::from_path(path, "repo").unwrap()
+    }
+
+    #[test]
+    fn test_backup_list_filter_composes() {
+        let temp = TempDir::new().unwrap();
+        let backups = vec![
+            backup_info_with(
+                temp.path(),
+                "backup-20240901-000000.txt",
+                "2024-09-01T00:00:00+00:00",
+                &["feature/login", "wip/spike"],
+            ),
+            backup_info_with(
+                temp.path(),
+                "backup-20240701-000000.txt",
+                "2024-07-01T00:00:00+00:00",
+                &["feature/search"],
+            ),
+            backup_info_with(
+                temp.path(),
+                "backup-20240501-000000.txt",
+                "2024-05-01T00:00:00+00:00",
+                &["feature/old"],
+            ),
+        ];
+
+        let unfiltered = BackupListFilter::default();
+        assert!(!unfiltered.is_active());
+        assert_eq!(unfiltered.apply(backups.clone()).len(), 3);
+
+        let since = BackupListFilter {
+            since: Some("2024-06-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert_eq!(since.apply(backups.clone()).len(), 2);
+
+        let contains = BackupListFilter {
+            contains: Some("feature/*".to_string()),
+            since: Some("2024-06-01T00:00:00Z".parse().unwrap()),
+            limit: Some(1),
+            ..Default::default()
+        };
+        let result = contains.apply(backups.clone());
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].filename(), "backup-20240901-000000.txt");
+
+        let no_match = BackupListFilter {
+            contains: Some("hotfix/*".to_string()),
+            ..Default::default()
+        };
+        assert!(no_match.apply(backups).is_empty());
+    }
+
+    #[test]
+    fn test_backup_list_filter_before_and_sort() {
+        let temp = TempDir::new().unwrap();
+        let backups = vec![
+            backup_info_with(
+                temp.path(),
+                "backup-20240901-000000.txt",
+                "2024-09-01T00:00:00+00:00",
+                &["a"],
+            ),
+            backup_info_with(
+                temp.path(),
+                "backup-20240701-000000.txt",
+                "2024-07-01T00:00:00+00:00",
+                &["b", "c", "d"],
+            ),
+            backup_info_with(
+                temp.path(),
+                "backup-20240501-000000.txt",
+                "2024-05-01T00:00:00+00:00",
+                &["e", "f"],
+            ),
+        ];
+        let names = |list: Vec<BackupInfo>| -> Vec<String> {
+            list.iter().map(|b| b.filename().to_string()).collect()
+        };
+
+        let window = BackupListFilter {
+            since: Some("2024-06-01T00:00:00Z".parse().unwrap()),
+            before: Some("2024-08-01T00:00:00Z".parse().unwrap()),
+            ..Default::default()
+        };
+        assert!(window.is_active());
+        assert_eq!(
+            names(window.apply(backups.clone())),
+            vec!["backup-20240701-000000.txt"]
+        );
+
+        // Age order is newest first whatever the input order
+        let mut shuffled = backups.clone();
+        shuffled.swap(0, 2);
+        assert_eq!(
+            names(BackupListFilter::default().apply(shuffled)),
+            vec![
+                "backup-20240901-000000.txt",
+                "backup-20240701-000000.txt",
+                "backup-20240501-000000.txt"
+            ]
+        );
+
+        // Sorting happens before --limit, so the limit keeps the biggest backups
+        let by_branches = BackupListFilter {
+            sort: BackupSort::Branches,
+            limit: Some(2),
+            ..Default::default()
+        };
+        assert!(by_branches.is_active());
+        assert_eq!(
+            names(by_branches.apply(backups)),
+            vec!["backup-20240701-000000.txt", "backup-20240501-000000.txt"]
+        );
+    }
+
+    #[test]
+    fn test_backup_info_reads_repo_path() {
+        let temp = TempDir::new().unwrap();
+        let path = create_test_backup(
+            temp.path(),
+            "backup-20260201-143022.txt",
+            "# deadbranch backup\n# Created: 2026-02-01T14:30:22+00:00\n# Repository: app\n# Repository path: /home/me/src/app\n\n# old\ngit branch old abc1234\n",
+        );
+
+        let info = BackupInfo::from_path(path, "app").unwrap();
+        assert_eq!(info.repo_path, Some(PathBuf::from("/home/me/src/app")));
+        assert_eq!(info.branch_count, 1);
+    }
+
+    #[test]
+    fn test_repo_status_from_backups() {
+        let temp = TempDir::new().unwrap();
+        let repo_dir = temp.path().join("app");
+        fs::create_dir_all(repo_dir.join(".git")).unwrap();
+
+        let info = |repo_path: Option<PathBuf>| BackupInfo {
+            path: PathBuf::from("/test"),
+            repo_name: "app".to_string(),
+            timestamp: Utc::now(),
+            branch_count: 1,
+            repo_path,
+            label: None,
+            checksum: ChecksumStatus::NotRecorded,
+        };
+
+        assert_eq!(
+            RepoStatus::from_backups(&[info(None), info(Some(repo_dir.clone()))]),
+            RepoStatus::Present
+        );
+        assert_eq!(
+            RepoStatus::from_backups(&[info(Some(temp.path().join("gone")))]),
+            RepoStatus::Orphaned
+        );
+        // A directory that exists but is no longer a git repository is orphaned too
+        assert_eq!(
+            RepoStatus::from_backups(&[info(Some(temp.path().to_path_buf()))]),
+            RepoStatus::Orphaned
+        );
+        assert_eq!(RepoStatus::from_backups(&[info(None)]), RepoStatus::Unknown);
+    }
+
+    #[test]
+    fn test_parse_v1_backup_treats_config_lines_as_comments() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "# deadbranch backup\n# Created: 2026-02-01T14:30:22+00:00\n\n# feature/auth\n# branch.feature/auth.description = looks like config\ngit branch feature/auth a1b2c3d4\n";
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", content);
+
+        let parsed = parse_backup_file(&path).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].name, "feature/auth");
+        assert!(parsed.entries[0].config.is_empty());
+    }
+
+    #[test]
+    fn test_parse_backup_rejects_newer_format() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "# deadbranch backup\n# Format: 99\n\ngit branch feature/auth a1b2c3d4\n";
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", content);
+
+        let err = parse_backup_file(&path).unwrap_err();
+        assert!(matches!(
+            err,
+            RestoreError::UnsupportedFormat { version: 99 }
+        ));
+        assert!(err.to_string().contains("format version 99"));
+    }
+
+    #[test]
+    fn test_parse_backup_rejects_invalid_format_version() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "# deadbranch backup\n# Format: two\n\ngit branch feature/auth a1b2c3d4\n";
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", content);
+
+        let err = parse_backup_file(&path).unwrap_err();
+        assert!(matches!(err, RestoreError::BackupCorrupted { .. }));
+    }
+
+    fn sealed(body: &str) -> String {
+        format!("{}{}", body, checksum_line(body.as_bytes()))
+    }
+
+    #[test]
+    fn test_backup_template_round_trip() {
+        let template = BackupTemplate::new(
+            "set -e\n# deadbranch-entry: {name} {sha}\ngit branch {name} {sha}\ngit push {remote} {sha}:refs/heads/{name}\n"
+                .to_string(),
+        )
+        .unwrap();
+
+        let mut body = String::from("# deadbranch backup\n# Format: 3\n\n# feature/{sha}\n");
+        body.push_str(&format_config_comment("feature/{sha}", "remote", "origin"));
+        body.push('\n');
+        body.push_str(&template.render("feature/{sha}", "abc1234", "origin"));
+        body.push_str("\n# old\n");
+        body.push_str(&template.render("old", "def5678", ""));
+        let content = sealed(&body);
+        assert!(content.contains("git push origin abc1234:refs/heads/feature/{sha}\n"));
+
+        let parsed = parse_backup_content(&content).unwrap();
+        assert!(parsed.skipped_lines.is_empty());
+        let restored: Vec<(&str, &str)> = parsed
+            .entries
+            .iter()
+            .map(|e| (e.name.as_str(), e.commit_sha.as_str()))
+            .collect();
+        assert_eq!(
+            restored,
+            vec![("feature/{sha}", "abc1234"), ("old", "def5678")]
+        );
+        assert_eq!(
+            parsed.entries[0].config,
+            vec![("remote".to_string(), "origin".to_string())]
+        );
+        assert!(parsed.entries[1].config.is_empty());
+    }
+
+    #[test]
+    fn test_backup_template_requires_marker_line() {
+        let err = BackupTemplate::new("git branch {name} {sha}\n".to_string()).unwrap_err();
+        assert!(err.to_string().contains(TEMPLATE_MARKER_LINE));
+    }
+
+    #[test]
+    fn test_checksum_valid_and_parses() {
+        let content = sealed("# deadbranch backup\n# Format: 3\n\n# a\ngit branch a abc1234\n\n");
+        assert_eq!(verify_checksum(&content, 3), ChecksumStatus::Valid);
+
+        let parsed = parse_backup_content(&content).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert!(parsed.skipped_lines.is_empty());
+    }
+
+    #[test]
+    fn test_checksum_missing_in_legacy_formats_is_fine() {
+        let v1 = "# deadbranch backup\n\ngit branch a abc1234\n";
+        let v2 = "# deadbranch backup\n# Format: 2\n\ngit branch a abc1234\n";
+        assert_eq!(verify_checksum(v1, 1), ChecksumStatus::NotRecorded);
+        assert_eq!(verify_checksum(v2, 2), ChecksumStatus::NotRecorded);
+        assert!(parse_backup_content(v2).is_ok());
+    }
+
+    #[test]
+    fn test_checksum_detects_truncation() {
+        let content = sealed("# deadbranch backup\n# Format: 3\n\n# a\ngit branch a abc1234\n\n# b\ngit branch b def5678\n\n");
+
+        // Cut inside the body: the checksum line is gone
+        let cut_body = &content[..content.find("# b").unwrap()];
+        assert_eq!(verify_checksum(cut_body, 3), ChecksumStatus::Truncated);
+
+        // Cut inside the checksum line itself
+        let cut_hash = &content[..content.len() - 20];
+        assert_eq!(verify_checksum(cut_hash, 3), ChecksumStatus::Truncated);
+
+        // Losing only the final newline leaves the checksum intact
+        let no_newline = content.strip_suffix('\n').unwrap();
+        assert_eq!(verify_checksum(no_newline, 3), ChecksumStatus::Valid);
+
+        let err = parse_backup_content(cut_body).unwrap_err();
+        assert!(err.to_string().contains("truncated"));
+    }
+
+    #[test]
+    fn test_checksum_detects_modification() {
+        let content = sealed("# deadbranch backup\n# Format: 3\n\n# a\ngit branch a abc1234\n\n");
+
+        let edited = content.replace("abc1234", "abc9999");
+        assert_eq!(verify_checksum(&edited, 3), ChecksumStatus::Modified);
+
+        let appended = format!("{}git branch extra 1234567\n", content);
+        assert_eq!(verify_checksum(&appended, 3), ChecksumStatus::Modified);
+
+        // Newline conversion changes the bytes, so it counts as a modification
+        let crlf = content.replace('\n', "\r\n");
+        assert_eq!(verify_checksum(&crlf, 3), ChecksumStatus::Modified);
+
+        let err = parse_backup_content(&edited).unwrap_err();
+        assert!(err.to_string().contains("modified"));
+    }
+
+    #[test]
+    fn test_migrate_v1_backup_file() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "# deadbranch backup\n# Created: 2026-02-01T14:30:22+00:00\n\n# old\ngit branch old a1b2c3d4\n";
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", content);
+
+        let outcome = migrate_backup_file(&path).unwrap();
+        assert!(matches!(outcome, MigrateOutcome::Migrated));
+
+        let migrated = fs::read_to_string(&path).unwrap();
+        assert!(migrated.starts_with("# deadbranch backup\n# Format: 3\n# Created:"));
+        assert_eq!(
+            verify_checksum(&migrated, BACKUP_FORMAT_VERSION),
+            ChecksumStatus::Valid
+        );
+        let original = fs::read_to_string(temp_dir.path().join("backup-20260201-143022.txt.bak"));
+        assert_eq!(original.unwrap(), content);
+
+        let parsed = parse_backup_file(&path).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].commit_sha, "a1b2c3d4");
+
+        // Running again is a no-op
+        let outcome = migrate_backup_file(&path).unwrap();
+        assert!(matches!(outcome, MigrateOutcome::AlreadyCurrent));
+    }
+
+    #[test]
+    fn test_parse_backup_reads_repository_metadata() {
+        let temp_dir = TempDir::new().unwrap();
+        let content = "# deadbranch backup\n# Format: 2\n# Repository: app\n# Repository path: /home/me/src/app\n\n# old\ngit branch old a1b2c3d4\n";
+        let path = create_test_backup(temp_dir.path(), "backup-20260201-143022.txt", content);
+
+        let parsed = parse_backup_file(&path).unwrap();
+        assert_eq!(parsed.repository.as_deref(), Some("app"));
+        assert_eq!(parsed.repo_path, Some(PathBuf::from("/home/me/src/app")));
+
+        let legacy = create_test_backup(
+            temp_dir.path(),
+            "backup-20260101-000000.txt",
+            "# deadbranch backup\n\ngit branch old a1b2c3d4\n",
+        );
+        let parsed = parse_backup_file(&legacy).unwrap();
+        assert!(parsed.repository.is_none());
+        assert!(parsed.repo_path.is_none());
+    }
+
+    #[test]
+    fn test_backup_list_filter_by_label() {
+        let temp = TempDir::new().unwrap();
+        let labelled = create_test_backup(
+            temp.path(),
+            "backup-20240901-000000.txt",
+            "# deadbranch backup\n# Format: 2\n# Created: 2024-09-01T00:00:00+00:00\n# Label: Pre-Migration purge\n\ngit branch a abc1234\n",
+        );
+        let unlabelled = create_test_backup(
+            temp.path(),
+            "backup-20240801-000000.txt",
+            "# deadbranch backup\n# Created: 2024-08-01T00:00:00+00:00\n\ngit branch b abc1234\n",
+        );
+        let backups = vec![
+            BackupInfo::from_path(labelled, "repo").unwrap(),
+            BackupInfo::from_path(unlabelled, "repo").unwrap(),
+        ];
+        assert_eq!(backups[0].label.as_deref(), Some("Pre-Migration purge"));
+        assert!(backups[1].label.is_none());
+
+        let filter = BackupListFilter {
+            label: Some("migration".to_string()),
+            ..Default::default()
+        };
+        let result = filter.apply(backups);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].filename(), "backup-20240901-000000.txt");
+    }
+
+    #[test]
+    fn test_binary_file_is_not_a_text_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let mut bytes = b"# deadbranch backup\n\0\0\x7fELF".to_vec();
+        bytes.extend_from_slice(b"\ngit branch a abc1234\n");
+        let path = temp_dir.path().join("backup-20260201-143022.txt");
+        fs::write(&path, &bytes).unwrap();
+
+        let err = parse_backup_file(&path).unwrap_err();
+        assert!(err.to_string().contains("not a text backup"), "{}", err);
+        assert!(BackupInfo::from_path(path, "repo").is_err());
+
+        let err = backup_text(b"# deadbranch backup\n\xff\xfe\n".to_vec()).unwrap_err();
+        assert!(err.to_string().contains("invalid UTF-8"), "{}", err);
+    }
+
+    #[test]
+    fn test_skipped_lines_are_capped_but_counted() {
+        let mut content = String::from("# deadbranch backup\n");
+        for i in 0..1000 {
+            content.push_str(&format!("garbage {}\n", i));
+        }
+        content.push_str(&format!(
+            "git branch {} abc1234\n",
+            "x".repeat(MAX_LINE_LEN)
+        ));
+        content.push_str("git branch kept abc1234\n");
+
+        let parsed = parse_backup_content(&content).unwrap();
+        assert_eq!(parsed.entries.len(), 1);
+        assert_eq!(parsed.entries[0].name, "kept");
+        assert_eq!(parsed.skipped_lines.len(), MAX_SKIPPED_LINES);
+        assert_eq!(parsed.skipped_line_count, 1001);
+        assert_eq!(parsed.skipped_lines[0].content, "garbage 0");
+    }
+
+    #[test]
+    fn test_overlong_line_is_skipped_and_shortened() {
+        let content = format!("# deadbranch backup\n# {}\n", "é".repeat(MAX_LINE_LEN));
+        let parsed = parse_backup_content(&content).unwrap();
+        assert_eq!(parsed.skipped_line_count, 1);
+        assert_eq!(parsed.skipped_lines[0].line_number, 2);
+        assert!(parsed.skipped_lines[0].content.len() <= 200);
+    }
+
+    mod byte_soup {
+        use super::super::*;
+        use proptest::prelude::*;
+
+        fn check(bytes: Vec<u8>) -> Result<(), TestCaseError> {
+            match backup_text(bytes).and_then(|text| parse_backup_content(&text)) {
+                Ok(parsed) => {
+                    prop_assert!(parsed.skipped_lines.len() <= MAX_SKIPPED_LINES);
+                    prop_assert!(parsed.skipped_line_count >= parsed.skipped_lines.len());
+                }
+                Err(RestoreError::BackupCorrupted { .. })
+                | Err(RestoreError::UnsupportedFormat { .. }) => {}
+                Err(e) => prop_assert!(false, "unexpected error: {}", e),
+            }
+            Ok(())
+        }
+
+        /// Lines that look enough like a backup to get past the header checks
+        fn backup_like_line() -> impl Strategy<Value = String> {
+            prop_oneof![
+                Just("# Format: 3".to_string()),
+                Just("# SHA256: 00".to_string()),
+                "# Format: [0-9a-z]{0,12}",
+                "# branch\\.[a-z.]{0,6} = [ -~\\\\]{0,10}",
+                "git branch[ a-z0-9]{0,12}",
+                "# deadbranch-entry:[ a-z0-9]{0,12}",
+                "\\PC{0,40}",
+            ]
+        }
+
+        proptest! {
+            #[test]
+            fn arbitrary_bytes_never_panic(bytes in proptest::collection::vec(any::<u8>(), 0..512)) {
+                check(bytes)?;
+            }
+
+            #[test]
+            fn arbitrary_bytes_after_the_header_never_panic(
+                tail in proptest::collection::vec(any::<u8>(), 0..512),
+            ) {
+                let mut bytes = BACKUP_HEADER.as_bytes().to_vec();
+                bytes.push(b'\n');
+                bytes.extend(tail);
+                check(bytes)?;
+            }
+
+            #[test]
+            fn backup_like_lines_never_panic(
+                lines in proptest::collection::vec(backup_like_line(), 0..24),
+            ) {
+                let mut text = format!("{}\n", BACKUP_HEADER);
+                for line in lines {
+                    text.push_str(&line);
+                    text.push('\n');
+                }
+                check(text.into_bytes())?;
+            }
+        }
+    }
 }
@@ -1,6 +1,9 @@
 //! Branch struct and filtering logic
 
 use chrono::{DateTime, Utc};
+use serde::ser::{Serialize, SerializeMap, Serializer};
+
+use crate::format_string::{Field, FieldValue};
 
 /// Age severity for color coding across UIs
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,6 +25,29 @@ impl AgeSeverity {
             _ => AgeSeverity::Stale,
         }
     }
+
+    /// Severity relative to the staleness threshold: fresh under
+    /// [`AGE_GRADE_MULTIPLES`].0 × `default_days`, moderate under .1 ×, stale
+    /// beyond (60 and 180 days with the default of 30)
+    pub fn graded(age_days: i64, default_days: u32) -> Self {
+        let (moderate_from, stale_from) = age_grade_bounds(default_days);
+        if age_days < moderate_from {
+            AgeSeverity::Fresh
+        } else if age_days < stale_from {
+            AgeSeverity::Moderate
+        } else {
+            AgeSeverity::Stale
+        }
+    }
+}
+
+/// Multiples of `default_days` at which table ages turn yellow, then red
+pub const AGE_GRADE_MULTIPLES: (i64, i64) = (2, 6);
+
+/// Ages (in days) at which [`AgeSeverity::graded`] turns moderate, then stale
+pub fn age_grade_bounds(default_days: u32) -> (i64, i64) {
+    let days = i64::from(default_days.max(1));
+    (days * AGE_GRADE_MULTIPLES.0, days * AGE_GRADE_MULTIPLES.1)
 }
 
 /// Represents a git branch with metadata
@@ -38,77 +64,100 @@ pub struct Branch {
     pub merged_by_tree: bool,
     /// Whether this is a remote branch
     pub is_remote: bool,
+    /// Remote a remote branch was listed from (`upstream` for `upstream/feat`),
+    /// which may itself contain `/`; `None` for local branches
+    pub remote: Option<String>,
     /// SHA of the last commit
     pub last_commit_sha: String,
     /// Date of the last commit
     pub last_commit_date: DateTime<Utc>,
     /// Author of the last commit
     pub last_commit_author: String,
+    /// Subject line of the last commit; only looked up for `list --show-subject`
+    pub last_commit_subject: Option<String>,
+    /// Committer of the last commit; only looked up for `list` output that has it
+    pub last_commit_committer: Option<String>,
 }
 
 impl Branch {
-    /// Check if this branch matches any protected pattern
+    /// Check if this branch matches any protected name or glob pattern
+    /// (e.g. "main" or "release/*")
     pub fn is_protected(&self, protected_branches: &[String]) -> bool {
-        let name = self.short_name();
-        protected_branches.iter().any(|p| p == name)
+        is_protected_name(self.short_name(), protected_branches)
     }
 
     /// Check if this branch matches any exclude pattern (glob-style)
     /// Supports: "wip/*", "*/draft", "feature/*/temp", etc.
     pub fn matches_exclude_pattern(&self, patterns: &[String]) -> bool {
+        self.matching_pattern(patterns).is_some()
+    }
+
+    /// The first pattern in `patterns` matching this branch's short name
+    pub fn matching_pattern<'a>(&self, patterns: &'a [String]) -> Option<&'a str> {
         let name = self.short_name();
         patterns
             .iter()
-            .any(|pattern| Self::glob_match(pattern, name))
+            .find(|pattern| Self::glob_match(pattern, name))
+            .map(String::as_str)
     }
 
-    /// Simple glob matching: supports * as wildcard
-    fn glob_match(pattern: &str, text: &str) -> bool {
-        let parts: Vec<&str> = pattern.split('*').collect();
-
-        if parts.len() == 1 {
-            // No wildcard, exact match
-            return pattern == text;
-        }
-
-        let mut remaining = text;
-
-        for (i, part) in parts.iter().enumerate() {
-            if part.is_empty() {
-                continue;
-            }
-
-            if i == 0 {
-                // First part must be at the start
-                if !remaining.starts_with(part) {
-                    return false;
+    /// Glob matching on whole branch names: `*` matches any run of characters
+    /// (including `/`), `?` any one character, and `[abc]`, `[a-z]` or `[!abc]`
+    /// one character in (or not in) a set. A `[` with no closing `]` is an
+    /// ordinary character.
+    pub fn glob_match(pattern: &str, text: &str) -> bool {
+        let tokens = parse_glob(pattern)
+            .unwrap_or_else(|_| pattern.chars().map(GlobToken::Literal).collect());
+        let text: Vec<char> = text.chars().collect();
+
+        // Match left to right; on a mismatch, let the last `*` take one more
+        // character and retry from there
+        let (mut p, mut t) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+        while t < text.len() {
+            match tokens.get(p) {
+                Some(GlobToken::Star) => {
+                    p += 1;
+                    backtrack = Some((p, t));
+                    continue;
                 }
-                remaining = &remaining[part.len()..];
-            } else if i == parts.len() - 1 {
-                // Last part must be at the end
-                if !remaining.ends_with(part) {
-                    return false;
-                }
-                remaining = "";
-            } else {
-                // Middle parts can be anywhere
-                if let Some(pos) = remaining.find(part) {
-                    remaining = &remaining[pos + part.len()..];
-                } else {
-                    return false;
+                Some(token) if token.matches(text[t]) => {
+                    p += 1;
+                    t += 1;
+                    continue;
                 }
+                _ => {}
             }
+            let Some((after_star, start)) = backtrack else {
+                return false;
+            };
+            p = after_star;
+            t = start + 1;
+            backtrack = Some((after_star, t));
         }
-
-        true
+        tokens[p..].iter().all(|token| *token == GlobToken::Star)
     }
 
-    /// Get the short name (without origin/ prefix for remote branches)
+    /// Get the short name (without the `<remote>/` prefix for remote branches)
     pub fn short_name(&self) -> &str {
+        self.remote
+            .as_deref()
+            .and_then(|remote| self.name.strip_prefix(remote)?.strip_prefix('/'))
+            .unwrap_or(&self.name)
+    }
+
+    /// Number of `/`-separated segments in the short name (`feature/team/x` -> 3)
+    pub fn depth(&self) -> u32 {
+        u32::try_from(self.short_name().split('/').count()).unwrap_or(u32::MAX)
+    }
+
+    /// Fully qualified ref (`refs/heads/<name>` or `refs/remotes/<remote>/<name>`),
+    /// which a tag or file with the same name can't shadow
+    pub fn full_ref(&self) -> String {
         if self.is_remote {
-            self.name.strip_prefix("origin/").unwrap_or(&self.name)
+            format!("refs/remotes/{}", self.name)
         } else {
-            &self.name
+            format!("refs/heads/{}", self.name)
         }
     }
 
@@ -125,6 +174,47 @@ impl Branch {
             format!("{} days", self.age_days)
         }
     }
+
+    /// One reportable value; `None` when it wasn't looked up (see [`Field::requires`])
+    pub fn field(&self, field: Field) -> Option<FieldValue> {
+        Some(match field {
+            Field::Name => FieldValue::Text(self.name.clone()),
+            Field::ShortName => FieldValue::Text(self.short_name().to_string()),
+            Field::AgeDays => FieldValue::Number(self.age_days),
+            Field::AgeHuman => FieldValue::Text(self.format_age()),
+            Field::Merged => FieldValue::Flag(self.is_merged),
+            Field::Remote => FieldValue::Flag(self.is_remote),
+            Field::Sha => FieldValue::Text(self.last_commit_sha.clone()),
+            Field::Date => FieldValue::Text(
+                self.last_commit_date
+                    .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            ),
+            Field::Author => FieldValue::Text(self.last_commit_author.clone()),
+            Field::Committer => FieldValue::Text(self.last_commit_committer.clone()?),
+            Field::Subject => FieldValue::Text(self.last_commit_subject.clone()?),
+        })
+    }
+}
+
+/// `list --format json`: every [`Field`], with `null` for those not looked up
+impl Serialize for Branch {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(Field::ALL.len()))?;
+        for field in Field::ALL {
+            map.serialize_entry(field.key(), &self.field(field))?;
+        }
+        map.end()
+    }
+}
+
+impl Serialize for FieldValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            FieldValue::Text(text) => serializer.serialize_str(text),
+            FieldValue::Number(n) => serializer.serialize_i64(*n),
+            FieldValue::Flag(flag) => serializer.serialize_bool(*flag),
+        }
+    }
 }
 
 /// Filter options for listing branches
@@ -132,6 +222,9 @@ impl Branch {
 pub struct BranchFilter {
     /// Minimum age in days
     pub min_age_days: u32,
+    /// Minimum age for remote branches, when it differs from `min_age_days`
+    /// (`general.default_days_remote`)
+    pub remote_min_age_days: Option<u32>,
     /// Only show local branches
     pub local_only: bool,
     /// Only show remote branches
@@ -142,6 +235,104 @@ pub struct BranchFilter {
     pub protected_branches: Vec<String>,
     /// Glob patterns to exclude (e.g., "wip/*", "*/draft")
     pub exclude_patterns: Vec<String>,
+    /// Extra rules that keep otherwise-stale branches
+    pub keep: KeepRules,
+    /// Only show local branches whose configured upstream no longer exists
+    pub orphaned_local_only: bool,
+    /// Only show unmerged remote branches with no local branch of the same name
+    pub stale_remote_only: bool,
+    /// Only branches with at least this many name segments ([`Branch::depth`])
+    pub min_depth: Option<u32>,
+    /// Only branches with at most this many name segments
+    pub max_depth: Option<u32>,
+    /// Only branches last committed within these dates (`list --since-tag`)
+    pub commit_window: CommitWindow,
+    /// Only branches with at least this many commits not on the default branch
+    pub min_commits: Option<u32>,
+    /// Only branches with at most this many commits not on the default branch
+    pub max_commits: Option<u32>,
+}
+
+/// A range of last-commit dates, like `git log <after>..<until>`: later than
+/// `after` and no later than `until`. Unset ends are open.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitWindow {
+    pub after: Option<DateTime<Utc>>,
+    pub until: Option<DateTime<Utc>>,
+}
+
+impl CommitWindow {
+    /// Whether either end is set
+    pub fn is_bounded(&self) -> bool {
+        self.after.is_some() || self.until.is_some()
+    }
+
+    /// Whether a commit date falls inside the window
+    pub fn contains(&self, date: DateTime<Utc>) -> bool {
+        self.after.is_none_or(|after| date > after) && self.until.is_none_or(|until| date <= until)
+    }
+}
+
+/// Rules that keep branches the cheap filters would select. Most need extra
+/// git lookups per branch, so they run after `matches_pre_merge`.
+#[derive(Debug, Clone, Default)]
+pub struct KeepRules {
+    /// Names or globs protected for this run only (`--protect`), on top of
+    /// `protected_branches`
+    pub protect: Vec<String>,
+    /// Keep local branches created (per reflog) within this many days
+    pub respect_creation_days: Option<u32>,
+    /// Keep branches that other active (younger than the age threshold)
+    /// branches are built on
+    pub respect_descendants: bool,
+    /// Keep this many of the most recently committed candidates (`clean` only)
+    pub keep_last: Option<usize>,
+}
+
+/// Why a branch old enough to clean was left out (`clean --dry-run --explain`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// Checked out in this worktree
+    CurrentBranch,
+    /// Checked out in another worktree at this path; git refuses to delete it
+    InWorktree(String),
+    /// Matches this `branches.protected` entry
+    Protected(String),
+    /// Matches this `--protect` pattern
+    ProtectedForRun(String),
+    /// Matches this exclude pattern
+    Excluded(String),
+    /// Not merged, and `--force` wasn't given
+    Unmerged,
+    /// Not allowed by this `branches.delete_when` policy, and `--force` wasn't given
+    Policy(String),
+    /// Kept by a keep rule, described in full
+    Kept(String),
+    /// Another branch has the same name apart from case, and deleting this
+    /// one wasn't confirmed
+    CaseCollision(String),
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::CurrentBranch => write!(f, "current branch"),
+            SkipReason::InWorktree(path) => write!(f, "checked out in worktree {}", path),
+            SkipReason::Protected(pattern) => write!(f, "protected ({})", pattern),
+            SkipReason::ProtectedForRun(pattern) => {
+                write!(f, "protected by --protect ({})", pattern)
+            }
+            SkipReason::Excluded(pattern) => write!(f, "exclude pattern '{}'", pattern),
+            SkipReason::Unmerged => write!(f, "unmerged (needs --force)"),
+            SkipReason::Policy(policy) => {
+                write!(f, "not matched by delete_when '{}' (needs --force)", policy)
+            }
+            SkipReason::Kept(rule) => write!(f, "{}", rule),
+            SkipReason::CaseCollision(other) => {
+                write!(f, "differs only by case from '{}'", other)
+            }
+        }
+    }
 }
 
 impl BranchFilter {
@@ -149,7 +340,54 @@ impl BranchFilter {
     /// Use this before running the squash-merge tree-check pass, since that
     /// pass can promote `is_merged` from false to true.
     pub fn matches_pre_merge(&self, branch: &Branch) -> bool {
-        if branch.age_days < self.min_age_days as i64 {
+        self.in_window(branch)
+            && !branch.is_protected(&self.protected_branches)
+            && !branch.is_protected(&self.keep.protect)
+            && !branch.matches_exclude_pattern(&self.exclude_patterns)
+    }
+
+    /// The age threshold for local or remote branches
+    pub fn min_age_for(&self, remote: bool) -> u32 {
+        match self.remote_min_age_days {
+            Some(days) if remote => days,
+            _ => self.min_age_days,
+        }
+    }
+
+    /// The age threshold in words, per scope when local and remote branches
+    /// differ: "older than 30 days" or "local older than 60 days, remote any age"
+    pub fn describe_age(&self) -> String {
+        let older_than = |days: u32| match days {
+            0 => "any age".to_string(),
+            days => format!(
+                "older than {} {}",
+                days,
+                if days == 1 { "day" } else { "days" }
+            ),
+        };
+        match self.remote_min_age_days {
+            Some(remote) if remote != self.min_age_days && !self.local_only => {
+                if self.remote_only {
+                    older_than(remote)
+                } else {
+                    format!(
+                        "local {}, remote {}",
+                        older_than(self.min_age_days),
+                        older_than(remote)
+                    )
+                }
+            }
+            _ => older_than(self.min_age_days),
+        }
+    }
+
+    /// Whether a branch is old enough, in the local/remote scope and within
+    /// the depth limits and commit window
+    pub fn in_window(&self, branch: &Branch) -> bool {
+        if branch.age_days < self.min_age_for(branch.is_remote) as i64 {
+            return false;
+        }
+        if !self.commit_window.contains(branch.last_commit_date) {
             return false;
         }
         if self.local_only && branch.is_remote {
@@ -158,21 +396,192 @@ impl BranchFilter {
         if self.remote_only && !branch.is_remote {
             return false;
         }
-        if branch.is_protected(&self.protected_branches) {
+        let depth = branch.depth();
+        if self.min_depth.is_some_and(|min| depth < min) {
             return false;
         }
-        if branch.matches_exclude_pattern(&self.exclude_patterns) {
+        if self.max_depth.is_some_and(|max| depth > max) {
             return false;
         }
         true
     }
 
+    /// Whether `--min-commits`/`--max-commits` apply, which needs a
+    /// `git rev-list` per branch
+    pub fn counts_commits(&self) -> bool {
+        self.min_commits.is_some() || self.max_commits.is_some()
+    }
+
+    /// Whether a branch with `count` unique commits passes the commit limits
+    pub fn commit_count_in_range(&self, count: u32) -> bool {
+        self.min_commits.is_none_or(|min| count >= min)
+            && self.max_commits.is_none_or(|max| count <= max)
+    }
+
+    /// Which protected name or exclude pattern keeps a branch, if any
+    pub fn skip_reason(&self, branch: &Branch) -> Option<SkipReason> {
+        if let Some(pattern) = branch.matching_pattern(&self.protected_branches) {
+            return Some(SkipReason::Protected(pattern.to_string()));
+        }
+        if let Some(pattern) = branch.matching_pattern(&self.keep.protect) {
+            return Some(SkipReason::ProtectedForRun(pattern.to_string()));
+        }
+        branch
+            .matching_pattern(&self.exclude_patterns)
+            .map(|pattern| SkipReason::Excluded(pattern.to_string()))
+    }
+
     /// Check if a branch passes this filter
     pub fn matches(&self, branch: &Branch) -> bool {
         self.matches_pre_merge(branch) && (!self.merged_only || branch.is_merged)
     }
 }
 
+/// Active branches that build on `branch`: those in `containing` (branches
+/// whose history includes its tip, named `<remote>/<branch>` for `remotes`)
+/// with a commit newer than `cutoff(is_remote)`.
+///
+/// The branch itself, its local/remote counterparts, remote HEADs and
+/// protected branches (which contain everything merged) don't count.
+pub fn active_descendants(
+    branch: &Branch,
+    containing: &[(String, DateTime<Utc>)],
+    remotes: &[String],
+    protected_branches: &[String],
+    cutoff: impl Fn(bool) -> DateTime<Utc>,
+) -> Vec<String> {
+    containing
+        .iter()
+        .filter(|(name, date)| {
+            let (is_remote, short) = match crate::git::split_remote_name(name, remotes) {
+                Some((_, short)) => (true, short),
+                None => (false, name.as_str()),
+            };
+            *date > cutoff(is_remote)
+                && !remotes.contains(name)
+                && short != "HEAD"
+                && short != branch.short_name()
+                && !is_protected_name(short, protected_branches)
+        })
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Whether a short branch name matches any protected name or glob pattern
+pub fn is_protected_name(name: &str, protected_branches: &[String]) -> bool {
+    protected_branches
+        .iter()
+        .any(|pattern| Branch::glob_match(pattern, name))
+}
+
+/// The `--release-pattern` names and globs `audit` checks branches against.
+///
+/// Release branches aren't protected: they are left out of the audit, but
+/// `clean` treats them like any other branch.
+#[derive(Debug, Clone, Default)]
+pub struct ReleaseMatcher {
+    patterns: Vec<String>,
+}
+
+impl ReleaseMatcher {
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns.to_vec(),
+        }
+    }
+
+    /// Whether a short branch name (`release/1.0`) is a release branch
+    pub fn matches(&self, name: &str) -> bool {
+        self.patterns
+            .iter()
+            .any(|pattern| Branch::glob_match(pattern, name))
+    }
+
+    /// The release branches among `local` and `remote` (named `<remote>/<branch>`
+    /// for one of `remotes`): local ones first, then remote ones whose short
+    /// name matches and has no local branch of the same name
+    pub fn release_branches(
+        &self,
+        local: &[String],
+        remote: &[String],
+        remotes: &[String],
+    ) -> Vec<String> {
+        let mut releases: Vec<String> = local
+            .iter()
+            .filter(|name| self.matches(name))
+            .cloned()
+            .collect();
+        for name in remote {
+            let Some((_, short)) = crate::git::split_remote_name(name, remotes) else {
+                continue;
+            };
+            if short != "HEAD" && self.matches(short) && !local.iter().any(|l| l == short) {
+                releases.push(name.clone());
+            }
+        }
+        releases
+    }
+}
+
+/// Remove the `count` most recently committed branches from `branches` and
+/// return them, newest first
+pub fn take_most_recent(branches: &mut Vec<Branch>, count: usize) -> Vec<Branch> {
+    branches.sort_by_key(|b| std::cmp::Reverse(b.last_commit_date));
+    let rest = branches.split_off(count.min(branches.len()));
+    std::mem::replace(branches, rest)
+}
+
+/// The entries of `names` that equal `name` apart from case (`Feature/X` for
+/// `feature/x`). Such refs are distinct to git but share a file on
+/// case-insensitive filesystems.
+pub fn case_variants<'a>(name: &str, names: &'a [String]) -> Vec<&'a str> {
+    let lower = name.to_lowercase();
+    names
+        .iter()
+        .filter(|other| *other != name && other.to_lowercase() == lower)
+        .map(String::as_str)
+        .collect()
+}
+
+/// Names that usually belong to long-lived branches (environments, release lines)
+const LONG_LIVED_NAMES: &[&str] = &[
+    "dev",
+    "develop",
+    "development",
+    "qa",
+    "uat",
+    "test",
+    "staging",
+    "stage",
+    "prod",
+    "production",
+    "stable",
+    "next",
+    "beta",
+    "release",
+    "hotfix",
+    "gh-pages",
+    "preview",
+    "demo",
+];
+
+/// Long-lived branch names among `names` that are not already protected,
+/// deduplicated (local and remote copies count once) and sorted
+pub fn long_lived_candidates<'a>(
+    names: impl IntoIterator<Item = &'a str>,
+    protected: &[String],
+) -> Vec<String> {
+    let mut found: Vec<String> = names
+        .into_iter()
+        .filter(|name| LONG_LIVED_NAMES.contains(name))
+        .filter(|name| !is_protected_name(name, protected))
+        .map(str::to_string)
+        .collect();
+    found.sort();
+    found.dedup();
+    found
+}
+
 /// Sort branches: merged first, then by age (oldest first)
 pub fn sort_branches(branches: &mut [Branch]) {
     branches.sort_by(|a, b| {
@@ -186,6 +595,90 @@ pub fn sort_branches(branches: &mut [Branch]) {
     });
 }
 
+/// One element of a parsed glob pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GlobToken {
+    Literal(char),
+    /// `?`
+    AnyChar,
+    /// `*`
+    Star,
+    /// `[...]`: inclusive ranges (a single character is a range of one)
+    Class {
+        negated: bool,
+        ranges: Vec<(char, char)>,
+    },
+}
+
+impl GlobToken {
+    /// Whether this (non-`*`) token matches `c`
+    fn matches(&self, c: char) -> bool {
+        match self {
+            GlobToken::Literal(l) => *l == c,
+            GlobToken::AnyChar => true,
+            GlobToken::Star => false,
+            GlobToken::Class { negated, ranges } => {
+                ranges.iter().any(|&(lo, hi)| lo <= c && c <= hi) != *negated
+            }
+        }
+    }
+}
+
+/// Split a glob into tokens, or explain why a `[...]` class is malformed.
+/// As in `fnmatch`, a `]` right after `[` or `[!` is part of the set.
+pub fn parse_glob(pattern: &str) -> Result<Vec<GlobToken>, String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '*' => {
+                // `**` means the same as `*`
+                if tokens.last() != Some(&GlobToken::Star) {
+                    tokens.push(GlobToken::Star);
+                }
+            }
+            '?' => tokens.push(GlobToken::AnyChar),
+            '[' => {
+                let mut j = i + 1;
+                let negated = matches!(chars.get(j), Some('!' | '^'));
+                if negated {
+                    j += 1;
+                }
+                let mut ranges = Vec::new();
+                let mut first = true;
+                loop {
+                    match chars.get(j) {
+                        None => return Err(format!("'[' at position {} is never closed", i + 1)),
+                        Some(']') if !first => break,
+                        Some(&lo) => {
+                            if chars.get(j + 1) == Some(&'-')
+                                && chars.get(j + 2).is_some_and(|&c| c != ']')
+                            {
+                                let hi = chars[j + 2];
+                                if hi < lo {
+                                    return Err(format!("range '{}-{}' is backwards", lo, hi));
+                                }
+                                ranges.push((lo, hi));
+                                j += 3;
+                            } else {
+                                ranges.push((lo, lo));
+                                j += 1;
+                            }
+                        }
+                    }
+                    first = false;
+                }
+                tokens.push(GlobToken::Class { negated, ranges });
+                i = j;
+            }
+            c => tokens.push(GlobToken::Literal(c)),
+        }
+        i += 1;
+    }
+    Ok(tokens)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -199,12 +692,40 @@ mod tests {
             is_merged,
             merged_by_tree: false,
             is_remote,
+            remote: name
+                .split_once('/')
+                .filter(|_| is_remote)
+                .map(|(remote, _)| remote.to_string()),
             last_commit_sha: "abc123".to_string(),
             last_commit_date: Utc::now(),
             last_commit_author: "testuser".to_string(),
+            last_commit_subject: None,
+            last_commit_committer: None,
         }
     }
 
+    #[test]
+    fn test_take_most_recent() {
+        let mut branches: Vec<Branch> = [40, 10, 90, 20, 60]
+            .iter()
+            .map(|&days| {
+                let mut b = test_branch(&format!("b{}", days), days, true, false);
+                b.last_commit_date = Utc::now() - chrono::Duration::days(days);
+                b
+            })
+            .collect();
+
+        let kept = take_most_recent(&mut branches, 2);
+        let names = |list: &[Branch]| list.iter().map(|b| b.name.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&kept), vec!["b10", "b20"]);
+        assert_eq!(names(&branches), vec!["b40", "b60", "b90"]);
+
+        // Asking for more than there are keeps everything
+        let kept = take_most_recent(&mut branches, 10);
+        assert_eq!(kept.len(), 3);
+        assert!(branches.is_empty());
+    }
+
     #[test]
     fn test_branch_short_name() {
         let local = test_branch("feature/test", 10, false, false);
@@ -212,6 +733,11 @@ mod tests {
 
         let remote = test_branch("origin/feature/test", 10, false, true);
         assert_eq!(remote.short_name(), "feature/test");
+
+        let upstream = test_branch("upstream/feature/test", 10, false, true);
+        assert_eq!(upstream.short_name(), "feature/test");
+        assert_eq!(upstream.full_ref(), "refs/remotes/upstream/feature/test");
+        assert_eq!(local.full_ref(), "refs/heads/feature/test");
     }
 
     #[test]
@@ -237,6 +763,16 @@ mod tests {
         assert!(remote_main.is_protected(&protected));
     }
 
+    #[test]
+    fn test_branch_is_protected_by_glob() {
+        let protected = vec!["main".to_string(), "release/*".to_string()];
+        assert!(test_branch("release/1.0", 10, false, false).is_protected(&protected));
+        assert!(test_branch("origin/release/2.x", 10, false, true).is_protected(&protected));
+        assert!(test_branch("main", 10, false, false).is_protected(&protected));
+        assert!(!test_branch("feature/x", 10, false, false).is_protected(&protected));
+        assert!(!test_branch("release", 10, false, false).is_protected(&protected));
+    }
+
     #[test]
     fn test_glob_match_exact() {
         assert!(Branch::glob_match("main", "main"));
@@ -271,6 +807,148 @@ mod tests {
         assert!(Branch::glob_match("*test*", "mytest123"));
     }
 
+    #[test]
+    fn test_glob_match_regressions() {
+        let cases = [
+            ("main", "main", true),
+            ("wip/*", "wip/", true),
+            ("*", "", true),
+            ("**", "a/b", true),
+            ("a*a", "a", false),
+            ("aa*aa", "aaa", false),
+            ("*ab*b", "ab", false),
+            ("a*b*b", "abb", true),
+            ("feature/*/temp", "feature//temp", true),
+            ("*-*", "a-b-c", true),
+        ];
+        for (pattern, text, expected) in cases {
+            assert_eq!(
+                Branch::glob_match(pattern, text),
+                expected,
+                "{:?} vs {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_match_single_character() {
+        assert!(Branch::glob_match("release/v?", "release/v2"));
+        assert!(!Branch::glob_match("release/v?", "release/v10"));
+        assert!(!Branch::glob_match("release/v?", "release/v"));
+        assert!(Branch::glob_match("release/v??*", "release/v10.1"));
+    }
+
+    #[test]
+    fn test_glob_match_character_classes() {
+        assert!(Branch::glob_match("hotfix-[abc]", "hotfix-b"));
+        assert!(!Branch::glob_match("hotfix-[abc]", "hotfix-d"));
+        assert!(Branch::glob_match("v[0-9].*", "v3.x"));
+        assert!(!Branch::glob_match("v[0-9].*", "vx.3"));
+        assert!(Branch::glob_match("[!.]*", "feature"));
+        assert!(!Branch::glob_match("[!.]*", ".hidden"));
+        assert!(Branch::glob_match("[^a-c]x", "dx"));
+        // `]` first in the set is a member, not the end
+        assert!(Branch::glob_match("[]x]", "]"));
+    }
+
+    #[test]
+    fn test_glob_unclosed_bracket_is_literal() {
+        assert!(Branch::glob_match("odd[name", "odd[name"));
+        assert!(!Branch::glob_match("odd[name", "oddn"));
+        assert!(parse_glob("odd[name").unwrap_err().contains("never closed"));
+        assert!(parse_glob("v[9-0]").unwrap_err().contains("backwards"));
+        assert!(parse_glob("wip/[a-z]*").is_ok());
+    }
+
+    /// The `*`-only matcher `glob_match` replaced, kept to pin down compatibility
+    fn legacy_glob_match(pattern: &str, text: &str) -> bool {
+        let parts: Vec<&str> = pattern.split('*').collect();
+        if parts.len() == 1 {
+            return pattern == text;
+        }
+        let mut remaining = text;
+        for (i, part) in parts.iter().enumerate() {
+            if part.is_empty() {
+                continue;
+            }
+            if i == 0 {
+                if !remaining.starts_with(part) {
+                    return false;
+                }
+                remaining = &remaining[part.len()..];
+            } else if i == parts.len() - 1 {
+                if !remaining.ends_with(part) {
+                    return false;
+                }
+                remaining = "";
+            } else if let Some(pos) = remaining.find(part) {
+                remaining = &remaining[pos + part.len()..];
+            } else {
+                return false;
+            }
+        }
+        true
+    }
+
+    mod glob_properties {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Branch-like names over a small alphabet, so patterns and names collide often
+        const NAME: &str = "[ab/-]{0,8}";
+        const STAR_PATTERN: &str = "[ab/*-]{0,8}";
+
+        proptest! {
+            #[test]
+            fn literal_pattern_matches_only_itself(pattern in NAME, text in NAME) {
+                prop_assert!(Branch::glob_match(&pattern, &pattern));
+                prop_assert_eq!(Branch::glob_match(&pattern, &text), pattern == text);
+            }
+
+            #[test]
+            fn star_patterns_match_as_before(pattern in STAR_PATTERN, text in NAME) {
+                prop_assert_eq!(
+                    Branch::glob_match(&pattern, &text),
+                    legacy_glob_match(&pattern, &text)
+                );
+            }
+
+            #[test]
+            fn matches_concatenate(
+                (p1, s1) in (STAR_PATTERN, NAME),
+                (p2, s2) in (STAR_PATTERN, NAME),
+            ) {
+                if Branch::glob_match(&p1, &s1) && Branch::glob_match(&p2, &s2) {
+                    let pattern = format!("{}{}", p1, p2);
+                    let text = format!("{}{}", s1, s2);
+                    prop_assert!(Branch::glob_match(&pattern, &text));
+                }
+            }
+
+            #[test]
+            fn question_marks_match_by_length(n in 0usize..6, text in NAME) {
+                let pattern = "?".repeat(n);
+                prop_assert_eq!(Branch::glob_match(&pattern, &text), text.chars().count() == n);
+            }
+
+            #[test]
+            fn a_class_matches_its_members(text in "[a-z]", members in "[a-z]{1,5}") {
+                let pattern = format!("[{}]", members);
+                let negated = format!("[!{}]", members);
+                let member = members.contains(text.as_str());
+                prop_assert_eq!(Branch::glob_match(&pattern, &text), member);
+                prop_assert_eq!(Branch::glob_match(&negated, &text), !member);
+            }
+
+            #[test]
+            fn star_matches_everything(text in "\\PC{0,12}") {
+                prop_assert!(Branch::glob_match("*", &text));
+            }
+        }
+    }
+
     #[test]
     fn test_branch_matches_exclude_pattern() {
         let branch = test_branch("wip/feature", 10, false, false);
@@ -301,6 +979,81 @@ mod tests {
         assert!(filter.matches(&exact_age));
     }
 
+    #[test]
+    fn test_filter_by_commit_window() {
+        let now = Utc::now();
+        let dated = |name: &str, days: i64| {
+            let mut b = test_branch(name, days, false, false);
+            b.last_commit_date = now - chrono::Duration::days(days);
+            b
+        };
+        let filter = BranchFilter {
+            commit_window: CommitWindow {
+                after: Some(now - chrono::Duration::days(60)),
+                until: Some(now - chrono::Duration::days(20)),
+            },
+            ..Default::default()
+        };
+
+        assert!(filter.matches(&dated("inside", 40)));
+        assert!(filter.matches(&dated("at-until", 20)));
+        assert!(!filter.matches(&dated("at-since", 60)));
+        assert!(!filter.matches(&dated("before", 90)));
+        assert!(!filter.matches(&dated("after", 5)));
+
+        // Open-ended windows
+        let since_only = CommitWindow {
+            after: Some(now - chrono::Duration::days(60)),
+            until: None,
+        };
+        assert!(since_only.is_bounded());
+        assert!(since_only.contains(now));
+        assert!(!CommitWindow::default().is_bounded());
+        assert!(CommitWindow::default().contains(now - chrono::Duration::days(900)));
+    }
+
+    #[test]
+    fn test_filter_by_depth() {
+        let filter = BranchFilter {
+            min_depth: Some(2),
+            max_depth: Some(3),
+            ..Default::default()
+        };
+
+        assert!(!filter.matches(&test_branch("hotfix", 45, false, false)));
+        assert!(filter.matches(&test_branch("feature/x", 45, false, false)));
+        assert!(filter.matches(&test_branch("feature/team/x", 45, false, false)));
+        assert!(!filter.matches(&test_branch("feature/team/me/x", 45, false, false)));
+        // The remote name isn't a segment
+        assert!(!filter.matches(&test_branch("origin/hotfix", 45, false, true)));
+        assert!(filter.matches(&test_branch("origin/feature/x", 45, false, true)));
+    }
+
+    #[test]
+    fn test_filter_commit_count_range() {
+        let filter = BranchFilter::default();
+        assert!(!filter.counts_commits());
+        assert!(filter.commit_count_in_range(0));
+
+        let real = BranchFilter {
+            min_commits: Some(2),
+            ..Default::default()
+        };
+        assert!(real.counts_commits());
+        assert!(!real.commit_count_in_range(1));
+        assert!(real.commit_count_in_range(5));
+
+        let small = BranchFilter {
+            min_commits: Some(1),
+            max_commits: Some(2),
+            ..Default::default()
+        };
+        assert!(!small.commit_count_in_range(0));
+        assert!(small.commit_count_in_range(1));
+        assert!(small.commit_count_in_range(2));
+        assert!(!small.commit_count_in_range(5));
+    }
+
     #[test]
     fn test_filter_local_only() {
         let filter = BranchFilter {
@@ -377,15 +1130,149 @@ mod tests {
         assert!(!filter.matches(&draft));
     }
 
+    #[test]
+    fn test_release_branches() {
+        let names = |list: &[&str]| list.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let local = names(&["main", "release/1.0", "feature/x"]);
+        let remote = names(&[
+            "origin/HEAD",
+            "origin/release/1.0",
+            "origin/release/2.0",
+            "origin/main",
+        ]);
+        let remotes = names(&["origin"]);
+        assert_eq!(
+            ReleaseMatcher::new(&names(&["release/*"])).release_branches(&local, &remote, &remotes),
+            vec!["release/1.0", "origin/release/2.0"]
+        );
+        assert!(ReleaseMatcher::new(&names(&["hotfix/*"]))
+            .release_branches(&local, &remote, &remotes)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_case_variants() {
+        let names: Vec<String> = ["Feature/X", "feature/x", "FEATURE/x", "feature/y"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            case_variants("feature/x", &names),
+            vec!["Feature/X", "FEATURE/x"]
+        );
+        assert!(case_variants("feature/y", &names).is_empty());
+        assert!(case_variants("other", &names).is_empty());
+    }
+
+    #[test]
+    fn test_filter_skip_reason_names_pattern() {
+        let filter = BranchFilter {
+            protected_branches: vec!["main".to_string(), "release/*".to_string()],
+            exclude_patterns: vec!["wip/*".to_string(), "*/draft".to_string()],
+            ..Default::default()
+        };
+
+        let release = test_branch("origin/release/1.0", 45, true, true);
+        assert_eq!(
+            filter.skip_reason(&release),
+            Some(SkipReason::Protected("release/*".to_string()))
+        );
+
+        let draft = test_branch("feature/draft", 45, false, false);
+        assert_eq!(
+            filter.skip_reason(&draft),
+            Some(SkipReason::Excluded("*/draft".to_string()))
+        );
+        assert_eq!(
+            SkipReason::Excluded("*/draft".to_string()).to_string(),
+            "exclude pattern '*/draft'"
+        );
+
+        let feature = test_branch("feature/test", 45, false, false);
+        assert_eq!(filter.skip_reason(&feature), None);
+    }
+
+    #[test]
+    fn test_filter_per_scope_age() {
+        let mut filter = BranchFilter {
+            min_age_days: 60,
+            remote_min_age_days: Some(0),
+            ..Default::default()
+        };
+
+        let local = test_branch("feature/local", 45, true, false);
+        let remote = test_branch("origin/feature/remote", 1, true, true);
+        assert!(!filter.matches(&local));
+        assert!(filter.matches(&remote));
+        assert_eq!(
+            filter.describe_age(),
+            "local older than 60 days, remote any age"
+        );
+
+        filter.remote_only = true;
+        assert_eq!(filter.describe_age(), "any age");
+        filter.remote_only = false;
+        filter.local_only = true;
+        assert_eq!(filter.describe_age(), "older than 60 days");
+
+        filter.remote_min_age_days = None;
+        filter.local_only = false;
+        assert!(!filter.matches(&remote));
+        assert_eq!(filter.describe_age(), "older than 60 days");
+    }
+
+    #[test]
+    fn test_filter_protect_for_run() {
+        let filter = BranchFilter {
+            protected_branches: vec!["main".to_string()],
+            keep: KeepRules {
+                protect: vec!["release/*".to_string(), "main".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Remote branches match by their short name, like branches.protected
+        let release = test_branch("origin/release/2.0", 45, true, true);
+        assert!(!filter.matches(&release));
+        assert_eq!(
+            filter.skip_reason(&release),
+            Some(SkipReason::ProtectedForRun("release/*".to_string()))
+        );
+        assert_eq!(
+            SkipReason::ProtectedForRun("release/*".to_string()).to_string(),
+            "protected by --protect (release/*)"
+        );
+
+        // The config's reason wins when both protect a branch
+        let main = test_branch("main", 45, true, false);
+        assert_eq!(
+            filter.skip_reason(&main),
+            Some(SkipReason::Protected("main".to_string()))
+        );
+
+        let feature = test_branch("feature/test", 45, true, false);
+        assert!(filter.matches(&feature));
+    }
+
     #[test]
     fn test_filter_combined() {
         let filter = BranchFilter {
             min_age_days: 30,
+            remote_min_age_days: None,
             merged_only: true,
             local_only: true,
             remote_only: false,
             protected_branches: vec!["main".to_string()],
             exclude_patterns: vec!["wip/*".to_string()],
+            keep: KeepRules::default(),
+            orphaned_local_only: false,
+            stale_remote_only: false,
+            min_depth: None,
+            max_depth: None,
+            commit_window: CommitWindow::default(),
+            min_commits: None,
+            max_commits: None,
         };
 
         // Should match: old, merged, local, not protected, not WIP
@@ -545,4 +1432,95 @@ mod tests {
         let branch = test_branch("test", 365, false, false);
         assert_eq!(branch.age_severity(), AgeSeverity::Stale);
     }
+
+    // ── active_descendants tests ────────────────────────────────────
+
+    #[test]
+    fn test_active_descendants_counts_recent_branches_only() {
+        let base = test_branch("base", 60, false, false);
+        let now = Utc::now();
+        let cutoff = now - chrono::Duration::days(30);
+        let containing = vec![
+            ("base".to_string(), now - chrono::Duration::days(60)),
+            ("origin/base".to_string(), now - chrono::Duration::days(1)),
+            ("feature-a".to_string(), now - chrono::Duration::days(2)),
+            (
+                "origin/feature-b".to_string(),
+                now - chrono::Duration::days(3),
+            ),
+            ("feature-old".to_string(), now - chrono::Duration::days(90)),
+            ("origin".to_string(), now),
+            ("main".to_string(), now),
+            ("origin/main".to_string(), now),
+        ];
+
+        let found = active_descendants(
+            &base,
+            &containing,
+            &["origin".to_string()],
+            &["main".to_string()],
+            |_| cutoff,
+        );
+        assert_eq!(found, vec!["feature-a", "origin/feature-b"]);
+    }
+
+    #[test]
+    fn test_active_descendants_none() {
+        let base = test_branch("origin/base", 60, false, true);
+        let now = Utc::now();
+        let containing = vec![("base".to_string(), now)];
+        let cutoff = now - chrono::Duration::days(30);
+        assert!(active_descendants(&base, &containing, &[], &[], |_| cutoff).is_empty());
+    }
+
+    #[test]
+    fn test_active_descendants_per_scope_cutoff_and_slashed_remote() {
+        let base = test_branch("base", 60, false, false);
+        let now = Utc::now();
+        let containing = vec![
+            ("feature-a".to_string(), now - chrono::Duration::days(20)),
+            (
+                "team/fork/feature-b".to_string(),
+                now - chrono::Duration::days(20),
+            ),
+            ("team/fork/base".to_string(), now),
+            ("team/fork/HEAD".to_string(), now),
+        ];
+        let remotes = ["team/fork".to_string()];
+        // Local branches are active for 30 days, remote ones for 10
+        let cutoff = |remote: bool| now - chrono::Duration::days(if remote { 10 } else { 30 });
+        assert_eq!(
+            active_descendants(&base, &containing, &remotes, &[], cutoff),
+            vec!["feature-a"]
+        );
+    }
+
+    #[test]
+    fn test_long_lived_candidates() {
+        let protected = vec!["main".to_string(), "develop".to_string()];
+        let names = [
+            "main",
+            "develop",
+            "qa",
+            "feature/x",
+            "qa",
+            "stable",
+            "release/1.0",
+        ];
+        assert_eq!(
+            long_lived_candidates(names, &protected),
+            vec!["qa".to_string(), "stable".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_age_severity_graded() {
+        assert_eq!(age_grade_bounds(30), (60, 180));
+        assert_eq!(AgeSeverity::graded(59, 30), AgeSeverity::Fresh);
+        assert_eq!(AgeSeverity::graded(60, 30), AgeSeverity::Moderate);
+        assert_eq!(AgeSeverity::graded(179, 30), AgeSeverity::Moderate);
+        assert_eq!(AgeSeverity::graded(180, 30), AgeSeverity::Stale);
+        assert_eq!(AgeSeverity::graded(20, 7), AgeSeverity::Moderate);
+        assert_eq!(AgeSeverity::graded(0, 0), AgeSeverity::Fresh);
+    }
 }
@@ -1,23 +1,66 @@
 //! CLI argument definitions using clap
 
-use clap::{Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 #[command(name = "deadbranch")]
 #[command(author, version, about = "Clean up stale git branches safely", long_about = None)]
 #[command(propagate_version = true)]
 pub struct Cli {
+    /// Print extra detail, such as how duration values were interpreted
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+
+    /// Read-only mode: never change branches, remotes, backups or config
+    /// (clean and backup clean run as dry runs; other changes are refused)
+    #[arg(long, global = true)]
+    pub check: bool,
+
+    /// Don't print follow-up hints, such as how to undo a deletion
+    #[arg(long, global = true)]
+    pub no_hints: bool,
+
+    /// Use text prefixes (OK:, WARN:, ERROR:, INFO:) instead of emoji/symbols
+    #[arg(long, global = true)]
+    pub plain: bool,
+
+    /// Disable colored output (also honored: the NO_COLOR environment variable)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Print how long each phase took and how many git commands it ran
+    #[arg(long, global = true)]
+    pub timing: bool,
+
+    /// Run in this repository; it must be the top level of the working tree,
+    /// not a subdirectory
+    #[arg(long, global = true, value_name = "PATH")]
+    pub repo_root: Option<PathBuf>,
+
+    /// Use built-in defaults only: don't read the config file, or create it
+    #[arg(long, global = true)]
+    pub ignore_config: bool,
+
+    /// How to report a failure on stderr: a styled message, or
+    /// `{"error": ..., "kind": ...}` for scripts
+    #[arg(long, global = true, value_enum, default_value_t = ErrorFormat::Text)]
+    pub error_format: ErrorFormat,
+
+    // When omitted, `general.default_command` runs (see `main`)
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
 }
 
 #[derive(Subcommand)]
 pub enum Commands {
     /// List stale branches
+    #[command(visible_alias = "ls")]
     List {
-        /// Only show branches older than N days (default: from config or 30)
-        #[arg(short, long)]
+        /// Only show branches older than this many days, or a duration like 3w, 6m, 1y
+        /// (default: from config or 30)
+        #[arg(short, long, value_parser = crate::duration::parse_days)]
         days: Option<u32>,
 
         /// Only show local branches
@@ -28,16 +71,89 @@ pub enum Commands {
         #[arg(long, conflicts_with = "local")]
         remote: bool,
 
+        #[command(flatten)]
+        remotes: RemoteArgs,
+
+        #[command(flatten)]
+        depth: DepthArgs,
+
+        #[command(flatten)]
+        commits: CommitCountArgs,
+
         /// Only show merged branches
         #[arg(long)]
         merged: bool,
+
+        /// Output format [default: table]
+        #[arg(long, value_enum)]
+        format: Option<OutputFormat>,
+
+        /// Print one line per branch from a template, e.g. '{name}\t{age_days}\t{sha}'.
+        /// Placeholders: name, short_name, age_days, age_human, merged, remote, sha,
+        /// date, author, committer, subject (with --show-subject); escapes: \t, \n,
+        /// \\, {{, }}
+        #[arg(
+            long,
+            value_name = "TEMPLATE",
+            conflicts_with = "format",
+            value_parser = crate::format_string::FormatString::parse
+        )]
+        format_string: Option<crate::format_string::FormatString>,
+
+        /// Exit with status 1 if any stale branches match (prints nothing unless --format is given)
+        #[arg(long)]
+        exit_code: bool,
+
+        /// Add a column with each branch's last commit subject, cut to the terminal width
+        #[arg(long)]
+        show_subject: bool,
+
+        /// Keep local branches created within N days (per reflog), whatever their commit age
+        /// (accepts durations like 2w)
+        #[arg(long, value_name = "DAYS", value_parser = crate::duration::parse_days)]
+        respect_creation: Option<u32>,
+
+        /// Keep branches that newer, non-protected branches are built on
+        #[arg(long)]
+        respect_descendants: bool,
+
+        /// Also protect branches matching this name or glob, for this run only
+        /// (repeatable; added to branches.protected)
+        #[arg(long, value_name = "PATTERN", value_parser = clap::builder::NonEmptyStringValueParser::new())]
+        protect: Vec<String>,
+
+        /// Only show local branches whose configured upstream ref no longer exists
+        /// (any age unless --days is given)
+        #[arg(long, conflicts_with = "remote")]
+        orphaned_local: bool,
+
+        /// Only show unmerged remote branches that have no local branch of the same
+        /// name, e.g. to audit a shared server without flagging your own work
+        #[arg(long, conflicts_with_all = ["local", "merged", "orphaned_local"])]
+        stale_remote_only: bool,
+
+        /// Run `git fetch --prune` first so remote branch ages are current
+        /// (list doesn't contact remotes otherwise)
+        #[arg(long, conflicts_with_all = ["local", "offline"])]
+        fetch: bool,
+
+        /// Only show branches whose last commit is newer than this tag's commit
+        /// (any age unless --days is given)
+        #[arg(long, value_name = "TAG")]
+        since_tag: Option<String>,
+
+        /// Only show branches whose last commit is no newer than this tag's commit
+        /// (any age unless --days is given)
+        #[arg(long, value_name = "TAG")]
+        until_tag: Option<String>,
     },
 
     /// Delete stale branches (merged only by default, use --force for unmerged)
-    #[command(alias = "snap")]
+    #[command(visible_alias = "prune", alias = "snap")]
     Clean {
-        /// Only delete branches older than N days (default: from config or 30)
-        #[arg(short, long)]
+        /// Only delete branches older than this many days, or a duration like 3w, 6m, 1y
+        /// (default: from config or 30)
+        #[arg(short, long, value_parser = crate::duration::parse_days)]
         days: Option<u32>,
 
         /// Only delete merged branches (this is the default behavior)
@@ -48,10 +164,33 @@ pub enum Commands {
         #[arg(long)]
         force: bool,
 
+        /// Let --force delete unmerged remote branches too, despite
+        /// branches.remote_require_merged
+        #[arg(long)]
+        force_remote_unmerged: bool,
+
         /// Show what would be deleted without doing it
         #[arg(long, conflicts_with = "interactive")]
         dry_run: bool,
 
+        /// With --dry-run, also list old enough branches that were skipped and why
+        #[arg(long, requires = "dry_run")]
+        explain: bool,
+
+        /// With --dry-run, print only the git commands, quoted for a POSIX shell and
+        /// one per line after a `set -e`, for `eval "$(...)"`; everything else goes to stderr
+        #[arg(long, requires = "dry_run")]
+        eval: bool,
+
+        /// With --eval, leave out the leading `set -e`
+        #[arg(long, requires = "eval")]
+        no_set_e: bool,
+
+        /// After deleting, check that each deleted branch is really gone (remote
+        /// ones via `git ls-remote`) and exit with status 1 if any still exists
+        #[arg(long, conflicts_with_all = ["dry_run", "interactive"])]
+        verify: bool,
+
         /// Only delete local branches
         #[arg(long)]
         local: bool,
@@ -60,16 +199,87 @@ pub enum Commands {
         #[arg(long, conflicts_with = "local")]
         remote: bool,
 
+        #[command(flatten)]
+        remotes: RemoteArgs,
+
+        #[command(flatten)]
+        depth: DepthArgs,
+
+        #[command(flatten)]
+        commits: CommitCountArgs,
+
         /// Skip confirmation prompts (useful for scripts)
         #[arg(short, long, conflicts_with = "interactive")]
         yes: bool,
 
+        /// Allow --force together with --yes to delete unmerged branches unattended
+        #[arg(long)]
+        i_understand_data_loss: bool,
+
+        /// Skip the typed confirmation for remote branches; only honored when
+        /// DEADBRANCH_ACCEPT_REMOTE_RISK=1 is set as well
+        #[arg(long, conflicts_with = "interactive")]
+        accept_risk: bool,
+
+        /// Confirm the remote deletion without a prompt by passing the phrase it asks
+        /// for, e.g. "delete 17 remote branches"; a different count aborts the remote phase.
+        /// With --yes, local branches are deleted without a prompt and the phrase
+        /// still decides the remote phase.
+        #[arg(
+            long,
+            value_name = "TEXT",
+            conflicts_with_all = ["interactive", "accept_risk"]
+        )]
+        confirm_phrase: Option<String>,
+
         /// Open interactive TUI for branch selection
         #[arg(short, long)]
         interactive: bool,
+
+        /// Start from a preset flag combination (individual flags still override it)
+        #[arg(long, value_enum)]
+        preset: Option<Preset>,
+
+        /// Note recorded in the backup file (shown by `backup list` and `backup show`)
+        #[arg(long, value_name = "TEXT", value_parser = parse_label)]
+        label: Option<String>,
+
+        /// Delete without writing a backup first, e.g. when the home directory is
+        /// read-only; the deleted branches can't be restored with `backup restore`
+        #[arg(long, conflicts_with_all = ["interactive", "label"])]
+        no_backup: bool,
+
+        /// Print only the backup file's absolute path to stdout, as the last thing
+        /// the run does (one line per backup); everything else goes to stderr
+        #[arg(long, conflicts_with_all = ["interactive", "dry_run", "no_backup"])]
+        print_backup_path: bool,
+
+        /// Write a report of the run (default branch, filters, each branch and what
+        /// happened to it, backups) to PATH: HTML for .html/.htm, plain text otherwise
+        #[arg(long, value_name = "PATH", conflicts_with_all = ["interactive", "dry_run"])]
+        report: Option<PathBuf>,
+
+        /// Keep local branches created within N days (per reflog), whatever their commit age
+        /// (accepts durations like 2w)
+        #[arg(long, value_name = "DAYS", value_parser = crate::duration::parse_days)]
+        respect_creation: Option<u32>,
+
+        /// Keep branches that newer, non-protected branches are built on
+        #[arg(long)]
+        respect_descendants: bool,
+
+        /// Also protect branches matching this name or glob, for this run only
+        /// (repeatable; added to branches.protected)
+        #[arg(long, value_name = "PATTERN", value_parser = clap::builder::NonEmptyStringValueParser::new())]
+        protect: Vec<String>,
+
+        /// Never delete the N most recently committed candidates, whatever their age
+        #[arg(long, value_name = "N")]
+        keep_last: Option<usize>,
     },
 
     /// Manage configuration
+    #[command(visible_alias = "cfg")]
     Config {
         #[command(subcommand)]
         action: ConfigAction,
@@ -83,9 +293,83 @@ pub enum Commands {
 
     /// Show repository branch statistics
     Stats {
-        /// Treat branches older than N days as stale (default: from config or 30)
-        #[arg(short, long)]
+        /// Treat branches older than this many days (or a duration like 3w) as stale
+        /// (default: from config or 30)
+        #[arg(short, long, value_parser = crate::duration::parse_days)]
         days: Option<u32>,
+
+        /// Count the branches of this remote instead of origin
+        #[arg(long, value_name = "NAME", conflicts_with = "all_remotes")]
+        remote_name: Option<String>,
+
+        /// Count the branches of every configured remote
+        #[arg(long)]
+        all_remotes: bool,
+    },
+
+    /// Count the stale branches `list` would show, fast enough for a shell prompt
+    /// (never fetches, and skips the slower merge checks)
+    Status {
+        /// Print only the count, for a prompt segment; prints nothing when there
+        /// are none, or when counting takes too long or fails
+        #[arg(long)]
+        short: bool,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = StatusFormat::Text, conflicts_with = "short")]
+        format: StatusFormat,
+    },
+
+    /// Show which release branches already contain each stale branch
+    Audit {
+        /// Release branch name or glob, e.g. 'release/*' (repeatable)
+        #[arg(
+            long,
+            value_name = "PATTERN",
+            required = true,
+            value_parser = clap::builder::NonEmptyStringValueParser::new()
+        )]
+        release_pattern: Vec<String>,
+
+        /// Only audit branches older than this many days, or a duration like 3w, 6m, 1y
+        /// (default: from config or 30)
+        #[arg(short, long, value_parser = crate::duration::parse_days)]
+        days: Option<u32>,
+
+        /// Only audit local branches
+        #[arg(long)]
+        local: bool,
+
+        /// Only audit remote branches
+        #[arg(long, conflicts_with = "local")]
+        remote: bool,
+
+        /// Audit the branches of this remote instead of origin
+        #[arg(long, value_name = "NAME", conflicts_with = "all_remotes")]
+        remote_name: Option<String>,
+
+        /// Audit the branches of every configured remote
+        #[arg(long)]
+        all_remotes: bool,
+    },
+
+    /// Print cron and systemd timer snippets for running `prune --yes` here periodically
+    /// (nothing is installed)
+    Schedule {
+        /// Print the snippets (the default)
+        #[arg(long, conflicts_with = "install_hint")]
+        show: bool,
+
+        /// Explain the steps to install one of the snippets
+        #[arg(long)]
+        install_hint: bool,
+    },
+
+    /// Interactively set up the configuration file
+    Init {
+        /// Write the default configuration without asking anything
+        #[arg(long)]
+        defaults: bool,
     },
 
     /// Generate shell completion scripts
@@ -95,17 +379,144 @@ pub enum Commands {
     },
 }
 
+/// Output format for commands that can be consumed by other tools
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human-readable tables
+    Table,
+    /// Prometheus text exposition format (counts only)
+    Metrics,
+    /// JSON array of branches, with the fields --format-string offers
+    Json,
+}
+
+/// Which remotes' branches `list` and `clean` look at
+#[derive(Args, Debug, Clone, Default)]
+pub struct RemoteArgs {
+    /// Only look at the branches of this remote (default: origin)
+    #[arg(long, value_name = "NAME", conflicts_with = "all_remotes")]
+    pub remote_name: Option<String>,
+
+    /// Look at the branches of every configured remote
+    #[arg(long)]
+    pub all_remotes: bool,
+
+    /// Don't contact remotes: skip `git fetch --prune` before deleting remote branches
+    #[arg(long)]
+    pub offline: bool,
+}
+
+/// Limits on how many `/`-separated segments a branch name (without the
+/// remote) has: `feature/team/x` has 3
+#[derive(Args, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DepthArgs {
+    /// Only branches whose name has at least N `/`-separated segments
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub min_depth: Option<u32>,
+
+    /// Only branches whose name has at most N `/`-separated segments
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub max_depth: Option<u32>,
+}
+
+/// Limits on how many commits a branch has that the default branch doesn't
+/// (`git rev-list --count <default>..<branch>`)
+#[derive(Args, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommitCountArgs {
+    /// Only branches with at least N commits not on the default branch
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    pub min_commits: Option<u32>,
+
+    /// Only branches with at most N commits not on the default branch
+    #[arg(long, value_name = "N")]
+    pub max_commits: Option<u32>,
+}
+
+/// How `main` reports the error that ended a run
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum ErrorFormat {
+    /// A styled message, with suggestions where there are any
+    #[default]
+    Text,
+    /// One JSON object with the message and a stable `kind`
+    Json,
+}
+
+/// Output format for `status`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum StatusFormat {
+    /// One line for people
+    #[default]
+    Text,
+    /// The counts as JSON, for richer prompts
+    Json,
+}
+
+/// Output format for backup commands
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum BackupFormat {
+    /// Human-readable tables
+    #[default]
+    Table,
+    /// JSON for scripts and dashboards
+    Json,
+}
+
+/// Preset flag combinations for `clean`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum Preset {
+    /// Merged local branches only
+    Safe,
+    /// Merged and unmerged branches, local and remote (still asks for confirmation)
+    Aggressive,
+}
+
+/// Sections of the config file, for `config reset --section`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum ConfigSection {
+    /// [general]: default days, default command and scope
+    General,
+    /// [branches]: default branch, protected branches and exclude patterns
+    Branches,
+    /// [ui]: table width, SHA length, compact mode and symbols
+    Ui,
+    /// [backups]: custom backup file template
+    Backups,
+}
+
+/// Sort order for `backup list`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum BackupSort {
+    /// Newest first
+    #[default]
+    Age,
+    /// Most branches first (newest first among equals)
+    Branches,
+}
+
 #[derive(Subcommand)]
 pub enum ConfigAction {
     /// Set a configuration value
     Set {
-        /// Configuration key (e.g., default-days, protected-branches, default-branch, exclude-patterns)
+        /// Configuration key (e.g., default-days, default-command, protected-branches, default-branch, exclude-patterns)
         key: String,
 
         /// Configuration value(s) - use multiple arguments for lists
         /// Example: config set exclude-patterns "wip/*" "draft/*" "temp/*"
         #[arg(required = true, num_args = 1..)]
         values: Vec<String>,
+
+        /// Add the values to a list key instead of replacing it
+        #[arg(long)]
+        append: bool,
+
+        /// Remove the values from a list key
+        #[arg(long, conflicts_with = "append")]
+        remove: bool,
+
+        /// Don't ask for confirmation when a list gets shorter
+        #[arg(short = 'y', long)]
+        yes: bool,
     },
 
     /// Show current configuration
@@ -114,8 +525,50 @@ pub enum ConfigAction {
     /// Open config file in $EDITOR
     Edit,
 
-    /// Reset configuration to defaults
-    Reset,
+    /// Reset configuration to defaults (the previous config is kept as config.toml.bak)
+    Reset {
+        /// Only reset this section, keeping the others
+        #[arg(long, value_enum)]
+        section: Option<ConfigSection>,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Rewrite the config file in the current format, moving legacy keys
+    /// (the previous config is kept as config.toml.bak)
+    Migrate {
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
+
+    /// Check a configuration file for errors and likely mistakes
+    Validate {
+        /// Configuration file to check (default: the active config)
+        path: Option<PathBuf>,
+    },
+
+    /// Write the current configuration to a file for sharing
+    Export {
+        /// Destination file (e.g. team-deadbranch.toml)
+        path: PathBuf,
+
+        /// Overwrite the destination if it already exists
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Validate a configuration file and make it the active configuration
+    Import {
+        /// Configuration file to import
+        path: PathBuf,
+
+        /// Skip confirmation prompt
+        #[arg(short = 'y', long)]
+        yes: bool,
+    },
 }
 
 #[derive(Subcommand)]
@@ -129,14 +582,51 @@ pub enum BackupAction {
         /// Show backups for a specific repository by name
         #[arg(long)]
         repo: Option<String>,
+
+        /// Only show backups created on or after this date (YYYY-MM-DD),
+        /// or within this duration (e.g. 1w)
+        #[arg(long, value_name = "WHEN")]
+        since: Option<String>,
+
+        /// Only show backups created before this date (YYYY-MM-DD),
+        /// or more than this duration ago (e.g. 3m)
+        #[arg(long, value_name = "WHEN")]
+        before: Option<String>,
+
+        /// Order backups by creation time (newest first) or branch count (most first)
+        #[arg(long, value_enum, default_value_t = BackupSort::Age)]
+        sort: BackupSort,
+
+        /// Show at most N backups (per repository in the summary view)
+        #[arg(long, value_name = "N")]
+        limit: Option<usize>,
+
+        /// Only show backups containing a branch matching this glob pattern
+        #[arg(long, value_name = "PATTERN")]
+        contains: Option<String>,
+
+        /// Only show backups whose label contains this text (case-insensitive)
+        #[arg(long, value_name = "TEXT")]
+        label: Option<String>,
+
+        /// Output format
+        #[arg(long, value_enum, default_value_t = BackupFormat::Table)]
+        format: BackupFormat,
     },
 
     /// Restore a branch from backup
     Restore {
         /// Name of the branch to restore
-        branch: String,
+        #[arg(required_unless_present = "matching")]
+        branch: Option<String>,
+
+        /// Restore every branch in the backup whose name matches this glob,
+        /// e.g. 'release/2.*' (--force then applies to each of them)
+        #[arg(long, value_name = "GLOB", conflicts_with_all = ["branch", "as"])]
+        matching: Option<String>,
 
-        /// Restore from a specific backup file (defaults to most recent)
+        /// Backup to restore from: a file, `latest`, or the # shown by `backup list --current`
+        /// (defaults to most recent)
         #[arg(long)]
         from: Option<String>,
 
@@ -147,10 +637,39 @@ pub enum BackupAction {
         /// Overwrite existing branch if it exists
         #[arg(long)]
         force: bool,
+
+        /// Look for the backup among this repository's backups (by name, as in
+        /// `backup list --repo`); refused unless it is the current repository
+        /// or --allow-cross-repo is given
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Restore even if the backup was recorded for a different repository
+        #[arg(long, visible_alias = "allow-cross-repo")]
+        ignore_repo_mismatch: bool,
+
+        /// When the branch was deleted from a remote, push it back there after
+        /// restoring it (asked interactively otherwise)
+        #[arg(long)]
+        push: bool,
+
+        /// Skip the confirmation before restoring the branches --matching selects
+        #[arg(short, long, requires = "matching")]
+        yes: bool,
     },
 
     /// Show backup storage statistics
-    Stats,
+    Stats {
+        /// Output format
+        #[arg(long, value_enum, default_value_t = BackupFormat::Table)]
+        format: BackupFormat,
+    },
+
+    /// Show the branches recorded in a backup
+    Show {
+        /// Backup file to show (defaults to the most recent for the current repository)
+        backup: Option<String>,
+    },
 
     /// Remove old backups, keeping the most recent ones
     Clean {
@@ -166,6 +685,11 @@ pub enum BackupAction {
         #[arg(long, default_value = "10")]
         keep: usize,
 
+        /// Also keep backups newer than N days (or a duration like 3m); a backup is only
+        /// removed when it is beyond --keep AND older than this
+        #[arg(long, value_name = "DAYS", value_parser = crate::duration::parse_days)]
+        keep_days: Option<u32>,
+
         /// Show what would be deleted without doing it
         #[arg(long)]
         dry_run: bool,
@@ -173,5 +697,44 @@ pub enum BackupAction {
         /// Skip confirmation prompt
         #[arg(short, long)]
         yes: bool,
+
+        /// Pick the backups to delete from a checklist (pre-checked per the keep rules)
+        #[arg(short, long, conflicts_with = "yes")]
+        interactive: bool,
+
+        /// Output format (json is only available with --dry-run)
+        #[arg(long, value_enum, default_value_t = BackupFormat::Table, conflicts_with = "interactive")]
+        format: BackupFormat,
+    },
+
+    /// Check backups for truncation or modification (checksums) and parse errors
+    Verify {
+        /// Only verify backups for current repository
+        #[arg(long, conflicts_with = "repo")]
+        current: bool,
+
+        /// Only verify backups for a specific repository by name
+        #[arg(long)]
+        repo: Option<String>,
     },
+
+    /// Rewrite backups from older deadbranch versions in the current format
+    Migrate {
+        /// Only migrate backups for current repository
+        #[arg(long, conflicts_with = "repo")]
+        current: bool,
+
+        /// Only migrate backups for a specific repository by name
+        #[arg(long)]
+        repo: Option<String>,
+    },
+}
+
+/// Validate a backup label: it is stored as a single header line
+fn parse_label(s: &str) -> Result<String, String> {
+    if s.contains(['\n', '\r']) {
+        Err("labels must be a single line".to_string())
+    } else {
+        Ok(s.to_string())
+    }
 }
@@ -1,9 +1,16 @@
 //! Configuration handling for deadbranch
 
 use anyhow::{Context, Result};
+use chrono::Local;
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::atomic_write;
+use crate::branch::{parse_glob, GlobToken};
+use crate::cli::ConfigSection;
+use crate::policy::Policy;
 
 /// Default number of days before a branch is considered stale
 const DEFAULT_DAYS: u32 = 30;
@@ -22,18 +29,186 @@ const DEFAULT_PROTECTED: &[&str] = &[
 /// Default exclude patterns (WIP/draft branches)
 const DEFAULT_EXCLUDE_PATTERNS: &[&str] = &["wip/*", "draft/*", "*/wip", "*/draft"];
 
+/// Characters git never allows in a branch name (see `git check-ref-format`)
+/// other than the glob metacharacters `?` and `[`; an exclude pattern with
+/// one outside a `[...]` set can never match anything
+const CHARS_NEVER_IN_BRANCH_NAMES: &[char] = &[' ', '~', '^', ':', '\\'];
+
+/// Current config schema version, written as `version` at the top of the file
+pub const CONFIG_VERSION: u32 = 1;
+
+/// Environment variable overriding the deadbranch directory (~/.deadbranch)
+pub const DEADBRANCH_HOME_ENV: &str = "DEADBRANCH_HOME";
+
+/// Environment variable naming the config file; backups go next to it
+/// unless `DEADBRANCH_HOME` is also set
+pub const DEADBRANCH_CONFIG_ENV: &str = "DEADBRANCH_CONFIG";
+
+static IGNORE_FILE: AtomicBool = AtomicBool::new(false);
+
+/// Use built-in defaults instead of the config file (from the global
+/// `--ignore-config` flag)
+pub fn set_ignore_file(enabled: bool) {
+    IGNORE_FILE.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--ignore-config` is in effect
+pub fn ignores_file() -> bool {
+    IGNORE_FILE.load(Ordering::Relaxed)
+}
+
+/// Fail if `--ignore-config` is in effect; `action` describes the refused
+/// change (e.g. "write the config file")
+pub fn ensure_file_in_use(action: &str) -> Result<()> {
+    if ignores_file() {
+        anyhow::bail!("Refusing to {}: --ignore-config is in effect", action);
+    }
+    Ok(())
+}
+
+/// Where a legacy key was found: `(section, key)`, with `None` for the top level
+type LegacyKey = (Option<&'static str>, &'static str);
+
+/// Keys that used to live elsewhere (or that users commonly put in the wrong
+/// place), mapped to their current `(section, key)`. Keys are matched after
+/// replacing `-` with `_`.
+const LEGACY_KEYS: &[(LegacyKey, (&str, &str))] = &[
+    ((None, "days"), ("general", "default_days")),
+    ((None, "default_days"), ("general", "default_days")),
+    ((None, "default_branch"), ("branches", "default_branch")),
+    ((None, "protected"), ("branches", "protected")),
+    ((None, "protected_branches"), ("branches", "protected")),
+    ((None, "exclude_patterns"), ("branches", "exclude_patterns")),
+    ((Some("general"), "days"), ("general", "default_days")),
+    (
+        (Some("general"), "default_branch"),
+        ("branches", "default_branch"),
+    ),
+    ((Some("general"), "protected"), ("branches", "protected")),
+    (
+        (Some("general"), "protected_branches"),
+        ("branches", "protected"),
+    ),
+    (
+        (Some("general"), "exclude_patterns"),
+        ("branches", "exclude_patterns"),
+    ),
+    ((Some("branches"), "days"), ("general", "default_days")),
+    (
+        (Some("branches"), "default_days"),
+        ("general", "default_days"),
+    ),
+    (
+        (Some("branches"), "protected_branches"),
+        ("branches", "protected"),
+    ),
+];
+
+/// Per-repository file of extra exclude globs, read from the repository root
+pub const KEEP_FILE: &str = ".deadbranch-keep";
+
+/// Places GitHub looks for a CODEOWNERS file, relative to the repository root
+const CODEOWNERS_PATHS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS"];
+
+/// Comment prefix marking exclude globs in a CODEOWNERS file
+const CODEOWNERS_KEEP_DIRECTIVE: &str = "deadbranch-keep:";
+
+/// Command run by a bare `deadbranch`
+const DEFAULT_COMMAND: &str = "list";
+
+/// Commands that `general.default_command` may name (ones that work with no arguments)
+pub const DEFAULT_COMMAND_CHOICES: &[&str] = &["list", "clean", "stats"];
+
+/// Which branches `list` and `clean` cover when neither `--local` nor `--remote` is given
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Scope {
+    Local,
+    Remote,
+    #[default]
+    Both,
+}
+
+impl Scope {
+    /// Turn the `--local`/`--remote` flags into `(local_only, remote_only)`,
+    /// falling back to this scope when neither flag was passed
+    pub fn resolve(self, local_only: bool, remote_only: bool) -> (bool, bool) {
+        if local_only || remote_only {
+            return (local_only, remote_only);
+        }
+        match self {
+            Scope::Local => (true, false),
+            Scope::Remote => (false, true),
+            Scope::Both => (false, false),
+        }
+    }
+
+    /// The config value (`local`, `remote` or `both`)
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Scope::Local => "local",
+            Scope::Remote => "remote",
+            Scope::Both => "both",
+        }
+    }
+}
+
 /// General settings section
 #[derive(Debug, Deserialize, Serialize)]
 pub struct GeneralConfig {
-    /// Default age threshold (days)
-    #[serde(default = "default_days")]
+    /// Default age threshold (days; a duration string like "3w" is also accepted)
+    #[serde(default = "default_days", deserialize_with = "deserialize_days")]
     pub default_days: u32,
+
+    /// Age threshold for local branches, instead of `default_days`
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_days",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_days_local: Option<u32>,
+
+    /// Age threshold for remote branches, instead of `default_days`
+    #[serde(
+        default,
+        deserialize_with = "deserialize_optional_days",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub default_days_remote: Option<u32>,
+
+    /// Command to run when `deadbranch` is invoked without one
+    #[serde(default = "default_command")]
+    pub default_command: String,
+
+    /// Branches `list` and `clean` cover unless `--local` or `--remote` is passed
+    #[serde(default)]
+    pub default_scope: Scope,
+
+    /// Give up on confirmation prompts nobody answers after this many seconds
+    /// (wait forever if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_timeout_secs: Option<u64>,
+
+    /// Never contact remotes before deleting remote branches, as if `--offline` was passed
+    #[serde(default)]
+    pub offline: bool,
+
+    /// Name to file backups under instead of the repository's directory name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repo_name: Option<String>,
 }
 
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
             default_days: default_days(),
+            default_days_local: None,
+            default_days_remote: None,
+            default_command: default_command(),
+            default_scope: Scope::default(),
+            prompt_timeout_secs: None,
+            offline: false,
+            repo_name: None,
         }
     }
 }
@@ -52,6 +227,34 @@ pub struct BranchesConfig {
     /// Branch name patterns to exclude (glob-style: wip/*, */draft, etc.)
     #[serde(default = "default_exclude_patterns")]
     pub exclude_patterns: Vec<String>,
+
+    /// Never delete unmerged remote branches, even with `--force`
+    /// (unless `--force-remote-unmerged` is passed)
+    #[serde(default = "default_true")]
+    pub remote_require_merged: bool,
+
+    /// Delete remote branches with one `git push --delete` each, this many at a
+    /// time, instead of one batched push per remote (batched if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_delete_jobs: Option<usize>,
+
+    /// Which branches `clean` deletes without `--force`, e.g. `merged || gone`
+    /// (see `policy.rs`; only merged branches if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delete_when: Option<String>,
+}
+
+impl BranchesConfig {
+    /// The parsed `delete_when` policy, if one is set
+    pub fn delete_policy(&self) -> Result<Option<Policy>> {
+        self.delete_when
+            .as_deref()
+            .map(|expr| {
+                Policy::parse(expr)
+                    .map_err(|e| anyhow::anyhow!("Invalid branches.delete_when '{}': {}", expr, e))
+            })
+            .transpose()
+    }
 }
 
 impl Default for BranchesConfig {
@@ -60,28 +263,324 @@ impl Default for BranchesConfig {
             default_branch: None,
             protected: default_protected_branches(),
             exclude_patterns: default_exclude_patterns(),
+            remote_require_merged: true,
+            remote_delete_jobs: None,
+            delete_when: None,
+        }
+    }
+}
+
+/// Backup file settings section
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub struct BackupsConfig {
+    /// Template file rendered for each branch in a backup, in place of the
+    /// built-in `git branch <name> <sha>` block (built-in format if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub template: Option<PathBuf>,
+
+    /// Larger backup files are skipped with a warning instead of being read
+    /// (`DEFAULT_MAX_FILE_SIZE_MB` if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_file_size_mb: Option<u64>,
+}
+
+impl BackupsConfig {
+    /// Size limit for reading backup files, in megabytes
+    pub fn max_file_size_mb(&self) -> u64 {
+        self.max_file_size_mb
+            .unwrap_or(crate::backup::DEFAULT_MAX_FILE_SIZE_MB)
+    }
+}
+
+/// Most parallel `git push --delete` calls `branches.remote_delete_jobs` allows,
+/// so a large cleanup can't flood the server
+pub const MAX_REMOTE_DELETE_JOBS: usize = 16;
+
+/// Narrowest `ui.max_branch_width` accepted; anything smaller makes names unreadable
+pub const MIN_BRANCH_WIDTH: usize = 10;
+
+/// Table display settings section
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UiConfig {
+    /// Widest the Branch column may grow before names wrap (no limit if not set)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_branch_width: Option<usize>,
+
+    /// Show full commit SHAs instead of the first 8 characters
+    #[serde(default)]
+    pub full_sha: bool,
+
+    /// Leave out the separator lines between table rows
+    #[serde(default)]
+    pub compact: bool,
+
+    /// Prefix messages with emoji/symbols; `false` uses text such as `OK:` and `WARN:`
+    #[serde(default = "default_true")]
+    pub symbols: bool,
+}
+
+impl Default for UiConfig {
+    fn default() -> Self {
+        Self {
+            max_branch_width: None,
+            full_sha: false,
+            compact: false,
+            symbols: true,
         }
     }
 }
 
 /// Configuration for deadbranch
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Schema version of the file; missing in files written before versioning
+    #[serde(default)]
+    pub version: u32,
+
     #[serde(default)]
     pub general: GeneralConfig,
 
     #[serde(default)]
     pub branches: BranchesConfig,
+
+    #[serde(default)]
+    pub ui: UiConfig,
+
+    #[serde(default)]
+    pub backups: BackupsConfig,
+
+    /// Legacy keys that were moved to their current location while loading
+    #[serde(skip)]
+    pub migration_notes: Vec<String>,
+
+    /// Invalid `[ui]` values that were replaced by their defaults while loading
+    #[serde(skip)]
+    pub ui_notes: Vec<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            general: GeneralConfig::default(),
+            branches: BranchesConfig::default(),
+            ui: UiConfig::default(),
+            backups: BackupsConfig::default(),
+            migration_notes: Vec::new(),
+            ui_notes: Vec::new(),
+        }
+    }
+}
+
+impl GeneralConfig {
+    /// The age threshold for local or remote branches: `default_days_local`
+    /// or `default_days_remote` when set, `default_days` otherwise
+    pub fn days_for(&self, remote: bool) -> u32 {
+        let scoped = if remote {
+            self.default_days_remote
+        } else {
+            self.default_days_local
+        };
+        scoped.unwrap_or(self.default_days)
+    }
+}
+
+/// Accept either a number of days or a duration string such as "3w" or "6m"
+fn deserialize_days<'de, D>(deserializer: D) -> std::result::Result<u32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Days {
+        Number(u32),
+        Duration(String),
+    }
+
+    match Days::deserialize(deserializer)? {
+        Days::Number(days) => Ok(days),
+        Days::Duration(s) => crate::duration::parse_days(&s).map_err(serde::de::Error::custom),
+    }
+}
+
+fn deserialize_optional_days<'de, D>(deserializer: D) -> std::result::Result<Option<u32>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize_days(deserializer).map(Some)
+}
+
+/// A `config set` value for an optional day count; `""` unsets it
+fn parse_optional_days(key: &str, value: &str) -> Result<Option<u32>> {
+    if value.is_empty() {
+        return Ok(None);
+    }
+    crate::duration::parse_days(value)
+        .map(Some)
+        .map_err(|e| anyhow::anyhow!("Invalid {}: {}", key, e))
 }
 
 fn default_days() -> u32 {
     DEFAULT_DAYS
 }
 
+fn default_command() -> String {
+    DEFAULT_COMMAND.to_string()
+}
+
+/// Drop invalid values from the `[ui]` section of a parsed config file so they
+/// fall back to their defaults, returning a note for each one
+fn sanitize_ui_section(table: &mut toml::Table) -> Vec<String> {
+    let mut notes = Vec::new();
+    let Some(section) = table.get_mut("ui") else {
+        return notes;
+    };
+    let Some(ui) = section.as_table_mut() else {
+        notes.push("[ui] is not a table; using the default display settings".to_string());
+        table.remove("ui");
+        return notes;
+    };
+
+    let keys: Vec<String> = ui.keys().cloned().collect();
+    for key in keys {
+        let value = &ui[&key];
+        let expected = match key.as_str() {
+            "max_branch_width" => match value.as_integer() {
+                Some(width) if width >= MIN_BRANCH_WIDTH as i64 => continue,
+                _ => format!("a whole number of at least {}", MIN_BRANCH_WIDTH),
+            },
+            "full_sha" | "compact" | "symbols" if value.is_bool() => continue,
+            "full_sha" | "compact" | "symbols" => "true or false".to_string(),
+            _ => continue,
+        };
+        notes.push(format!(
+            "ui.{} = {} is invalid (expected {}); using the default",
+            key, value, expected
+        ));
+        ui.remove(&key);
+    }
+    notes
+}
+
+/// Move legacy keys in a parsed config file to their current location,
+/// returning a note for each one
+fn migrate_legacy_keys(table: &mut toml::Table) -> Vec<String> {
+    let path = |section: Option<&str>, key: &str| match section {
+        Some(section) => format!("{}.{}", section, key),
+        None => key.to_string(),
+    };
+
+    let mut notes = Vec::new();
+    for section in [None, Some("general"), Some("branches")] {
+        let keys: Vec<String> = match section {
+            None => table.keys().cloned().collect(),
+            Some(name) => match table.get(name).and_then(|v| v.as_table()) {
+                Some(section_table) => section_table.keys().cloned().collect(),
+                None => continue,
+            },
+        };
+
+        for key in keys {
+            let normalized = key.replace('-', "_");
+            let target = LEGACY_KEYS
+                .iter()
+                .find(|(from, _)| *from == (section, normalized.as_str()))
+                .map(|(_, (to_section, to_key))| (to_section.to_string(), to_key.to_string()))
+                .or_else(|| match section {
+                    Some(name) if key != normalized => Some((name.to_string(), normalized)),
+                    _ => None,
+                });
+            let Some((to_section, to_key)) = target else {
+                continue;
+            };
+
+            let value = match section {
+                None => table.remove(&key),
+                Some(name) => table
+                    .get_mut(name)
+                    .and_then(|v| v.as_table_mut())
+                    .and_then(|t| t.remove(&key)),
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            let Some(target_table) = table
+                .entry(to_section.clone())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+            else {
+                continue;
+            };
+
+            if target_table.contains_key(&to_key) {
+                notes.push(format!(
+                    "ignored legacy key '{}' because '{}' is also set",
+                    path(section, &key),
+                    path(Some(&to_section), &to_key)
+                ));
+            } else {
+                target_table.insert(to_key.clone(), value);
+                notes.push(format!(
+                    "migrated key '{}' -> '{}'",
+                    path(section, &key),
+                    path(Some(&to_section), &to_key)
+                ));
+            }
+        }
+    }
+    notes
+}
+
+/// Parse a `.deadbranch-keep` file: one glob per line, blank lines and `#` comments ignored
+pub fn parse_keep_file(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Exclude globs from a CODEOWNERS file's `# deadbranch-keep: <glob>...` comments.
+/// Ownership rules themselves match file paths, not branches, and are ignored.
+pub fn parse_codeowners_keep(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix('#'))
+        .filter_map(|comment| comment.trim_start().strip_prefix(CODEOWNERS_KEEP_DIRECTIVE))
+        .flat_map(str::split_whitespace)
+        .map(str::to_string)
+        .collect()
+}
+
+/// Check that an exclude pattern could match some branch name
+fn validate_pattern(pattern: &str) -> Result<()> {
+    if pattern.trim().is_empty() {
+        anyhow::bail!("Invalid exclude pattern '{}': pattern is empty", pattern);
+    }
+    let tokens = parse_glob(pattern)
+        .map_err(|problem| anyhow::anyhow!("Invalid exclude pattern '{}': {}", pattern, problem))?;
+    if let Some(c) = tokens.iter().find_map(|token| match token {
+        GlobToken::Literal(c) if CHARS_NEVER_IN_BRANCH_NAMES.contains(c) => Some(*c),
+        _ => None,
+    }) {
+        anyhow::bail!(
+            "Invalid exclude pattern '{}': branch names cannot contain '{}', so it would never match",
+            pattern,
+            c
+        );
+    }
+    Ok(())
+}
+
 fn default_protected_branches() -> Vec<String> {
     DEFAULT_PROTECTED.iter().map(|s| s.to_string()).collect()
 }
 
+fn default_true() -> bool {
+    true
+}
+
 fn default_exclude_patterns() -> Vec<String> {
     DEFAULT_EXCLUDE_PATTERNS
         .iter()
@@ -90,15 +589,45 @@ fn default_exclude_patterns() -> Vec<String> {
 }
 
 impl Config {
-    /// Get the main deadbranch directory (~/.deadbranch)
+    /// Get the main deadbranch directory (`$DEADBRANCH_HOME`, the directory
+    /// of `$DEADBRANCH_CONFIG`, or ~/.deadbranch)
     pub fn deadbranch_dir() -> Result<PathBuf> {
+        if let Some(dir) = std::env::var_os(DEADBRANCH_HOME_ENV).filter(|d| !d.is_empty()) {
+            return Ok(PathBuf::from(dir));
+        }
+        if let Some(path) = Self::config_path_from_env()? {
+            return path
+                .parent()
+                .map(Path::to_path_buf)
+                .with_context(|| format!("{} has no parent directory", DEADBRANCH_CONFIG_ENV));
+        }
         let home = dirs::home_dir().context("Could not determine home directory")?;
         Ok(home.join(".deadbranch"))
     }
 
-    /// Get the path to the config file (~/.deadbranch/config.toml)
+    /// Get the path to the config file (`$DEADBRANCH_CONFIG`, or
+    /// ~/.deadbranch/config.toml)
     pub fn config_path() -> Result<PathBuf> {
-        Ok(Self::deadbranch_dir()?.join("config.toml"))
+        match Self::config_path_from_env()? {
+            Some(path) => Ok(path),
+            None => Ok(Self::deadbranch_dir()?.join("config.toml")),
+        }
+    }
+
+    /// `$DEADBRANCH_CONFIG`, which must be an absolute path
+    fn config_path_from_env() -> Result<Option<PathBuf>> {
+        let Some(path) = std::env::var_os(DEADBRANCH_CONFIG_ENV).filter(|p| !p.is_empty()) else {
+            return Ok(None);
+        };
+        let path = PathBuf::from(path);
+        if !path.is_absolute() {
+            anyhow::bail!(
+                "{} must be an absolute path, got '{}'",
+                DEADBRANCH_CONFIG_ENV,
+                path.display()
+            );
+        }
+        Ok(Some(path))
     }
 
     /// Get the backups directory (~/.deadbranch/backups)
@@ -106,59 +635,335 @@ impl Config {
         Ok(Self::deadbranch_dir()?.join("backups"))
     }
 
-    /// Get the backup directory for a specific repository
+    /// Get the backup directory for a specific repository. A submodule's
+    /// `parent/name` is joined component-wise so the path uses native separators.
     pub fn repo_backup_dir(repo_name: &str) -> Result<PathBuf> {
-        Ok(Self::backups_dir()?.join(repo_name))
+        Ok(repo_name
+            .split('/')
+            .fold(Self::backups_dir()?, |dir, part| dir.join(part)))
     }
 
-    /// Get the current repository name (uses directory name)
+    /// Existing backup directories whose names equal `repo_name` apart from
+    /// case. On case-insensitive filesystems (macOS, Windows) they are the
+    /// same directory, so the two repositories' backups would mix.
+    pub fn case_colliding_backup_dirs(repo_name: &str) -> Vec<String> {
+        let Ok(dir) = Self::repo_backup_dir(repo_name) else {
+            return Vec::new();
+        };
+        let (Some(parent), Some(own)) = (dir.parent(), dir.file_name()) else {
+            return Vec::new();
+        };
+        let own = own.to_string_lossy();
+        let Ok(entries) = fs::read_dir(parent) else {
+            return Vec::new();
+        };
+
+        let mut names: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|name| *name != own && name.to_lowercase() == own.to_lowercase())
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Get the current repository name: the directory name of the repository's
+    /// toplevel with symlinks resolved, so every path into it gives the same name
+    /// (the current directory's name outside a repository).
+    ///
+    /// Inside a submodule the superproject's directory name is prepended
+    /// (`parent/vendor`), so its backups aren't filed under a generic name.
+    /// `general.repo_name` replaces the directory name, e.g. to tell apart
+    /// repositories whose names differ only by case.
     pub fn get_repo_name() -> String {
-        std::env::current_dir()
+        if let Some(name) = Self::load_quietly()
             .ok()
+            .and_then(|config| config.general.repo_name)
+        {
+            return name;
+        }
+        let name = crate::git::get_repo_dir()
+            .map(|root| root.canonicalize().unwrap_or(root))
+            .or_else(|| std::env::current_dir().ok())
             .and_then(|path| {
                 path.file_name()
                     .and_then(|name| name.to_str())
                     .map(|s| s.to_string())
             })
-            .unwrap_or_else(|| "unknown-repo".to_string())
+            .unwrap_or_else(|| "unknown-repo".to_string());
+
+        match Self::get_superproject_name() {
+            Some(parent) => format!("{}/{}", parent, name),
+            None => name,
+        }
+    }
+
+    /// Directory name of the superproject when running inside a submodule
+    pub fn get_superproject_name() -> Option<String> {
+        crate::git::get_superproject_toplevel()?
+            .file_name()
+            .and_then(|name| name.to_str())
+            .map(|s| s.to_string())
+    }
+
+    /// Exclude patterns from the config plus any from the repository's
+    /// `.deadbranch-keep` and CODEOWNERS `# deadbranch-keep:` comments, which
+    /// can only add patterns, never remove them
+    pub fn exclude_patterns_for_repo(&self) -> Vec<String> {
+        let mut patterns = self.branches.exclude_patterns.clone();
+        let Some(root) = crate::git::get_repo_toplevel() else {
+            return patterns;
+        };
+        let keep_file = fs::read_to_string(root.join(KEEP_FILE))
+            .map(|content| parse_keep_file(&content))
+            .unwrap_or_default();
+        // Only the first CODEOWNERS found counts, as on GitHub
+        let codeowners = CODEOWNERS_PATHS
+            .iter()
+            .find_map(|path| fs::read_to_string(root.join(path)).ok())
+            .map(|content| parse_codeowners_keep(&content))
+            .unwrap_or_default();
+        for pattern in keep_file.into_iter().chain(codeowners) {
+            if !patterns.contains(&pattern) {
+                patterns.push(pattern);
+            }
+        }
+        patterns
     }
 
-    /// Load config from file, or create default config if file doesn't exist
+    /// Load config from file, or the defaults if there is no file yet. Nothing
+    /// is written: the file only appears once the user changes a setting.
     pub fn load() -> Result<Self> {
+        let config = Self::load_quietly()?;
+        if !config.migration_notes.is_empty() {
+            // Config is loaded more than once per run; say this only once. On
+            // stderr, so JSON output and `status --short` stay parseable.
+            static NOTICE: std::sync::Once = std::sync::Once::new();
+            NOTICE.call_once(|| {
+                for note in &config.migration_notes {
+                    crate::ui::warning_stderr(&format!("Config: {}", note));
+                }
+                crate::ui::follow_up_stderr(
+                    "Run `deadbranch config show` to review, then `deadbranch config migrate` to update the file",
+                );
+            });
+        }
+        Ok(config)
+    }
+
+    /// [`Config::load`] without the migration notice, for output that must be
+    /// nothing but a value (`status --short` in a shell prompt)
+    pub fn load_quietly() -> Result<Self> {
+        if ignores_file() {
+            return Ok(Config::default());
+        }
         let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(Config::default());
+        }
+        Self::load_from(&path)
+    }
 
-        if path.exists() {
-            let content = fs::read_to_string(&path)
-                .with_context(|| format!("Failed to read config file: {}", path.display()))?;
-            let config: Config = toml::from_str(&content)
-                .with_context(|| format!("Failed to parse config file: {}", path.display()))?;
-            Ok(config)
-        } else {
-            // Auto-create config file with defaults on first use
-            let config = Config::default();
-            config.save()?;
-            Ok(config)
+    /// Load config from a specific file (no defaults are written if it is missing)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Failed to parse config file: {}", path.display()))
+    }
+
+    /// Parse config file contents, moving legacy keys to their current place
+    /// (see `migration_notes`)
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut table: toml::Table = toml::from_str(content)?;
+        let notes = migrate_legacy_keys(&mut table);
+        let ui_notes = sanitize_ui_section(&mut table);
+        let mut config: Config = toml::Value::Table(table).try_into()?;
+        if config.version > CONFIG_VERSION {
+            anyhow::bail!(
+                "config version {} was written by a newer deadbranch (this one supports up to {})",
+                config.version,
+                CONFIG_VERSION
+            );
         }
+        config.migration_notes = notes;
+        config.ui_notes = ui_notes;
+        Ok(config)
+    }
+
+    /// Config for display purposes (table style, age colors): the file if it can be
+    /// read, defaults otherwise. Nothing is written and no notices are printed;
+    /// invalid `[ui]` values are left in `ui_notes`.
+    pub fn load_for_display() -> Self {
+        if ignores_file() {
+            return Config::default();
+        }
+        Self::config_path()
+            .ok()
+            .filter(|path| path.exists())
+            .and_then(|path| Self::load_from(&path).ok())
+            .unwrap_or_default()
+    }
+
+    /// Whether `config migrate` has anything to do for this file
+    pub fn needs_migration(&self) -> bool {
+        self.version < CONFIG_VERSION || !self.migration_notes.is_empty()
     }
 
     /// Save config to file
     pub fn save(&self) -> Result<()> {
-        let path = Self::config_path()?;
+        ensure_file_in_use("write the config file")?;
+        self.save_to(&Self::config_path()?)
+    }
+
+    /// Save config to a specific file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
+        crate::readonly::ensure_writable(&format!("write {}", path.display()))?;
 
         // Create directory if it doesn't exist
         if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent).with_context(|| {
-                format!("Failed to create config directory: {}", parent.display())
-            })?;
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).with_context(|| {
+                    format!("Failed to create config directory: {}", parent.display())
+                })?;
+            }
         }
 
         let content = toml::to_string_pretty(self).context("Failed to serialize config")?;
-        fs::write(&path, content)
+        // A crash mid-write must not leave a config that fails to parse next time
+        atomic_write::write(path, content.as_bytes())
             .with_context(|| format!("Failed to write config file: {}", path.display()))?;
 
         Ok(())
     }
 
+    /// Copy the active config file to `config.toml.bak`, returning the backup path
+    /// (None if there was no config file to back up). The copy starts with a
+    /// comment recording when and why it was taken, e.g. "config reset".
+    pub fn backup_active(reason: &str) -> Result<Option<PathBuf>> {
+        crate::readonly::ensure_writable("back up the active config")?;
+        ensure_file_in_use("back up the active config")?;
+        let path = Self::config_path()?;
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: {}", path.display()))?;
+        let backup_path = path.with_extension("toml.bak");
+        let header = format!(
+            "# deadbranch config saved {} before {}\n",
+            Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false),
+            reason
+        );
+        atomic_write::write(&backup_path, (header + &content).as_bytes()).with_context(|| {
+            format!("Failed to back up config file to {}", backup_path.display())
+        })?;
+        Ok(Some(backup_path))
+    }
+
+    /// Restore one section to its defaults, leaving the others untouched
+    pub fn reset_section(&mut self, section: ConfigSection) {
+        match section {
+            ConfigSection::General => self.general = GeneralConfig::default(),
+            ConfigSection::Branches => self.branches = BranchesConfig::default(),
+            ConfigSection::Ui => self.ui = UiConfig::default(),
+            ConfigSection::Backups => self.backups = BackupsConfig::default(),
+        }
+    }
+
+    /// Check that the config values are usable
+    pub fn validate(&self) -> Result<()> {
+        if !DEFAULT_COMMAND_CHOICES.contains(&self.general.default_command.as_str()) {
+            anyhow::bail!(
+                "general.default_command must be one of: {}",
+                DEFAULT_COMMAND_CHOICES.join(", ")
+            );
+        }
+
+        if self.general.prompt_timeout_secs == Some(0) {
+            anyhow::bail!(
+                "general.prompt_timeout_secs must be at least 1 (omit it to wait forever)"
+            );
+        }
+
+        if let Some(name) = &self.general.repo_name {
+            if name.trim().is_empty() || name == "." || name == ".." || name.contains(['/', '\\']) {
+                anyhow::bail!(
+                    "general.repo_name must be a single directory name, without '/' or '\\' (omit it to use the repository's directory name)"
+                );
+            }
+        }
+
+        if let Some(default_branch) = &self.branches.default_branch {
+            if default_branch.trim().is_empty() {
+                anyhow::bail!("branches.default_branch must not be empty (omit it to auto-detect)");
+            }
+        }
+
+        if let Some(jobs) = self.branches.remote_delete_jobs {
+            if !(1..=MAX_REMOTE_DELETE_JOBS).contains(&jobs) {
+                anyhow::bail!(
+                    "branches.remote_delete_jobs must be between 1 and {} (omit it to batch deletions)",
+                    MAX_REMOTE_DELETE_JOBS
+                );
+            }
+        }
+
+        self.branches.delete_policy()?;
+
+        if self.backups.max_file_size_mb == Some(0) {
+            anyhow::bail!(
+                "backups.max_file_size_mb must be at least 1 (omit it for the default of {})",
+                crate::backup::DEFAULT_MAX_FILE_SIZE_MB
+            );
+        }
+
+        if self.branches.protected.iter().any(|b| b.trim().is_empty()) {
+            anyhow::bail!("branches.protected must not contain empty branch names");
+        }
+
+        for pattern in &self.branches.exclude_patterns {
+            validate_pattern(pattern)
+                .map_err(|e| anyhow::anyhow!("branches.exclude_patterns: {}", e))?;
+        }
+
+        Ok(())
+    }
+
+    /// Settings that are valid but probably not what the user meant
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = self.ui_notes.clone();
+        for pattern in &self.branches.exclude_patterns {
+            if pattern.chars().all(|c| c == '*') {
+                warnings.push(format!(
+                    "Exclude pattern '{}' matches every branch, so nothing will be listed or cleaned",
+                    pattern
+                ));
+            } else if !pattern.contains('*')
+                && crate::branch::is_protected_name(pattern, &self.branches.protected)
+            {
+                warnings.push(format!(
+                    "Exclude pattern '{}' only matches the protected branch '{}', which is never deleted anyway",
+                    pattern, pattern
+                ));
+            }
+        }
+        warnings
+    }
+
+    /// Current value of a list-valued key (None for single-value keys)
+    pub fn list_value(&self, key: &str) -> Option<&[String]> {
+        match key {
+            "branches.protected" | "protected-branches" => Some(&self.branches.protected),
+            "branches.exclude-patterns" | "exclude-patterns" => {
+                Some(&self.branches.exclude_patterns)
+            }
+            _ => None,
+        }
+    }
+
     /// Set a configuration value by key (accepts multiple values for list types)
     /// Supports both flat keys (default-days) and dotted keys (general.default-days)
     pub fn set(&mut self, key: &str, values: &[String]) -> Result<()> {
@@ -168,9 +973,81 @@ impl Config {
                 if values.len() != 1 {
                     anyhow::bail!("default-days expects a single value");
                 }
-                self.general.default_days = values[0]
-                    .parse()
-                    .with_context(|| format!("Invalid number: {}", values[0]))?;
+                self.general.default_days = crate::duration::parse_days(&values[0])
+                    .map_err(|e| anyhow::anyhow!("Invalid default-days: {}", e))?;
+            }
+            "general.default-days-local" | "default-days-local" => {
+                if values.len() != 1 {
+                    anyhow::bail!("default-days-local expects a single value");
+                }
+                self.general.default_days_local =
+                    parse_optional_days("default-days-local", &values[0])?;
+            }
+            "general.default-days-remote" | "default-days-remote" => {
+                if values.len() != 1 {
+                    anyhow::bail!("default-days-remote expects a single value");
+                }
+                self.general.default_days_remote =
+                    parse_optional_days("default-days-remote", &values[0])?;
+            }
+            "general.default-command" | "default-command" => {
+                if values.len() != 1 {
+                    anyhow::bail!("default-command expects a single value");
+                }
+                if !DEFAULT_COMMAND_CHOICES.contains(&values[0].as_str()) {
+                    anyhow::bail!(
+                        "Invalid default-command: '{}'. Expected one of: {}",
+                        values[0],
+                        DEFAULT_COMMAND_CHOICES.join(", ")
+                    );
+                }
+                self.general.default_command = values[0].clone();
+            }
+            "general.default-scope" | "default-scope" => {
+                if values.len() != 1 {
+                    anyhow::bail!("default-scope expects a single value");
+                }
+                self.general.default_scope = match values[0].as_str() {
+                    "local" => Scope::Local,
+                    "remote" => Scope::Remote,
+                    "both" => Scope::Both,
+                    other => anyhow::bail!(
+                        "Invalid default-scope: '{}'. Expected one of: local, remote, both",
+                        other
+                    ),
+                };
+            }
+            "general.prompt-timeout-secs" | "prompt-timeout-secs" => {
+                if values.len() != 1 {
+                    anyhow::bail!("prompt-timeout-secs expects a single value");
+                }
+                self.general.prompt_timeout_secs = if values[0].is_empty() {
+                    None
+                } else {
+                    match values[0].parse::<u64>() {
+                        Ok(secs) if secs > 0 => Some(secs),
+                        _ => anyhow::bail!(
+                            "Invalid prompt-timeout-secs: '{}'. Expected a positive whole number of seconds (or \"\" to wait forever)",
+                            values[0]
+                        ),
+                    }
+                };
+            }
+            "general.repo-name" | "repo-name" => {
+                if values.len() != 1 {
+                    anyhow::bail!("repo-name expects a single value");
+                }
+                self.general.repo_name = (!values[0].is_empty()).then(|| values[0].clone());
+            }
+            "general.offline" | "offline" => {
+                if values.len() != 1 {
+                    anyhow::bail!("offline expects a single value");
+                }
+                self.general.offline = match values[0].as_str() {
+                    "true" => true,
+                    "false" => false,
+                    other => anyhow::bail!("Invalid offline: '{}'. Expected true or false", other),
+                };
             }
 
             // Branches section
@@ -191,13 +1068,130 @@ impl Config {
             }
             "branches.exclude-patterns" | "exclude-patterns" => {
                 // Filter out empty strings to allow clearing with ""
-                self.branches.exclude_patterns =
+                let patterns: Vec<String> =
                     values.iter().filter(|s| !s.is_empty()).cloned().collect();
+                for pattern in &patterns {
+                    validate_pattern(pattern)?;
+                }
+                self.branches.exclude_patterns = patterns;
+            }
+
+            "branches.remote-require-merged" | "remote-require-merged" => {
+                if values.len() != 1 {
+                    anyhow::bail!("remote-require-merged expects a single value");
+                }
+                self.branches.remote_require_merged = match values[0].as_str() {
+                    "true" => true,
+                    "false" => false,
+                    other => anyhow::bail!(
+                        "Invalid remote-require-merged: '{}'. Expected true or false",
+                        other
+                    ),
+                };
+            }
+
+            "branches.remote-delete-jobs" | "remote-delete-jobs" => {
+                if values.len() != 1 {
+                    anyhow::bail!("remote-delete-jobs expects a single value");
+                }
+                self.branches.remote_delete_jobs = if values[0].is_empty() {
+                    None
+                } else {
+                    match values[0].parse::<usize>() {
+                        Ok(jobs) if (1..=MAX_REMOTE_DELETE_JOBS).contains(&jobs) => Some(jobs),
+                        _ => anyhow::bail!(
+                            "Invalid remote-delete-jobs: '{}'. Expected a whole number from 1 to {} (or \"\" to batch deletions)",
+                            values[0],
+                            MAX_REMOTE_DELETE_JOBS
+                        ),
+                    }
+                };
+            }
+
+            "branches.delete-when" | "delete-when" => {
+                if values.len() != 1 {
+                    anyhow::bail!("delete-when expects a single value (quote the expression)");
+                }
+                self.branches.delete_when = if values[0].trim().is_empty() {
+                    None
+                } else {
+                    let policy = Policy::parse(&values[0]).map_err(|e| {
+                        anyhow::anyhow!("Invalid delete-when '{}': {}", values[0], e)
+                    })?;
+                    Some(policy.to_string())
+                };
+            }
+
+            // Backups section
+            "backups.template" | "backup-template" => {
+                if values.len() != 1 {
+                    anyhow::bail!("backup-template expects a single value");
+                }
+                self.backups.template = if values[0].is_empty() {
+                    None
+                } else {
+                    let path = PathBuf::from(&values[0]);
+                    crate::backup::BackupTemplate::load(&path)?;
+                    Some(path)
+                };
+            }
+            "backups.max-file-size-mb" | "max-file-size-mb" => {
+                if values.len() != 1 {
+                    anyhow::bail!("max-file-size-mb expects a single value");
+                }
+                self.backups.max_file_size_mb = if values[0].is_empty() {
+                    None
+                } else {
+                    match values[0].parse::<u64>() {
+                        Ok(mb) if mb >= 1 => Some(mb),
+                        _ => anyhow::bail!(
+                            "Invalid max-file-size-mb: '{}'. Expected a whole number of at least 1 (or \"\" for the default of {})",
+                            values[0],
+                            crate::backup::DEFAULT_MAX_FILE_SIZE_MB
+                        ),
+                    }
+                };
+            }
+
+            // UI section
+            "ui.max-branch-width" | "max-branch-width" => {
+                if values.len() != 1 {
+                    anyhow::bail!("max-branch-width expects a single value");
+                }
+                self.ui.max_branch_width = if values[0].is_empty() {
+                    None
+                } else {
+                    match values[0].parse::<usize>() {
+                        Ok(width) if width >= MIN_BRANCH_WIDTH => Some(width),
+                        _ => anyhow::bail!(
+                            "Invalid max-branch-width: '{}'. Expected a whole number of at least {} (or \"\" for no limit)",
+                            values[0],
+                            MIN_BRANCH_WIDTH
+                        ),
+                    }
+                };
+            }
+            "ui.full-sha" | "full-sha" | "ui.compact" | "compact" | "ui.symbols" | "symbols" => {
+                if values.len() != 1 {
+                    anyhow::bail!("{} expects a single value", key);
+                }
+                let enabled = match values[0].as_str() {
+                    "true" => true,
+                    "false" => false,
+                    other => anyhow::bail!("Invalid {}: '{}'. Expected true or false", key, other),
+                };
+                if key.ends_with("full-sha") {
+                    self.ui.full_sha = enabled;
+                } else if key.ends_with("compact") {
+                    self.ui.compact = enabled;
+                } else {
+                    self.ui.symbols = enabled;
+                }
             }
 
             _ => {
                 anyhow::bail!(
-                    "Unknown config key: {}. Valid keys: general.default-days, branches.protected, branches.default-branch, branches.exclude-patterns",
+                    "Unknown config key: {}. Valid keys: general.default-days, general.default-days-local, general.default-days-remote, general.default-command, general.default-scope, general.prompt-timeout-secs, general.offline, branches.protected, branches.default-branch, branches.exclude-patterns, branches.remote-require-merged, branches.remote-delete-jobs, branches.delete-when, backups.template, backups.max-file-size-mb, ui.max-branch-width, ui.full-sha, ui.compact, ui.symbols",
                     key
                 );
             }
@@ -291,6 +1285,285 @@ mod tests {
         assert_eq!(config.general.default_days, 75);
     }
 
+    #[test]
+    fn test_config_set_repo_name() {
+        let mut config = Config::default();
+        config.set("repo-name", &["renamed".to_string()]).unwrap();
+        assert_eq!(config.general.repo_name.as_deref(), Some("renamed"));
+        assert!(config.validate().is_ok());
+
+        for bad in ["a/b", "..", " "] {
+            config.set("general.repo-name", &[bad.to_string()]).unwrap();
+            assert!(config.validate().is_err(), "{:?} should be rejected", bad);
+        }
+
+        config.set("repo-name", &[String::new()]).unwrap();
+        assert_eq!(config.general.repo_name, None);
+    }
+
+    #[test]
+    fn test_config_default_days_accepts_durations() {
+        let mut config = Config::default();
+        config.set("default-days", &["3w".to_string()]).unwrap();
+        assert_eq!(config.general.default_days, 21);
+
+        let config: Config = toml::from_str("[general]\ndefault_days = \"6m\"\n").unwrap();
+        assert_eq!(config.general.default_days, 180);
+
+        let err = toml::from_str::<Config>("[general]\ndefault_days = \"12h\"\n").unwrap_err();
+        assert!(err.to_string().contains("shorter than a day"));
+    }
+
+    #[test]
+    fn test_config_set_default_command() {
+        let mut config = Config::default();
+        assert_eq!(config.general.default_command, "list");
+
+        config
+            .set("default-command", &["stats".to_string()])
+            .unwrap();
+        assert_eq!(config.general.default_command, "stats");
+        assert!(config.validate().is_ok());
+
+        let err = config
+            .set("default-command", &["backup".to_string()])
+            .unwrap_err();
+        assert!(err.to_string().contains("list, clean, stats"));
+        assert_eq!(config.general.default_command, "stats");
+
+        config.general.default_command = "nope".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_default_scope() {
+        let mut config = Config::default();
+        assert_eq!(config.general.default_scope, Scope::Both);
+
+        config.set("default-scope", &["local".to_string()]).unwrap();
+        assert_eq!(config.general.default_scope, Scope::Local);
+        assert!(config
+            .set("default-scope", &["everything".to_string()])
+            .is_err());
+
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("default_scope = \"local\""));
+        let config: Config = toml::from_str("[general]\ndefault_scope = \"remote\"\n").unwrap();
+        assert_eq!(config.general.default_scope, Scope::Remote);
+        assert!(toml::from_str::<Config>("[general]\ndefault_scope = \"all\"\n").is_err());
+    }
+
+    #[test]
+    fn test_scope_resolve_flags_override() {
+        assert_eq!(Scope::Local.resolve(false, false), (true, false));
+        assert_eq!(Scope::Remote.resolve(false, false), (false, true));
+        assert_eq!(Scope::Both.resolve(false, false), (false, false));
+        assert_eq!(Scope::Local.resolve(false, true), (false, true));
+        assert_eq!(Scope::Remote.resolve(true, false), (true, false));
+    }
+
+    #[test]
+    fn test_reset_section_keeps_other_sections() {
+        let mut config = Config::default();
+        config.set("default-days", &["60".to_string()]).unwrap();
+        config
+            .set("exclude-patterns", &["temp/*".to_string()])
+            .unwrap();
+
+        config.reset_section(ConfigSection::Branches);
+        assert_eq!(
+            config.branches.exclude_patterns,
+            BranchesConfig::default().exclude_patterns
+        );
+        assert_eq!(config.general.default_days, 60);
+
+        config.reset_section(ConfigSection::General);
+        assert_eq!(config.general.default_days, DEFAULT_DAYS);
+    }
+
+    #[test]
+    fn test_config_set_rejects_unmatchable_patterns() {
+        let mut config = Config::default();
+        for pattern in ["feature/[", "   ", "re:^wip", "my branch"] {
+            let err = config
+                .set(
+                    "exclude-patterns",
+                    &["ok/*".to_string(), pattern.to_string()],
+                )
+                .unwrap_err();
+            assert!(
+                err.to_string().contains(&format!("'{}'", pattern)),
+                "{}",
+                err
+            );
+        }
+        // Nothing was changed by the failed attempts
+        assert_eq!(
+            config.branches.exclude_patterns,
+            BranchesConfig::default().exclude_patterns
+        );
+
+        // "" still clears the list
+        config.set("exclude-patterns", &["".to_string()]).unwrap();
+        assert!(config.branches.exclude_patterns.is_empty());
+
+        // `?` and `[...]` are glob syntax, including `^` for negation inside a set
+        config
+            .set(
+                "exclude-patterns",
+                &[
+                    "hotfix-?".to_string(),
+                    "v[0-9]*".to_string(),
+                    "[^a]*".to_string(),
+                ],
+            )
+            .unwrap();
+        assert_eq!(config.branches.exclude_patterns.len(), 3);
+    }
+
+    #[test]
+    fn test_config_validate_and_warnings() {
+        let mut config = Config::default();
+        assert!(config.warnings().is_empty());
+
+        config.branches.exclude_patterns = vec!["develop".to_string(), "*".to_string()];
+        let warnings = config.warnings();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].contains("'develop'"));
+        assert!(warnings[1].contains("matches every branch"));
+        assert!(config.validate().is_ok());
+
+        config.branches.exclude_patterns = vec!["feature/[".to_string()];
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("'feature/['"));
+    }
+
+    #[test]
+    fn test_list_value() {
+        let config = Config::default();
+        assert_eq!(
+            config.list_value("branches.protected"),
+            Some(config.branches.protected.as_slice())
+        );
+        assert_eq!(
+            config.list_value("exclude-patterns"),
+            Some(config.branches.exclude_patterns.as_slice())
+        );
+        assert_eq!(config.list_value("default-days"), None);
+    }
+
+    #[test]
+    fn test_parse_keep_file() {
+        let content = "# my in-progress work\n\nfeature/spike-*\n  jane/*  \n# old/*\n";
+        assert_eq!(parse_keep_file(content), vec!["feature/spike-*", "jane/*"]);
+        assert!(parse_keep_file("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_codeowners_keep() {
+        let content = "# Owners\n*.rs @core\n# deadbranch-keep: release/* hotfix/*\n#deadbranch-keep:lts\n/docs/ @docs # deadbranch-keep: no\n";
+        assert_eq!(
+            parse_codeowners_keep(content),
+            vec!["release/*", "hotfix/*", "lts"]
+        );
+        assert!(parse_codeowners_keep("*.rs @core\n").is_empty());
+    }
+
+    /// Parse a legacy config, then check that writing it back out (as
+    /// `config migrate` does) yields a current file with the same values
+    fn migrate_round_trip(legacy: &str) -> Config {
+        let mut config = Config::parse(legacy).unwrap();
+        assert_eq!(config.version, 0);
+        assert!(config.needs_migration());
+
+        config.version = CONFIG_VERSION;
+        let migrated = Config::parse(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert!(!migrated.needs_migration(), "{:?}", migrated);
+        assert_eq!(migrated.general.default_days, config.general.default_days);
+        assert_eq!(migrated.branches.protected, config.branches.protected);
+        assert_eq!(
+            migrated.branches.exclude_patterns,
+            config.branches.exclude_patterns
+        );
+        assert_eq!(
+            migrated.branches.default_branch,
+            config.branches.default_branch
+        );
+        config
+    }
+
+    #[test]
+    fn test_migrate_unversioned_current_shape() {
+        let config = migrate_round_trip(
+            "[general]\ndefault_days = 45\n\n[branches]\nprotected = [\"main\"]\n",
+        );
+        assert!(config.migration_notes.is_empty());
+        assert_eq!(config.general.default_days, 45);
+        assert_eq!(config.branches.protected, vec!["main"]);
+    }
+
+    #[test]
+    fn test_migrate_flat_top_level_keys() {
+        let config = migrate_round_trip(
+            "days = 45\nprotected = [\"main\", \"release\"]\nexclude-patterns = [\"tmp/*\"]\ndefault_branch = \"master\"\n",
+        );
+        assert_eq!(config.general.default_days, 45);
+        assert_eq!(config.branches.protected, vec!["main", "release"]);
+        assert_eq!(config.branches.exclude_patterns, vec!["tmp/*"]);
+        assert_eq!(config.branches.default_branch.as_deref(), Some("master"));
+        assert!(config
+            .migration_notes
+            .contains(&"migrated key 'days' -> 'general.default_days'".to_string()));
+        assert_eq!(config.migration_notes.len(), 4);
+    }
+
+    #[test]
+    fn test_migrate_keys_in_the_wrong_section() {
+        let config = migrate_round_trip(
+            "[general]\nprotected_branches = [\"main\"]\nexclude_patterns = [\"tmp/*\"]\ndefault_branch = \"trunk\"\n\n[branches]\ndefault_days = 60\n",
+        );
+        assert_eq!(config.general.default_days, 60);
+        assert_eq!(config.branches.protected, vec!["main"]);
+        assert_eq!(config.branches.exclude_patterns, vec!["tmp/*"]);
+        assert_eq!(config.branches.default_branch.as_deref(), Some("trunk"));
+        assert!(config.migration_notes.contains(
+            &"migrated key 'general.protected_branches' -> 'branches.protected'".to_string()
+        ));
+    }
+
+    #[test]
+    fn test_migrate_dashed_key_names() {
+        let config = migrate_round_trip(
+            "[general]\ndefault-days = \"3w\"\n\n[branches]\nexclude-patterns = [\"tmp/*\"]\nprotected-branches = [\"main\"]\n",
+        );
+        assert_eq!(config.general.default_days, 21);
+        assert_eq!(config.branches.exclude_patterns, vec!["tmp/*"]);
+        assert_eq!(config.branches.protected, vec!["main"]);
+        assert!(config.migration_notes.contains(
+            &"migrated key 'general.default-days' -> 'general.default_days'".to_string()
+        ));
+    }
+
+    #[test]
+    fn test_migrate_keeps_current_key_over_legacy() {
+        let config = migrate_round_trip("days = 10\n\n[general]\ndefault_days = 45\n");
+        assert_eq!(config.general.default_days, 45);
+        assert_eq!(
+            config.migration_notes,
+            vec!["ignored legacy key 'days' because 'general.default_days' is also set"]
+        );
+    }
+
+    #[test]
+    fn test_current_config_needs_no_migration() {
+        let config = Config::parse(&toml::to_string_pretty(&Config::default()).unwrap()).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert!(!config.needs_migration());
+
+        let err = Config::parse("version = 99\n").unwrap_err();
+        assert!(err.to_string().contains("newer deadbranch"));
+    }
+
     #[test]
     fn test_config_set_default_days_invalid() {
         let mut config = Config::default();
@@ -382,6 +1655,47 @@ mod tests {
         assert!(config.branches.exclude_patterns.is_empty());
     }
 
+    #[test]
+    fn test_invalid_ui_values_fall_back_to_defaults() {
+        let config =
+            Config::parse("[ui]\nmax_branch_width = 4\nfull_sha = \"yes\"\ncompact = true\n")
+                .unwrap();
+        assert_eq!(config.ui.max_branch_width, None);
+        assert!(!config.ui.full_sha);
+        assert!(config.ui.compact);
+        assert_eq!(config.ui_notes.len(), 2);
+        assert!(config
+            .ui_notes
+            .iter()
+            .any(|note| note.starts_with("ui.max_branch_width = 4 is invalid")));
+        assert_eq!(config.warnings(), config.ui_notes);
+
+        let config = Config::parse("ui = 3\n").unwrap();
+        assert!(!config.ui.compact);
+        assert_eq!(config.ui_notes.len(), 1);
+    }
+
+    #[test]
+    fn test_config_set_ui_keys() {
+        let mut config = Config::default();
+        config
+            .set("ui.max-branch-width", &["40".to_string()])
+            .unwrap();
+        config.set("ui.full-sha", &["true".to_string()]).unwrap();
+        config.set("compact", &["true".to_string()]).unwrap();
+        assert_eq!(config.ui.max_branch_width, Some(40));
+        assert!(config.ui.full_sha && config.ui.compact);
+
+        assert!(config
+            .set("ui.max-branch-width", &["5".to_string()])
+            .is_err());
+        assert!(config.set("ui.compact", &["maybe".to_string()]).is_err());
+        config
+            .set("ui.max-branch-width", &["".to_string()])
+            .unwrap();
+        assert_eq!(config.ui.max_branch_width, None);
+    }
+
     #[test]
     fn test_config_set_unknown_key() {
         let mut config = Config::default();
@@ -416,6 +1730,145 @@ mod tests {
         });
     }
 
+    #[test]
+    fn test_config_save_to_and_load_from_round_trip() {
+        with_temp_config(|config_path| {
+            let mut config = Config::default();
+            config.general.default_days = 14;
+            config.branches.default_branch = Some("trunk".to_string());
+            config.branches.protected = vec!["trunk".to_string(), "release".to_string()];
+            config.branches.exclude_patterns = vec!["spike/*".to_string()];
+
+            config.save_to(&config_path).unwrap();
+            let loaded = Config::load_from(&config_path).unwrap();
+
+            assert!(loaded.validate().is_ok());
+            assert_eq!(loaded.general.default_days, 14);
+            assert_eq!(loaded.branches.default_branch, Some("trunk".to_string()));
+            assert_eq!(loaded.branches.protected, vec!["trunk", "release"]);
+            assert_eq!(loaded.branches.exclude_patterns, vec!["spike/*"]);
+        });
+    }
+
+    #[test]
+    fn test_config_load_from_missing_file() {
+        with_temp_config(|config_path| {
+            assert!(Config::load_from(&config_path).is_err());
+            assert!(!config_path.exists());
+        });
+    }
+
+    #[test]
+    fn test_submodule_backup_dir_uses_native_separators() {
+        let dir = Config::repo_backup_dir("app/vendor").unwrap();
+        let expected = Config::backups_dir().unwrap().join("app").join("vendor");
+        assert_eq!(dir, expected);
+    }
+
+    #[test]
+    fn test_config_validate_rejects_empty_values() {
+        let mut config = Config::default();
+        assert!(config.validate().is_ok());
+
+        config.branches.default_branch = Some(" ".to_string());
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.branches.protected.push(String::new());
+        assert!(config.validate().is_err());
+
+        let mut config = Config::default();
+        config.branches.exclude_patterns.push(String::new());
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_set_remote_delete_jobs() {
+        let mut config = Config::default();
+        config
+            .set("branches.remote-delete-jobs", &["4".to_string()])
+            .unwrap();
+        assert_eq!(config.branches.remote_delete_jobs, Some(4));
+        assert!(toml::to_string(&config)
+            .unwrap()
+            .contains("remote_delete_jobs = 4"));
+
+        for invalid in ["0", "17", "many"] {
+            assert!(config
+                .set("remote-delete-jobs", &[invalid.to_string()])
+                .is_err());
+        }
+        assert_eq!(config.branches.remote_delete_jobs, Some(4));
+
+        config.set("remote-delete-jobs", &["".to_string()]).unwrap();
+        assert_eq!(config.branches.remote_delete_jobs, None);
+    }
+
+    #[test]
+    fn test_config_per_scope_days() {
+        let mut config = Config::default();
+        assert_eq!(config.general.days_for(false), 30);
+        assert_eq!(config.general.days_for(true), 30);
+
+        config
+            .set("general.default-days-local", &["2m".to_string()])
+            .unwrap();
+        config
+            .set("default-days-remote", &["0".to_string()])
+            .unwrap();
+        assert_eq!(config.general.days_for(false), 60);
+        assert_eq!(config.general.days_for(true), 0);
+
+        let toml_str = toml::to_string(&config).unwrap();
+        assert!(toml_str.contains("default_days_local = 60"));
+        let loaded: Config = toml::from_str(&toml_str).unwrap();
+        assert_eq!(loaded.general.default_days_remote, Some(0));
+
+        let config: Config =
+            toml::from_str("[general]\ndefault_days = 45\ndefault_days_remote = \"1w\"\n").unwrap();
+        assert_eq!(config.general.days_for(false), 45);
+        assert_eq!(config.general.days_for(true), 7);
+
+        let mut config = config;
+        assert!(config
+            .set("default-days-remote", &["soon".to_string()])
+            .is_err());
+        config
+            .set("default-days-remote", &["".to_string()])
+            .unwrap();
+        assert_eq!(config.general.days_for(true), 45);
+        assert!(!toml::to_string(&config)
+            .unwrap()
+            .contains("default_days_remote"));
+    }
+
+    #[test]
+    fn test_config_set_prompt_timeout() {
+        let mut config = Config::default();
+        config
+            .set("general.prompt-timeout-secs", &["90".to_string()])
+            .unwrap();
+        assert_eq!(config.general.prompt_timeout_secs, Some(90));
+        assert!(toml::to_string(&config)
+            .unwrap()
+            .contains("prompt_timeout_secs = 90"));
+
+        for invalid in ["0", "-5", "soon"] {
+            assert!(config
+                .set("prompt-timeout-secs", &[invalid.to_string()])
+                .is_err());
+        }
+        assert_eq!(config.general.prompt_timeout_secs, Some(90));
+
+        config
+            .set("prompt-timeout-secs", &["".to_string()])
+            .unwrap();
+        assert_eq!(config.general.prompt_timeout_secs, None);
+        assert!(!toml::to_string(&config)
+            .unwrap()
+            .contains("prompt_timeout_secs"));
+    }
+
     #[test]
     fn test_get_repo_name() {
         let repo_name = Config::get_repo_name();
@@ -0,0 +1,150 @@
+//! Day-count values written as human-friendly durations
+//!
+//! Shared by the clap value parsers in `cli.rs` (which `build.rs` also
+//! compiles, so this module may only depend on `std`) and by config parsing.
+
+/// Days in a month and a year, for `m`/`y` durations
+const DAYS_PER_MONTH: u64 = 30;
+const DAYS_PER_YEAR: u64 = 365;
+
+/// Parse a duration into whole days.
+///
+/// Accepts a bare integer (days) or an integer followed by a unit:
+/// `d`/`day(s)`, `w`/`week(s)`, `m`/`mo`/`month(s)` (30 days) and
+/// `y`/`year(s)` (365 days). Units are case-insensitive and may be separated
+/// from the number by spaces, e.g. `30`, `3w`, `6mo`, `1 y`.
+pub fn parse_days(input: &str) -> Result<u32, String> {
+    let s = input.trim();
+    if s.is_empty() {
+        return Err("expected a number of days or a duration like 30d, 3w, 6m or 1y".to_string());
+    }
+
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (number, unit) = s.split_at(split);
+    let unit = unit.trim_start().to_ascii_lowercase();
+
+    if number.is_empty() {
+        return Err(match s.chars().next() {
+            Some('-') => format!("'{}' is negative; durations must be zero or more", input),
+            _ => format!(
+                "'{}' does not start with a number (e.g. 30d, 3w, 6m, 1y)",
+                input
+            ),
+        });
+    }
+    if unit.starts_with(['.', ',']) {
+        return Err(format!(
+            "'{}' has a fraction; use a whole number in a smaller unit (e.g. 10d instead of 1.5w)",
+            input
+        ));
+    }
+
+    let multiplier = match unit.as_str() {
+        "" | "d" | "day" | "days" => 1,
+        "w" | "week" | "weeks" => 7,
+        "m" | "mo" | "month" | "months" => DAYS_PER_MONTH,
+        "y" | "year" | "years" => DAYS_PER_YEAR,
+        "h" | "hr" | "hrs" | "hour" | "hours" | "min" | "mins" | "minute" | "minutes" | "s"
+        | "sec" | "secs" | "second" | "seconds" => {
+            return Err(format!(
+                "'{}' is shorter than a day; branch ages are counted in whole days (d, w, m, y)",
+                input
+            ))
+        }
+        other if other.contains(|c: char| c.is_ascii_digit()) => {
+            return Err(format!(
+                "'{}' combines several values; use a single unit (e.g. 17d instead of 2w3d)",
+                input
+            ))
+        }
+        other => {
+            return Err(format!(
+                "unknown unit '{}' in '{}'; use d (days), w (weeks), m (months = 30d) or y (years = 365d)",
+                other, input
+            ))
+        }
+    };
+
+    number
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(multiplier))
+        .and_then(|days| u32::try_from(days).ok())
+        .ok_or_else(|| format!("'{}' is too large", input))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_integers_are_days() {
+        assert_eq!(parse_days("0"), Ok(0));
+        assert_eq!(parse_days("30"), Ok(30));
+        assert_eq!(parse_days(" 45 "), Ok(45));
+    }
+
+    #[test]
+    fn units() {
+        assert_eq!(parse_days("10d"), Ok(10));
+        assert_eq!(parse_days("3w"), Ok(21));
+        assert_eq!(parse_days("2m"), Ok(60));
+        assert_eq!(parse_days("6mo"), Ok(180));
+        assert_eq!(parse_days("1y"), Ok(365));
+    }
+
+    #[test]
+    fn long_unit_names_spaces_and_case() {
+        assert_eq!(parse_days("1 day"), Ok(1));
+        assert_eq!(parse_days("2 weeks"), Ok(14));
+        assert_eq!(parse_days("3 Months"), Ok(90));
+        assert_eq!(parse_days("2YEARS"), Ok(730));
+        assert_eq!(parse_days("4W"), Ok(28));
+    }
+
+    #[test]
+    fn rejects_empty_and_missing_number() {
+        assert!(parse_days("").is_err());
+        assert!(parse_days("   ").is_err());
+        assert!(parse_days("w")
+            .unwrap_err()
+            .contains("does not start with a number"));
+        assert!(parse_days("-5d").unwrap_err().contains("negative"));
+    }
+
+    #[test]
+    fn rejects_fractions() {
+        assert!(parse_days("1.5w").unwrap_err().contains("fraction"));
+        assert!(parse_days("2,5d").unwrap_err().contains("fraction"));
+    }
+
+    #[test]
+    fn rejects_sub_day_units() {
+        for input in ["12h", "30min", "90s", "2 hours"] {
+            assert!(
+                parse_days(input)
+                    .unwrap_err()
+                    .contains("shorter than a day"),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_compound_and_unknown_units() {
+        assert!(parse_days("2w3d").unwrap_err().contains("single unit"));
+        assert!(parse_days("3 fortnights")
+            .unwrap_err()
+            .contains("unknown unit 'fortnights'"));
+        assert!(parse_days("5q").unwrap_err().contains("unknown unit"));
+    }
+
+    #[test]
+    fn rejects_overflow() {
+        assert!(parse_days("4294967296").unwrap_err().contains("too large"));
+        assert!(parse_days("99999999999y")
+            .unwrap_err()
+            .contains("too large"));
+        assert_eq!(parse_days("4294967295"), Ok(u32::MAX));
+    }
+}
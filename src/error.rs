@@ -1,9 +1,83 @@
 //! Custom error types for deadbranch
 
+use std::path::PathBuf;
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+#[derive(Error, Debug, PartialEq, Eq)]
 pub enum DeadbranchError {
     #[error("Branch '{0}' has unmerged changes. Use --force to delete anyway")]
     UnmergedBranch(String),
+
+    #[error("Branch '{0}' is checked out here. Switch to another branch first")]
+    IsCurrentBranch(String),
+
+    #[error(
+        "Branch '{branch}' is checked out in the worktree at '{}'. Switch branches there or run `git worktree remove`",
+        path.display()
+    )]
+    CheckedOutInWorktree { branch: String, path: PathBuf },
+
+    #[error("Branch '{0}' no longer exists")]
+    BranchNotFound(String),
+
+    #[error("Branch '{0}' is locked by another git process. Retry once it finishes")]
+    RefLocked(String),
+
+    #[error("Cancelled: {0}")]
+    UserCancelled(String),
+
+    #[error(
+        "Not a git repository{}",
+        git_dir.as_ref().map_or_else(
+            || " (or any parent up to mount point)".to_string(),
+            |dir| format!(": GIT_DIR is set to '{}'", dir)
+        )
+    )]
+    NotAGitRepository { git_dir: Option<String> },
 }
+
+impl DeadbranchError {
+    /// The variant's name, for `--error-format json`; part of the output
+    /// scripts rely on, so never rename one
+    pub fn kind(&self) -> &'static str {
+        match self {
+            DeadbranchError::UnmergedBranch(_) => "UnmergedBranch",
+            DeadbranchError::IsCurrentBranch(_) => "IsCurrentBranch",
+            DeadbranchError::CheckedOutInWorktree { .. } => "CheckedOutInWorktree",
+            DeadbranchError::BranchNotFound(_) => "BranchNotFound",
+            DeadbranchError::RefLocked(_) => "RefLocked",
+            DeadbranchError::UserCancelled(_) => "UserCancelled",
+            DeadbranchError::NotAGitRepository { .. } => "NotAGitRepository",
+        }
+    }
+
+    /// The process exit status for this error under `--error-format json`
+    /// (text errors always exit with 1); like `kind`, scripts rely on these, so
+    /// never reuse one. 1 is any other error, 2 is a bare `deadbranch` outside
+    /// a repository.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DeadbranchError::NotAGitRepository { .. } => 3,
+            DeadbranchError::UserCancelled(_) => 4,
+            DeadbranchError::UnmergedBranch(_) => 5,
+            DeadbranchError::IsCurrentBranch(_) => 6,
+            DeadbranchError::CheckedOutInWorktree { .. } => 7,
+            DeadbranchError::BranchNotFound(_) => 8,
+            DeadbranchError::RefLocked(_) => 9,
+        }
+    }
+
+    /// `NotAGitRepository`, naming `GIT_DIR` when that is what points nowhere
+    pub fn not_a_git_repository() -> Self {
+        DeadbranchError::NotAGitRepository {
+            git_dir: std::env::var_os("GIT_DIR").map(|dir| dir.to_string_lossy().into_owned()),
+        }
+    }
+}
+
+/// A run that ended normally but must exit with a non-zero status, e.g.
+/// `list --exit-code` finding stale branches. `main` exits with it without
+/// reporting an error, after the `--timing`/`--verbose` summary is printed.
+#[derive(Error, Debug, PartialEq, Eq)]
+#[error("exit status {0}")]
+pub struct ExitStatus(pub i32);
@@ -0,0 +1,245 @@
+//! Templates for `list --format-string`, like git's `--format`
+//!
+//! `{name}\t{age_days}` prints one line per branch with the placeholders
+//! filled in; `\t`, `\n` and `\\` are escapes and `{{`/`}}` are literal
+//! braces. The placeholders are the [`Field`]s that `--format json` writes,
+//! so the two outputs offer the same data.
+//!
+//! Shared with the clap value parsers in `cli.rs` (which `build.rs` also
+//! compiles), so this module may only depend on `std`.
+
+use std::fmt;
+
+/// A value `list` can report for a branch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    Name,
+    ShortName,
+    AgeDays,
+    AgeHuman,
+    Merged,
+    Remote,
+    Sha,
+    Date,
+    Author,
+    Committer,
+    Subject,
+}
+
+impl Field {
+    /// Every field, in the order `--format json` writes them
+    pub const ALL: [Field; 11] = [
+        Field::Name,
+        Field::ShortName,
+        Field::AgeDays,
+        Field::AgeHuman,
+        Field::Merged,
+        Field::Remote,
+        Field::Sha,
+        Field::Date,
+        Field::Author,
+        Field::Committer,
+        Field::Subject,
+    ];
+
+    /// The placeholder and JSON key, e.g. `age_days`
+    pub fn key(self) -> &'static str {
+        match self {
+            Field::Name => "name",
+            Field::ShortName => "short_name",
+            Field::AgeDays => "age_days",
+            Field::AgeHuman => "age_human",
+            Field::Merged => "merged",
+            Field::Remote => "remote",
+            Field::Sha => "sha",
+            Field::Date => "date",
+            Field::Author => "author",
+            Field::Committer => "committer",
+            Field::Subject => "subject",
+        }
+    }
+
+    /// The `list` flag without which this field isn't looked up (and is empty)
+    pub fn requires(self) -> Option<&'static str> {
+        match self {
+            Field::Subject => Some("--show-subject"),
+            _ => None,
+        }
+    }
+
+    fn from_key(key: &str) -> Option<Field> {
+        Field::ALL.into_iter().find(|field| field.key() == key)
+    }
+}
+
+/// One field of one branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldValue {
+    Text(String),
+    Number(i64),
+    Flag(bool),
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldValue::Text(text) => write!(f, "{}", text),
+            FieldValue::Number(n) => write!(f, "{}", n),
+            FieldValue::Flag(flag) => write!(f, "{}", flag),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed `--format-string` template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FormatString {
+    segments: Vec<Segment>,
+}
+
+impl FormatString {
+    /// Parse a template, rejecting unknown placeholders and escapes
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = input.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => literal.push(match chars.next() {
+                    Some('t') => '\t',
+                    Some('n') => '\n',
+                    Some('\\') => '\\',
+                    Some(other) => {
+                        return Err(format!(
+                            "unknown escape \\{} (expected \\t, \\n or \\\\)",
+                            other
+                        ))
+                    }
+                    None => return Err("template ends with a lone '\\'".to_string()),
+                }),
+                '}' => {
+                    if chars.next() != Some('}') {
+                        return Err("unmatched '}' (write }} for a literal brace)".to_string());
+                    }
+                    literal.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    if let Some(after) = rest.strip_prefix('{') {
+                        literal.push('{');
+                        chars = after.chars();
+                        continue;
+                    }
+                    let Some(end) = rest.find('}') else {
+                        return Err(format!("unclosed placeholder {{{}", rest));
+                    };
+                    let key = &rest[..end];
+                    let field = Field::from_key(key).ok_or_else(|| {
+                        let known: Vec<&str> = Field::ALL.iter().map(|f| f.key()).collect();
+                        format!(
+                            "unknown placeholder {{{}}} (expected one of: {})",
+                            key,
+                            known.join(", ")
+                        )
+                    })?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+                    segments.push(Segment::Field(field));
+                    chars = rest[end + 1..].chars();
+                }
+                c => literal.push(c),
+            }
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+        Ok(FormatString { segments })
+    }
+
+    /// The placeholders used, each once, in order of first use
+    pub fn fields(&self) -> Vec<Field> {
+        let mut fields = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Field(field) = segment {
+                if !fields.contains(field) {
+                    fields.push(*field);
+                }
+            }
+        }
+        fields
+    }
+
+    /// Fill in the template; fields `value` has nothing for render empty
+    pub fn render(&self, mut value: impl FnMut(Field) -> Option<FieldValue>) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => out.push_str(text),
+                Segment::Field(field) => {
+                    if let Some(v) = value(*field) {
+                        out.push_str(&v.to_string());
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(field: Field) -> Option<FieldValue> {
+        match field {
+            Field::Name => Some(FieldValue::Text("origin/feature/x".to_string())),
+            Field::AgeDays => Some(FieldValue::Number(45)),
+            Field::Merged => Some(FieldValue::Flag(true)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_render_fields_and_escapes() {
+        let template = FormatString::parse(r"{name}\t{age_days}\\{merged}\n").unwrap();
+        assert_eq!(template.render(sample), "origin/feature/x\t45\\true\n");
+
+        let braces = FormatString::parse("{{{name}}} {{literal}}").unwrap();
+        assert_eq!(braces.render(sample), "{origin/feature/x} {literal}");
+        assert_eq!(braces.fields(), vec![Field::Name]);
+
+        // Fields without a value render empty
+        let missing = FormatString::parse("[{subject}]").unwrap();
+        assert_eq!(missing.render(sample), "[]");
+        assert_eq!(missing.fields(), vec![Field::Subject]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (input, message) in [
+            ("{nme}", "unknown placeholder {nme}"),
+            ("{}", "unknown placeholder {}"),
+            ("{name", "unclosed placeholder {name"),
+            ("name}", "unmatched '}'"),
+            (r"{name}\x", r"unknown escape \x"),
+            ("{name}\\", "lone '\\'"),
+        ] {
+            let err = FormatString::parse(input).unwrap_err();
+            assert!(err.contains(message), "{:?}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_every_field_is_a_placeholder() {
+        for field in Field::ALL {
+            let template = FormatString::parse(&format!("{{{}}}", field.key())).unwrap();
+            assert_eq!(template.fields(), vec![field]);
+        }
+    }
+}
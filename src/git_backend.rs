@@ -0,0 +1,176 @@
+//! Where git commands run: a real `git` ([`SystemGit`]) or, in unit tests, a
+//! recorded fixture ([`Replay`])
+//!
+//! Setting `DEADBRANCH_RECORD_GIT=<file>` makes [`SystemGit`] append every
+//! call it runs (arguments, exit status, stdout and stderr) to `<file>`. The
+//! result can be trimmed by hand and checked in under `tests/fixtures/git/`,
+//! where the unit tests replay it.
+//!
+//! Only calls made through a [`GitBackend`] are recorded: default-branch
+//! detection, `ref_exists`, the squash-merge check, local git writes and
+//! remote branch deletion. Everything else in `git.rs` still runs
+//! `perf::git()` directly, so a recording is a partial log of the session,
+//! not a replayable run of the whole command.
+
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+use std::process::Output;
+use std::sync::Mutex;
+
+use crate::perf;
+
+/// Append each git call to this file when set
+pub const RECORD_ENV: &str = "DEADBRANCH_RECORD_GIT";
+
+/// Runs one git command and returns its output
+pub trait GitBackend: Sync {
+    fn output(&self, args: &[&str]) -> io::Result<Output>;
+}
+
+/// The `git` on `PATH`
+pub struct SystemGit;
+
+impl GitBackend for SystemGit {
+    fn output(&self, args: &[&str]) -> io::Result<Output> {
+        let output = perf::git().args(args).output()?;
+        if let Some(path) = std::env::var_os(RECORD_ENV).filter(|p| !p.is_empty()) {
+            if let Err(e) = record(std::path::Path::new(&path), args, &output) {
+                crate::ui::verbose(&format!(
+                    "Could not record git call to '{}': {}",
+                    path.to_string_lossy(),
+                    e
+                ));
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// One git invocation as stored in a fixture file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordedCall {
+    pub args: Vec<String>,
+    pub status: i32,
+    #[serde(default)]
+    pub stdout: String,
+    #[serde(default)]
+    pub stderr: String,
+}
+
+/// A fixture file: `[[call]]` tables in the order they ran
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Fixture {
+    #[serde(default)]
+    call: Vec<RecordedCall>,
+}
+
+// Remote deletions run in parallel; keep their entries from interleaving
+static RECORD_LOCK: Mutex<()> = Mutex::new(());
+
+fn record(path: &std::path::Path, args: &[&str], output: &Output) -> io::Result<()> {
+    let fixture = Fixture {
+        call: vec![RecordedCall {
+            args: args.iter().map(|a| a.to_string()).collect(),
+            status: output.status.code().unwrap_or(-1),
+            stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }],
+    };
+    let entry = toml::to_string(&fixture).map_err(io::Error::other)?;
+
+    let _lock = RECORD_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", entry)
+}
+
+/// Answers git calls from a fixture instead of running git. Each recorded
+/// call is used once, in order, so a fixture can hold the same command with
+/// different results.
+#[cfg(test)]
+pub struct Replay {
+    calls: Mutex<Vec<Option<RecordedCall>>>,
+}
+
+#[cfg(test)]
+impl Replay {
+    /// Parse a fixture file's contents
+    pub fn from_toml(text: &str) -> Self {
+        let fixture: Fixture = toml::from_str(text).expect("invalid git fixture");
+        Replay {
+            calls: Mutex::new(fixture.call.into_iter().map(Some).collect()),
+        }
+    }
+
+    /// Recorded calls that were never asked for
+    pub fn unused(&self) -> Vec<Vec<String>> {
+        let calls = self.calls.lock().unwrap();
+        calls.iter().flatten().map(|c| c.args.clone()).collect()
+    }
+}
+
+#[cfg(test)]
+impl GitBackend for Replay {
+    fn output(&self, args: &[&str]) -> io::Result<Output> {
+        let mut calls = self.calls.lock().unwrap();
+        let call = calls
+            .iter_mut()
+            .find(|c| c.as_ref().is_some_and(|c| c.args == args))
+            .and_then(Option::take)
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("no recorded call for `git {}`", args.join(" ")),
+                )
+            })?;
+        Ok(Output {
+            status: exit_status(call.status),
+            stdout: call.stdout.into_bytes(),
+            stderr: call.stderr.into_bytes(),
+        })
+    }
+}
+
+#[cfg(all(test, unix))]
+fn exit_status(code: i32) -> std::process::ExitStatus {
+    use std::os::unix::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw(code << 8)
+}
+
+#[cfg(all(test, windows))]
+fn exit_status(code: i32) -> std::process::ExitStatus {
+    use std::os::windows::process::ExitStatusExt;
+    std::process::ExitStatus::from_raw(code as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recorded_calls_round_trip_and_replay_in_order() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("calls.toml");
+        let output = |status: i32, stderr: &str| Output {
+            status: exit_status(status),
+            stdout: b"line one\nline \"two\"\n".to_vec(),
+            stderr: stderr.as_bytes().to_vec(),
+        };
+        record(&path, &["show-ref", "main"], &output(1, "")).unwrap();
+        record(&path, &["show-ref", "main"], &output(0, "warning: 'x'\n")).unwrap();
+
+        let replay = Replay::from_toml(&std::fs::read_to_string(&path).unwrap());
+        let first = replay.output(&["show-ref", "main"]).unwrap();
+        assert_eq!(first.status.code(), Some(1));
+        assert_eq!(first.stdout, b"line one\nline \"two\"\n");
+        let second = replay.output(&["show-ref", "main"]).unwrap();
+        assert!(second.status.success());
+        assert_eq!(second.stderr, b"warning: 'x'\n");
+
+        let err = replay.output(&["show-ref", "main"]).unwrap_err();
+        assert!(err.to_string().contains("git show-ref main"));
+        assert!(replay.unused().is_empty());
+    }
+}
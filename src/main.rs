@@ -1,72 +1,309 @@
 //! deadbranch - Clean up stale git branches safely
 
+mod atomic_write;
 mod backup;
 mod branch;
 mod cli;
 mod config;
+mod duration;
 mod error;
+mod format_string;
 mod git;
+mod git_backend;
+mod perf;
+mod policy;
+mod preset;
+mod readonly;
+mod report;
+mod schedule;
+mod shell;
 mod stats;
 mod tui;
 mod ui;
 
 use anyhow::{Context, Result};
-use chrono::Utc;
+use chrono::{Local, Utc};
 use clap::{CommandFactory, Parser};
 use clap_complete::generate;
 use std::fs;
 use std::io::Write;
 
-use branch::BranchFilter;
-use cli::{BackupAction, Cli, Commands, ConfigAction};
+use branch::{BranchFilter, CommitWindow, KeepRules, SkipReason};
+use cli::{
+    BackupAction, BackupFormat, Cli, Commands, CommitCountArgs, ConfigAction, ConfigSection,
+    DepthArgs, ErrorFormat, OutputFormat, RemoteArgs, StatusFormat,
+};
 use config::Config;
+use error::{DeadbranchError, ExitStatus};
+use perf::Phase;
+use preset::CleanFlags;
+use ui::{out, outln};
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
+    let error_format = cli.error_format;
+    if let Err(err) = run(cli) {
+        if let Some(ExitStatus(code)) = err.downcast_ref::<ExitStatus>() {
+            std::process::exit(*code);
+        }
+        report_error(&err, error_format);
+        std::process::exit(exit_code(&err, error_format));
+    }
+}
+
+/// The exit status for the error that ended the run. With `--error-format json`
+/// each known kind has its own (see `DeadbranchError::exit_code`); text errors,
+/// like anything without a kind, exit with 1 as they always have.
+fn exit_code(err: &anyhow::Error, format: ErrorFormat) -> i32 {
+    if format == ErrorFormat::Text {
+        1
+    } else if let Some(restore) = err.downcast_ref::<backup::RestoreError>() {
+        restore.exit_code()
+    } else if let Some(known) = err.downcast_ref::<DeadbranchError>() {
+        known.exit_code()
+    } else {
+        1
+    }
+}
+
+/// Print the error that ended the run on stderr. Errors deadbranch knows the
+/// cause of are shown with their suggestions, or named by `kind` in JSON.
+fn report_error(err: &anyhow::Error, format: ErrorFormat) {
+    let restore = err.downcast_ref::<backup::RestoreError>();
+    let known = err.downcast_ref::<DeadbranchError>();
+    match format {
+        ErrorFormat::Json => {
+            let kind = restore
+                .map(backup::RestoreError::kind)
+                .or(known.map(DeadbranchError::kind))
+                .unwrap_or("Other");
+            let json = serde_json::json!({ "error": format!("{:#}", err), "kind": kind });
+            eprintln!("{}", json);
+        }
+        ErrorFormat::Text => match (restore, known) {
+            (Some(restore), _) => ui::display_restore_error(restore),
+            // `{:#}` keeps any context added on the way up, e.g. "Failed to delete x: ..."
+            (None, Some(_)) => ui::error(&format!("{:#}", err)),
+            // As `fn main() -> anyhow::Result<()>` would, with the causes
+            (None, None) => eprintln!("Error: {:?}", err),
+        },
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+    ui::set_verbose(cli.verbose);
+    ui::set_hints(!cli.no_hints);
+    ui::set_plain(cli.plain);
+    if cli.no_color || std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty()) {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+    }
+    readonly::set_enabled(cli.check);
+    config::set_ignore_file(cli.ignore_config);
+    perf::set_enabled(cli.timing);
+    let verbose = cli.verbose;
+
+    if let Some(root) = &cli.repo_root {
+        let root = git::verify_repo_root(root)?;
+        std::env::set_current_dir(&root)
+            .with_context(|| format!("Failed to enter '{}'", root.display()))?;
+    }
+
+    let command = match cli.command {
+        Some(command) => command,
+        None => default_command()?,
+    };
 
     // Check if we're in a git repository (except for config, backup, and completions commands)
     if !matches!(
-        cli.command,
-        Commands::Config { .. } | Commands::Backup { .. } | Commands::Completions { .. }
+        command,
+        Commands::Config { .. }
+            | Commands::Backup { .. }
+            | Commands::Init { .. }
+            | Commands::Completions { .. }
+            | Commands::Status { short: true, .. }
     ) && !git::is_git_repository()
     {
-        ui::error("Not a git repository (or any parent up to mount point)");
-        std::process::exit(1);
+        return Err(DeadbranchError::not_a_git_repository().into());
     }
 
-    match cli.command {
+    let result = match command {
         Commands::List {
             days,
             local,
             remote,
+            remotes,
+            depth,
+            commits,
             merged,
-        } => cmd_list(days, local, remote, merged),
+            format,
+            format_string,
+            exit_code,
+            show_subject,
+            respect_creation,
+            respect_descendants,
+            protect,
+            orphaned_local,
+            stale_remote_only,
+            fetch,
+            since_tag,
+            until_tag,
+        } => cmd_list(
+            days,
+            ListScope {
+                local_only: local,
+                remote_only: remote,
+                merged_only: merged,
+                orphaned_local_only: orphaned_local,
+                stale_remote_only,
+                remotes,
+                depth,
+                commits,
+                fetch,
+                since_tag,
+                until_tag,
+            },
+            ListReport {
+                format,
+                format_string,
+                exit_code,
+                show_subject,
+            },
+            KeepRules {
+                protect,
+                respect_creation_days: respect_creation,
+                respect_descendants,
+                keep_last: None,
+            },
+        ),
 
         Commands::Clean {
             days,
             merged,
             force,
+            force_remote_unmerged,
             dry_run,
+            explain,
+            eval,
+            no_set_e,
+            verify,
             local,
             remote,
+            remotes,
+            depth,
+            commits,
             yes,
+            i_understand_data_loss,
+            accept_risk,
+            confirm_phrase,
             interactive,
-        } => cmd_clean(
+            preset,
+            label,
+            no_backup,
+            print_backup_path,
+            report,
+            respect_creation,
+            respect_descendants,
+            protect,
+            keep_last,
+        } => {
+            let flags = CleanFlags {
+                merged,
+                force,
+                local_only: local,
+                remote_only: remote,
+                depth,
+                commits,
+            }
+            .with_preset(preset);
+            if print_backup_path || eval {
+                ui::reserve_stdout();
+            }
+            if readonly::is_enabled() && !dry_run {
+                ui::check_notice("Read-only mode: showing what would be deleted");
+            }
+            let mode = if interactive && !readonly::is_enabled() {
+                CleanMode::Interactive
+            } else if dry_run || readonly::is_enabled() {
+                CleanMode::DryRun {
+                    explain,
+                    eval,
+                    set_e: !no_set_e,
+                }
+            } else {
+                CleanMode::Delete { verify }
+            };
+            cmd_clean(
+                days,
+                flags,
+                mode,
+                Confirmation {
+                    yes,
+                    accept_data_loss: i_understand_data_loss,
+                    force_remote_unmerged,
+                    accept_remote_risk: accept_risk,
+                    phrase: confirm_phrase,
+                },
+                RecordOptions {
+                    label,
+                    skip: no_backup,
+                    print_path: print_backup_path,
+                    report,
+                },
+                KeepRules {
+                    protect,
+                    respect_creation_days: respect_creation,
+                    respect_descendants,
+                    keep_last,
+                },
+                remotes,
+            )
+        }
+
+        Commands::Config { action } => cmd_config(action),
+
+        Commands::Backup { action } => cmd_backup(action),
+
+        Commands::Stats {
+            days,
+            remote_name,
+            all_remotes,
+        } => cmd_stats(
+            days,
+            &RemoteArgs {
+                remote_name,
+                all_remotes,
+                offline: false,
+            },
+        ),
+
+        Commands::Status { short, format } => cmd_status(short, format),
+
+        Commands::Audit {
+            release_pattern,
             days,
-            merged,
-            force,
-            dry_run,
             local,
             remote,
-            yes,
-            interactive,
+            remote_name,
+            all_remotes,
+        } => cmd_audit(
+            &release_pattern,
+            days,
+            local,
+            remote,
+            &RemoteArgs {
+                remote_name,
+                all_remotes,
+                offline: false,
+            },
         ),
 
-        Commands::Config { action } => cmd_config(action),
-
-        Commands::Backup { action } => cmd_backup(action),
+        Commands::Schedule {
+            show: _,
+            install_hint,
+        } => cmd_schedule(install_hint),
 
-        Commands::Stats { days } => cmd_stats(days),
+        Commands::Init { defaults } => cmd_init(defaults),
 
         Commands::Completions { shell } => {
             generate(
@@ -77,25 +314,168 @@ fn main() -> Result<()> {
             );
             Ok(())
         }
+    };
+
+    if perf::is_enabled() {
+        ui::display_timing(&perf::phases(), perf::total_elapsed());
+    }
+    if perf::is_enabled() || verbose {
+        ui::display_run_summary(&perf::summary(), !perf::is_enabled());
+    }
+    result
+}
+
+/// Resolve a bare `deadbranch` to `general.default_command` with its default flags.
+/// Outside a git repository there is nothing to run, so print help instead.
+fn default_command() -> Result<Commands> {
+    if !git::is_git_repository() {
+        Cli::command().print_help()?;
+        std::process::exit(2);
     }
+
+    let config = Config::load()?;
+    let name = config.general.default_command.as_str();
+    ui::verbose(&format!("No command given: running '{}'", name));
+    Cli::try_parse_from(["deadbranch", name])
+        .ok()
+        .and_then(|cli| cli.command)
+        .with_context(|| format!("Invalid general.default_command: '{}'", name))
 }
 
 /// Run the two-pass branch loading pipeline:
 ///   1. list all branches (first-pass merge detection)
-///   2. pre-filter with `matches_pre_merge` (excludes `merged_only` so tree-check runs on all candidates)
+///   2. pre-filter with `matches_pre_merge` (excludes `merged_only` so tree-check runs on all candidates),
+///      then drop local branches created within `keep.respect_creation_days` (reflog lookup),
+///      branches other active branches build on (`keep.respect_descendants`)
+///      and, with `orphaned_local_only`, keep only branches whose upstream is gone
+///      (with `stale_remote_only`, only remote branches without a local namesake)
 ///   3. squash/rebase merge detection via `git merge-tree` (second pass)
-///   4. retain only merged branches if `filter.merged_only` is set
+///   4. retain only merged branches if `filter.merged_only` is set, or only
+///      unmerged ones with `stale_remote_only`
 ///
 /// Any warnings from the tree-check pass are printed via [`ui::warning`].
 fn load_filtered_branches(
     filter: &BranchFilter,
     default_branch: &str,
+    remotes: &[String],
 ) -> Result<Vec<branch::Branch>> {
-    let all_branches = git::list_branches(default_branch)?;
-    let mut branches: Vec<_> = all_branches
-        .into_iter()
-        .filter(|b| filter.matches_pre_merge(b))
-        .collect();
+    load_explained_branches(filter, default_branch, remotes, &mut Vec::new())
+}
+
+/// Branches left out of a `clean` run, with the reason (`--explain`)
+type SkippedBranches = Vec<(branch::Branch, SkipReason)>;
+
+/// Like [`load_filtered_branches`], also recording in `skipped` the branches
+/// in the age window and scope that a filter or keep rule left out
+fn load_explained_branches(
+    filter: &BranchFilter,
+    default_branch: &str,
+    remotes: &[String],
+    skipped: &mut SkippedBranches,
+) -> Result<Vec<branch::Branch>> {
+    // Cheap filters run while refs are read; the git calls below only see candidates
+    let left_out = std::cell::RefCell::new(Vec::new());
+    let mut branches = git::list_branches_matching(
+        default_branch,
+        !filter.remote_only,
+        if filter.local_only { &[] } else { remotes },
+        |b| {
+            if !filter.in_window(b) {
+                return false;
+            }
+            match filter.skip_reason(b) {
+                Some(reason) => {
+                    left_out.borrow_mut().push((b.clone(), reason));
+                    false
+                }
+                None => true,
+            }
+        },
+    )?;
+    skipped.extend(left_out.into_inner());
+
+    if let Some(days) = filter.keep.respect_creation_days {
+        let cutoff = Utc::now() - chrono::Duration::days(days as i64);
+        branches.retain(|b| {
+            let old =
+                b.is_remote || git::get_branch_creation_time(&b.name).is_none_or(|t| t < cutoff);
+            if !old {
+                skipped.push((
+                    b.clone(),
+                    SkipReason::Kept(format!(
+                        "created in the last {} (--respect-creation)",
+                        ui::format_day_count(days)
+                    )),
+                ));
+            }
+            old
+        });
+    }
+
+    if filter.keep.respect_descendants {
+        let now = Utc::now();
+        let cutoff = |remote: bool| now - chrono::Duration::days(filter.min_age_for(remote) as i64);
+        let default_ref = git::qualify_branch_name(default_branch);
+        let mut protected = filter.protected_branches.clone();
+        protected.extend(filter.keep.protect.iter().cloned());
+        protected.push(default_branch.to_string());
+        let mut kept = Vec::with_capacity(branches.len());
+        for b in branches {
+            // A branch containing the tip forks from the default branch below
+            // it unless the tip is already in the default branch's history;
+            // then every branch started since contains it and none builds on it
+            if git::is_ancestor(&b.last_commit_sha, &default_ref)? {
+                kept.push(b);
+                continue;
+            }
+            let containing = git::get_containing_branches(&b.last_commit_sha, remotes)?;
+            let descendants =
+                branch::active_descendants(&b, &containing, remotes, &protected, cutoff);
+            if descendants.is_empty() {
+                kept.push(b);
+                continue;
+            }
+            ui::verbose(&format!(
+                "Keeping '{}': {} active {} build on it ({})",
+                b.name,
+                descendants.len(),
+                ui::pluralize(descendants.len(), "branch", "branches"),
+                descendants.join(", ")
+            ));
+            skipped.push((
+                b,
+                SkipReason::Kept(format!(
+                    "active branches build on it ({})",
+                    descendants.join(", ")
+                )),
+            ));
+        }
+        branches = kept;
+    }
+
+    if filter.orphaned_local_only {
+        let orphaned = git::get_orphaned_local_branches()?;
+        branches.retain(|b| !b.is_remote && orphaned.contains(&b.name));
+    }
+
+    if filter.stale_remote_only {
+        // A local branch of the same name means someone here still works on it
+        let local: std::collections::HashSet<String> =
+            git::ref_names("refs/heads/")?.into_iter().collect();
+        branches.retain(|b| b.is_remote && !local.contains(b.short_name()));
+    }
+
+    if filter.counts_commits() {
+        // Branches git can't count for are left out rather than guessed at
+        let counts = git::count_unique_commits(&branches, default_branch);
+        let mut counts = counts.into_iter();
+        branches.retain(|_| {
+            counts
+                .next()
+                .flatten()
+                .is_some_and(|n| filter.commit_count_in_range(n))
+        });
+    }
 
     let progress = ui::progress_bar("Checking branches...");
     progress.set_length(branches.len() as u64);
@@ -109,23 +489,515 @@ fn load_filtered_branches(
     }
 
     if filter.merged_only {
-        branches.retain(|b| b.is_merged);
+        branches.retain(|b| {
+            if !b.is_merged {
+                skipped.push((b.clone(), SkipReason::Unmerged));
+            }
+            b.is_merged
+        });
+    }
+    if filter.stale_remote_only {
+        branches.retain(|b| !b.is_merged);
     }
 
     Ok(branches)
 }
 
-/// List stale branches
-fn cmd_list(
-    days: Option<u32>,
+/// Drop branches that other local branches track as their upstream, so
+/// deleting a stack's base doesn't break the branches built on it
+fn skip_upstream_bases(branches: &mut Vec<branch::Branch>, skipped: &mut SkippedBranches) {
+    let dependents = git::get_upstream_dependents().unwrap_or_default();
+    branches.retain(|b| match dependents.get(&b.name) {
+        Some(children) => {
+            ui::info(&format!(
+                "Skipping '{}' (base of {})",
+                b.name,
+                children.join(", ")
+            ));
+            skipped.push((
+                b.clone(),
+                SkipReason::Kept(format!("base of {}", children.join(", "))),
+            ));
+            false
+        }
+        None => true,
+    });
+}
+
+/// Drop local branches checked out in another worktree; git refuses to delete them
+fn skip_worktree_branches(branches: &mut Vec<branch::Branch>, skipped: &mut SkippedBranches) {
+    let worktrees = git::get_worktree_branches();
+    branches.retain(|b| match worktrees.get(&b.name).filter(|_| !b.is_remote) {
+        Some(path) => {
+            ui::info(&format!(
+                "Skipping '{}' (checked out in worktree {})",
+                b.name, path
+            ));
+            skipped.push((b.clone(), SkipReason::InWorktree(path.clone())));
+            false
+        }
+        None => true,
+    });
+}
+
+/// Branch names in the repository that differ from one of `branches` only by
+/// case, as `(candidate, other)` pairs. Local names are compared with local
+/// ones, remote-tracking names with remote-tracking ones.
+fn find_case_collisions(branches: &[branch::Branch]) -> Vec<(String, String)> {
+    let local = git::ref_names("refs/heads/").unwrap_or_default();
+    let remote = git::ref_names("refs/remotes/").unwrap_or_default();
+    branches
+        .iter()
+        .flat_map(|b| {
+            let names = if b.is_remote { &remote } else { &local };
+            branch::case_variants(&b.name, names)
+                .into_iter()
+                .map(|other| (b.name.clone(), other.to_string()))
+        })
+        .collect()
+}
+
+/// Ask about each candidate whose name differs only by case from another
+/// branch, even with `--yes`; declined ones (or any, without a terminal) are skipped
+fn confirm_case_collisions(
+    branches: &mut Vec<branch::Branch>,
+    collisions: &[(String, String)],
+    skipped: &mut SkippedBranches,
+) -> Result<()> {
+    let mut kept = Vec::with_capacity(branches.len());
+    for b in branches.drain(..) {
+        let Some((_, other)) = collisions.iter().find(|(name, _)| *name == b.name) else {
+            kept.push(b);
+            continue;
+        };
+        let confirmed = ui::confirm(
+            &format!(
+                "Delete '{}'? It differs only by case from '{}'",
+                b.name, other
+            ),
+            false,
+        )?;
+        if confirmed {
+            kept.push(b);
+        } else {
+            ui::info(&format!(
+                "Skipping '{}' (differs only by case from '{}')",
+                b.name, other
+            ));
+            skipped.push((b, SkipReason::CaseCollision(other.clone())));
+        }
+    }
+    *branches = kept;
+    Ok(())
+}
+
+/// Apply `--keep-last`: drop the most recently committed candidates
+fn keep_most_recent(
+    branches: &mut Vec<branch::Branch>,
+    keep_last: Option<usize>,
+    skipped: &mut SkippedBranches,
+) {
+    let Some(count) = keep_last.filter(|&n| n > 0) else {
+        return;
+    };
+    let kept = branch::take_most_recent(branches, count);
+    if !kept.is_empty() {
+        let names: Vec<_> = kept.iter().map(|b| b.name.as_str()).collect();
+        ui::info(&format!(
+            "Keeping the {} most recent {} (--keep-last): {}",
+            kept.len(),
+            ui::pluralize_branch(kept.len()),
+            names.join(", ")
+        ));
+    }
+    skipped.extend(kept.into_iter().map(|b| {
+        (
+            b,
+            SkipReason::Kept("among the most recent (--keep-last)".to_string()),
+        )
+    }));
+}
+
+/// Drop unmerged remote branches (`branches.remote_require_merged`), saying which
+fn skip_unmerged_remotes(branches: &mut Vec<branch::Branch>, skipped: &mut SkippedBranches) {
+    let (kept, rest): (Vec<_>, Vec<_>) = std::mem::take(branches)
+        .into_iter()
+        .partition(|b| b.is_remote && !b.is_merged);
+    *branches = rest;
+    if !kept.is_empty() {
+        let names: Vec<_> = kept.iter().map(|b| b.name.as_str()).collect();
+        ui::info(&format!(
+            "Keeping {} unmerged remote {} (branches.remote_require_merged; pass --force-remote-unmerged to include): {}",
+            kept.len(),
+            ui::pluralize_branch(kept.len()),
+            names.join(", ")
+        ));
+    }
+    skipped.extend(kept.into_iter().map(|b| {
+        (
+            b,
+            SkipReason::Kept("unmerged remote (branches.remote_require_merged)".to_string()),
+        )
+    }));
+}
+
+/// Show the age rules in use (verbose only), so duration values like `3w` can be checked
+fn report_age_rules(days: Option<u32>, age: &str, respect_creation_days: Option<u32>) {
+    let source = if days.is_some() { "--days" } else { "config" };
+    ui::verbose(&format!("Age threshold: {} (from {})", age, source));
+    if let Some(window) = respect_creation_days {
+        ui::verbose(&format!(
+            "Keeping branches created in the last {}",
+            ui::format_day_count(window)
+        ));
+    }
+}
+
+/// The age thresholds for local and remote branches: `--days` for both when
+/// given, the configured per-scope defaults otherwise. The remote one is
+/// `None` when it's the same as the local one.
+fn age_thresholds(days: Option<u32>, general: &config::GeneralConfig) -> (u32, Option<u32>) {
+    if let Some(days) = days {
+        return (days, None);
+    }
+    let local = general.days_for(false);
+    let remote = general.days_for(true);
+    (local, (remote != local).then_some(remote))
+}
+
+/// Which branches `list` looks at
+struct ListScope {
     local_only: bool,
     remote_only: bool,
     merged_only: bool,
+    /// Only local branches whose upstream is gone (`--orphaned-local`)
+    orphaned_local_only: bool,
+    /// Only unmerged remote branches without a local namesake (`--stale-remote-only`)
+    stale_remote_only: bool,
+    remotes: RemoteArgs,
+    /// `--min-depth` / `--max-depth`
+    depth: DepthArgs,
+    /// `--min-commits` / `--max-commits`
+    commits: CommitCountArgs,
+    /// Refresh remote-tracking refs before listing (`--fetch`)
+    fetch: bool,
+    /// Only branches last committed after this tag (`--since-tag`)
+    since_tag: Option<String>,
+    /// Only branches last committed no later than this tag (`--until-tag`)
+    until_tag: Option<String>,
+}
+
+/// Remote data fetched longer ago than this gets a `list --fetch` hint
+const STALE_FETCH_DAYS: i64 = 7;
+
+/// Say which repository is in play when running inside a submodule, since
+/// its branches are the ones listed and deleted, not the superproject's
+fn report_submodule() {
+    if let Some(parent) = Config::get_superproject_name() {
+        ui::info(&format!(
+            "Inside a submodule of '{}' — working on the branches of '{}'",
+            parent,
+            Config::get_repo_name()
+        ));
+    }
+}
+
+/// Remotes the given remote branches live on, sorted and without duplicates
+fn remote_names(branches: &[branch::Branch]) -> Vec<&str> {
+    let mut names: Vec<&str> = branches
+        .iter()
+        .filter_map(|b| b.remote.as_deref())
+        .collect();
+    names.sort_unstable();
+    names.dedup();
+    names
+}
+
+/// Fetch and prune before remote branches are deleted. Skipped with `--offline`
+/// (or `general.offline`), and when a remote doesn't answer quickly, so a
+/// missing network costs seconds rather than a connection timeout.
+fn refresh_remote_data(remotes: &[&str], offline: bool) {
+    if offline {
+        ui::info("Offline: skipping `git fetch --prune`; remote branch data may be stale");
+        return;
+    }
+
+    let spinner = ui::spinner("Fetching remote to ensure data is up to date...");
+    if let Some(remote) = remotes
+        .iter()
+        .find(|remote| !git::remote_reachable(remote, git::REMOTE_CHECK_TIMEOUT))
+    {
+        ui::spinner_warn(&spinner, &format!("Could not reach remote '{}'", remote));
+        ui::warning("  Skipped `git fetch --prune`; remote branch data may be stale.");
+        ui::hint("Pass --offline (or set general.offline) to skip this check when you know you're offline");
+        return;
+    }
+    let _timer = perf::phase(Phase::Fetch);
+    match git::fetch_and_prune() {
+        Ok(()) => ui::spinner_success(&spinner, "Remote data is up to date"),
+        Err(e) => {
+            ui::spinner_warn(&spinner, "Could not fetch remote");
+            ui::warning(&format!("  {}", e));
+            ui::warning("  Remote branch data may be stale.");
+        }
+    }
+}
+
+/// Point out `list --fetch` when remote-tracking refs haven't been updated for a while
+fn hint_if_fetch_is_old() {
+    let Some(fetched) = git::last_fetch_time() else {
+        return;
+    };
+    let days = Utc::now().signed_duration_since(fetched).num_days();
+    if days >= STALE_FETCH_DAYS {
+        ui::hint(&format!(
+            "Remote branches were last fetched {} ago; ages may be out of date (refresh with `deadbranch list --fetch`)",
+            ui::format_day_count(days as u32)
+        ));
+    }
+}
+
+/// The remotes whose branches to look at: `--remote-name`, every remote with
+/// `--all-remotes`, or `origin`
+fn resolve_remotes(args: &RemoteArgs) -> Result<Vec<String>> {
+    if args.all_remotes {
+        return git::list_remotes();
+    }
+    let Some(name) = &args.remote_name else {
+        return Ok(vec![git::DEFAULT_REMOTE.to_string()]);
+    };
+    let remotes = git::list_remotes()?;
+    if !remotes.contains(name) {
+        anyhow::bail!(
+            "No remote named '{}' (configured: {})",
+            name,
+            if remotes.is_empty() {
+                "none".to_string()
+            } else {
+                remotes.join(", ")
+            }
+        );
+    }
+    Ok(vec![name.clone()])
+}
+
+/// How `list` reports what it found
+struct ListReport {
+    /// Explicit `--format`, if any
+    format: Option<OutputFormat>,
+    /// One line per branch from this template (`--format-string`)
+    format_string: Option<format_string::FormatString>,
+    /// Exit with status 1 when stale branches are found (quiet unless `--format` is given)
+    exit_code: bool,
+    /// Show the last commit subject in the table (`--show-subject`)
+    show_subject: bool,
+}
+
+/// What `clean` does with the branches it selects
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CleanMode {
+    /// Delete after confirmation; `verify` then checks the branches are gone
+    Delete { verify: bool },
+    /// Only show what would be deleted; `explain` also lists what was skipped and why
+    /// `eval` prints the commands for a shell (`--eval`), after a `set -e` with `set_e`
+    DryRun {
+        explain: bool,
+        eval: bool,
+        set_e: bool,
+    },
+    /// Pick branches in the TUI
+    Interactive,
+}
+
+/// How `clean` asks before deleting
+#[derive(Clone)]
+struct Confirmation {
+    /// Skip confirmation prompts (`--yes`)
+    yes: bool,
+    /// Unattended force-deletion of unmerged branches was acknowledged
+    /// (`--i-understand-data-loss`)
+    accept_data_loss: bool,
+    /// Unmerged remote branches may go too, despite `branches.remote_require_merged`
+    /// (`--force-remote-unmerged`)
+    force_remote_unmerged: bool,
+    /// Skip the typed remote confirmation (`--accept-risk`); see [`remote_risk_accepted`]
+    accept_remote_risk: bool,
+    /// The typed remote confirmation, given up front (`--confirm-phrase`)
+    phrase: Option<String>,
+}
+
+/// What `clean` records about a deletion: the backup, and the `--report` file
+#[derive(Debug, Default)]
+struct RecordOptions {
+    /// Note recorded in the backup file (`--label`)
+    label: Option<String>,
+    /// Delete without writing a backup first (`--no-backup`)
+    skip: bool,
+    /// Finish by printing the backup paths to stdout (`--print-backup-path`)
+    print_path: bool,
+    /// Write a report of the run here (`--report`)
+    report: Option<std::path::PathBuf>,
+}
+
+/// What one deletion phase (local or remote) did
+#[derive(Debug, Default)]
+struct DeletionPhase {
+    /// Names of the branches that were deleted
+    deleted: Vec<String>,
+    /// `(name, reason)` for each branch that couldn't be deleted
+    failed: Vec<(String, String)>,
+    /// Names of the branches something else deleted before this run did
+    already_gone: Vec<String>,
+    /// Backup file written before deleting, unless `--no-backup` was given
+    backup: Option<String>,
+}
+
+impl DeletionPhase {
+    /// A phase that stopped with `err` before reporting on any of `branches`
+    fn aborted(branches: &[branch::Branch], err: &anyhow::Error) -> Self {
+        DeletionPhase {
+            failed: branches
+                .iter()
+                .map(|b| (b.name.clone(), format!("{:#}", err)))
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Must be `1` for `--accept-risk` to take effect
+const ACCEPT_REMOTE_RISK_ENV: &str = "DEADBRANCH_ACCEPT_REMOTE_RISK";
+
+/// Whether remote branches may be deleted without the typed confirmation:
+/// `--accept-risk` only counts together with `DEADBRANCH_ACCEPT_REMOTE_RISK=1`,
+/// so neither a stray flag nor a leftover variable is enough on its own
+fn remote_risk_accepted(flag: bool) -> bool {
+    let env_set = std::env::var_os(ACCEPT_REMOTE_RISK_ENV).is_some_and(|v| v == "1");
+    if flag && !env_set {
+        ui::warning(&format!(
+            "--accept-risk is ignored unless {}=1 is set; asking for confirmation instead",
+            ACCEPT_REMOTE_RISK_ENV
+        ));
+    }
+    flag && env_set
+}
+
+/// `--min-commits` above `--max-commits` can match nothing, so it's a typo
+fn check_commit_range(commits: CommitCountArgs) -> Result<()> {
+    if let (Some(min), Some(max)) = (commits.min_commits, commits.max_commits) {
+        if min > max {
+            anyhow::bail!(
+                "--min-commits ({}) is greater than --max-commits ({})",
+                min,
+                max
+            );
+        }
+    }
+    Ok(())
+}
+
+/// `--min-depth` above `--max-depth` can match nothing, so it's a typo
+fn check_depth_range(depth: DepthArgs) -> Result<()> {
+    if let (Some(min), Some(max)) = (depth.min_depth, depth.max_depth) {
+        if min > max {
+            anyhow::bail!(
+                "--min-depth ({}) is greater than --max-depth ({})",
+                min,
+                max
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The commit dates `--since-tag` / `--until-tag` allow. A window that ends
+/// before it starts can match nothing, so it's a mistake.
+fn resolve_tag_window(since_tag: Option<&str>, until_tag: Option<&str>) -> Result<CommitWindow> {
+    let after = since_tag.map(git::tag_commit_date).transpose()?;
+    let until = until_tag.map(git::tag_commit_date).transpose()?;
+    if let (Some(after), Some(until)) = (after, until) {
+        if after >= until {
+            anyhow::bail!(
+                "--since-tag {} ({}) is not older than --until-tag {} ({})",
+                since_tag.unwrap_or_default(),
+                after.format("%Y-%m-%d %H:%M"),
+                until_tag.unwrap_or_default(),
+                until.format("%Y-%m-%d %H:%M")
+            );
+        }
+    }
+    Ok(CommitWindow { after, until })
+}
+
+/// Shown by branch commands in a repository whose first commit hasn't been made
+const NO_COMMITS_YET: &str = "Repository has no commits yet — nothing to do";
+
+/// List stale branches
+fn cmd_list(
+    days: Option<u32>,
+    scope: ListScope,
+    report: ListReport,
+    keep: KeepRules,
 ) -> Result<()> {
+    let ListScope {
+        local_only,
+        remote_only,
+        merged_only,
+        orphaned_local_only,
+        stale_remote_only,
+        remotes,
+        depth,
+        commits,
+        fetch,
+        since_tag,
+        until_tag,
+    } = scope;
+    check_depth_range(depth)?;
+    check_commit_range(commits)?;
+    let commit_window = resolve_tag_window(since_tag.as_deref(), until_tag.as_deref())?;
     let config = Config::load()?;
+    let offline = remotes.offline || config.general.offline;
+    let format = match report.format {
+        None if report.exit_code || report.format_string.is_some() => None,
+        None => Some(OutputFormat::Table),
+        format => format,
+    };
 
-    // Use CLI value if provided, otherwise use config default
-    let min_age = days.unwrap_or(config.general.default_days);
+    if !git::has_commits() {
+        match format {
+            Some(OutputFormat::Table) => ui::info(NO_COMMITS_YET),
+            Some(OutputFormat::Metrics) => out!(
+                "{}",
+                stats::format_metrics(&stats::compute_stats(
+                    &[],
+                    days.unwrap_or(config.general.default_days)
+                ))
+            ),
+            Some(OutputFormat::Json) => ui::print_json(&[] as &[branch::Branch])?,
+            None => {}
+        }
+        return Ok(());
+    }
+
+    // Orphaned branches are always local; otherwise fall back to the configured scope
+    let (local_only, remote_only) = if orphaned_local_only {
+        (true, false)
+    } else if stale_remote_only {
+        (false, true)
+    } else {
+        config
+            .general
+            .default_scope
+            .resolve(local_only, remote_only)
+    };
+
+    // Use CLI value if provided, otherwise use config default. Orphaned
+    // branches and tag windows are reported regardless of age unless --days
+    // is given.
+    let (min_age, remote_min_age_days) = match days {
+        None if orphaned_local_only || commit_window.is_bounded() => (0, None),
+        days => age_thresholds(days, &config.general),
+    };
 
     // Get default branch for merge detection
     let default_branch = config
@@ -134,56 +1006,174 @@ fn cmd_list(
         .clone()
         .unwrap_or_else(|| git::get_default_branch().unwrap_or_else(|_| "main".to_string()));
 
-    ui::info(&format!(
-        "Using '{}' as the default branch for merge detection",
-        default_branch
-    ));
+    if format == Some(OutputFormat::Table) {
+        report_submodule();
+        ui::info(&format!(
+            "Using '{}' as the default branch for merge detection",
+            default_branch
+        ));
+    }
 
     let filter = BranchFilter {
         min_age_days: min_age,
+        remote_min_age_days,
         local_only,
         remote_only,
         merged_only,
-        protected_branches: config.branches.protected,
-        exclude_patterns: config.branches.exclude_patterns,
+        protected_branches: config.branches.protected.clone(),
+        exclude_patterns: config.exclude_patterns_for_repo(),
+        keep,
+        orphaned_local_only,
+        stale_remote_only,
+        min_depth: depth.min_depth,
+        max_depth: depth.max_depth,
+        commit_window,
+        min_commits: commits.min_commits,
+        max_commits: commits.max_commits,
     };
+    report_age_rules(
+        days,
+        &filter.describe_age(),
+        filter.keep.respect_creation_days,
+    );
 
-    let mut branches = load_filtered_branches(&filter, &default_branch)?;
+    let remotes = resolve_remotes(&remotes)?;
+    if fetch && !local_only {
+        let names: Vec<&str> = remotes.iter().map(String::as_str).collect();
+        if format == Some(OutputFormat::Table) {
+            refresh_remote_data(&names, offline);
+        } else if !offline {
+            // Keep machine-readable output clean: only report a failure, on stderr
+            let _timer = perf::phase(Phase::Fetch);
+            if let Err(e) = git::fetch_and_prune() {
+                ui::error(&format!("{:#}; remote branch data may be stale", e));
+            }
+        }
+    }
+    let mut branches = load_filtered_branches(&filter, &default_branch, &remotes)?;
     branch::sort_branches(&mut branches);
-
-    let local: Vec<_> = branches.iter().filter(|b| !b.is_remote).cloned().collect();
-    let remote: Vec<_> = branches.iter().filter(|b| b.is_remote).cloned().collect();
-
-    // Display in table format
-    if !local.is_empty() {
-        ui::display_branches(&local, "Local Branches:");
+    let reports_branches = matches!(format, Some(OutputFormat::Table | OutputFormat::Json))
+        || report.format_string.is_some();
+    if report.show_subject && reports_branches {
+        git::fill_commit_subjects(&mut branches);
     }
-    if !remote.is_empty() {
-        ui::display_branches(&remote, "Remote Branches:");
+    let reports_committer = format == Some(OutputFormat::Json)
+        || report
+            .format_string
+            .as_ref()
+            .is_some_and(|template| template.fields().contains(&format_string::Field::Committer));
+    if reports_committer {
+        git::fill_commit_committers(&mut branches);
     }
-    if local.is_empty() && remote.is_empty() {
-        ui::info("No stale branches found.");
+
+    match format {
+        Some(OutputFormat::Metrics) => out!(
+            "{}",
+            stats::format_metrics(&stats::compute_stats_per_scope(
+                &branches,
+                min_age,
+                remote_min_age_days
+            ))
+        ),
+        Some(OutputFormat::Json) => ui::print_json(&branches)?,
+        Some(OutputFormat::Table) => {
+            ui::warn_case_collisions(&find_case_collisions(&branches));
+            let local: Vec<_> = branches.iter().filter(|b| !b.is_remote).cloned().collect();
+            let remote: Vec<_> = branches.iter().filter(|b| b.is_remote).cloned().collect();
+
+            ui::print_list_census(&local, &remote);
+            if !local.is_empty() {
+                ui::display_branches(&local, "Local Branches:");
+            }
+            if !remote.is_empty() {
+                ui::display_branches(&remote, "Remote Branches:");
+            }
+            if local.is_empty() && remote.is_empty() {
+                ui::info(&format!(
+                    "No stale branches found ({}).",
+                    filter.describe_age()
+                ));
+            }
+            if !fetch && !remote.is_empty() {
+                hint_if_fetch_is_old();
+            }
+        }
+        None => {
+            if let Some(template) = &report.format_string {
+                print_with_format_string(template, &branches);
+            }
+        }
+    }
+
+    if report.exit_code && !branches.is_empty() {
+        return Err(ExitStatus(1).into());
     }
 
     Ok(())
 }
 
+/// `list --format-string`: one line per branch. Fields that weren't looked up
+/// render empty, with a warning on stderr so stdout stays as templated.
+fn print_with_format_string(template: &format_string::FormatString, branches: &[branch::Branch]) {
+    for field in template.fields() {
+        let Some(flag) = field.requires() else {
+            continue;
+        };
+        if branches.iter().any(|b| b.field(field).is_none()) {
+            ui::warning_stderr(&format!("{{{}}} is empty without {}", field.key(), flag));
+        }
+    }
+    for branch in branches {
+        outln!("{}", template.render(|field| branch.field(field)));
+    }
+}
+
 /// Clean (delete) stale branches
-#[allow(clippy::too_many_arguments)]
 fn cmd_clean(
     days: Option<u32>,
-    merged: bool,
-    force: bool,
-    dry_run: bool,
-    local_only: bool,
-    remote_only: bool,
-    skip_confirm: bool,
-    interactive: bool,
+    flags: CleanFlags,
+    mode: CleanMode,
+    confirmation: Confirmation,
+    records: RecordOptions,
+    keep: KeepRules,
+    remotes: RemoteArgs,
 ) -> Result<()> {
+    if !git::has_commits() {
+        ui::info(NO_COMMITS_YET);
+        return Ok(());
+    }
+    let skip_confirm = confirmation.yes;
+    let CleanFlags {
+        merged,
+        force,
+        local_only,
+        remote_only,
+        depth,
+        commits,
+    } = flags;
+    check_depth_range(depth)?;
+    check_commit_range(commits)?;
     let config = Config::load()?;
+    let offline = remotes.offline || config.general.offline;
+    let remotes = resolve_remotes(&remotes)?;
+    let scope_from_flags = local_only || remote_only;
+    let (local_only, remote_only) = config
+        .general
+        .default_scope
+        .resolve(local_only, remote_only);
 
     // Use CLI value if provided, otherwise use config default
-    let min_age = days.unwrap_or(config.general.default_days);
+    let (min_age, remote_min_age_days) = age_thresholds(days, &config.general);
+    // `--force` lifts the deletion policy, as it lifts the merged-only default
+    let policy = if force {
+        None
+    } else {
+        config.branches.delete_policy()?
+    };
+    // Neither `--force` nor a policy reaches unmerged remote branches unless the config allows it
+    let keep_unmerged_remotes = (force || policy.is_some())
+        && config.branches.remote_require_merged
+        && !confirmation.force_remote_unmerged;
 
     // Get default branch for merge detection
     let default_branch = config
@@ -191,20 +1181,43 @@ fn cmd_clean(
         .default_branch
         .clone()
         .unwrap_or_else(|| git::get_default_branch().unwrap_or_else(|_| "main".to_string()));
+    report_submodule();
 
-    if interactive {
+    if mode == CleanMode::Interactive {
         // For TUI, apply only age + protection + exclusion filters.
         // merged/local/remote become initial toggle state in the TUI.
         let tui_filter = BranchFilter {
             min_age_days: min_age,
+            remote_min_age_days,
             local_only: false,
             remote_only: false,
             merged_only: false,
             protected_branches: config.branches.protected.clone(),
-            exclude_patterns: config.branches.exclude_patterns.clone(),
+            exclude_patterns: config.exclude_patterns_for_repo(),
+            keep,
+            orphaned_local_only: false,
+            stale_remote_only: false,
+            min_depth: depth.min_depth,
+            max_depth: depth.max_depth,
+            commit_window: CommitWindow::default(),
+            min_commits: commits.min_commits,
+            max_commits: commits.max_commits,
         };
+        report_age_rules(
+            days,
+            &tui_filter.describe_age(),
+            tui_filter.keep.respect_creation_days,
+        );
 
-        let tui_branches = load_filtered_branches(&tui_filter, &default_branch)?;
+        let mut skipped = Vec::new();
+        let mut tui_branches =
+            load_explained_branches(&tui_filter, &default_branch, &remotes, &mut skipped)?;
+        skip_upstream_bases(&mut tui_branches, &mut skipped);
+        skip_worktree_branches(&mut tui_branches, &mut skipped);
+        keep_most_recent(&mut tui_branches, tui_filter.keep.keep_last, &mut skipped);
+        if keep_unmerged_remotes {
+            skip_unmerged_remotes(&mut tui_branches, &mut skipped);
+        }
 
         if tui_branches.is_empty() {
             ui::info("No branches to show in interactive mode.");
@@ -214,42 +1227,141 @@ fn cmd_clean(
         // Build initial filter state from CLI flags for toggle seeding
         let initial_filter = BranchFilter {
             min_age_days: 0,
+            remote_min_age_days: None,
             local_only,
             remote_only,
             merged_only: merged,
             protected_branches: Vec::new(),
             exclude_patterns: Vec::new(),
+            keep: KeepRules::default(),
+            orphaned_local_only: false,
+            stale_remote_only: false,
+            min_depth: None,
+            max_depth: None,
+            commit_window: CommitWindow::default(),
+            min_commits: None,
+            max_commits: None,
         };
 
-        return tui::run_interactive(tui_branches, &initial_filter, &default_branch, force);
+        return tui::run_interactive(
+            tui_branches,
+            &initial_filter,
+            &default_branch,
+            force,
+            records.label,
+            config.branches.remote_delete_jobs,
+        );
     }
 
-    // By default, only delete merged branches unless --force is used
-    let merged_only = merged || !force;
+    // By default, only delete merged branches unless --force is used;
+    // a `branches.delete_when` policy decides instead when one is set
+    let merged_only = match &policy {
+        Some(policy) => {
+            ui::info(&format!(
+                "Deleting branches where: {} (branches.delete_when)",
+                policy
+            ));
+            merged
+        }
+        None => flags.merged_only(),
+    };
 
     // Create filter - by default, show both local and remote branches
     // Use --local or --remote to filter to only one type
     let filter = BranchFilter {
         min_age_days: min_age,
+        remote_min_age_days,
         local_only,
         remote_only,
         merged_only,
         protected_branches: config.branches.protected.clone(),
-        exclude_patterns: config.branches.exclude_patterns,
+        exclude_patterns: config.exclude_patterns_for_repo(),
+        keep,
+        orphaned_local_only: false,
+        stale_remote_only: false,
+        min_depth: depth.min_depth,
+        max_depth: depth.max_depth,
+        commit_window: CommitWindow::default(),
+        min_commits: commits.min_commits,
+        max_commits: commits.max_commits,
+    };
+    report_age_rules(
+        days,
+        &filter.describe_age(),
+        filter.keep.respect_creation_days,
+    );
+
+    let selection = match &policy {
+        Some(policy) => format!("branches.delete_when: {}", policy),
+        None if force => "merged and unmerged (--force)".to_string(),
+        None => "merged only".to_string(),
     };
+    let report_filters = describe_filters(&filter, selection, &remotes);
 
-    let mut branches = load_filtered_branches(&filter, &default_branch)?;
+    let mut skipped = Vec::new();
+    let mut branches = load_explained_branches(&filter, &default_branch, &remotes, &mut skipped)?;
+    if let Some(policy) = &policy {
+        apply_delete_policy(&mut branches, policy, &mut skipped)?;
+    }
+    skip_upstream_bases(&mut branches, &mut skipped);
+    skip_worktree_branches(&mut branches, &mut skipped);
+    keep_most_recent(&mut branches, filter.keep.keep_last, &mut skipped);
+    if keep_unmerged_remotes {
+        skip_unmerged_remotes(&mut branches, &mut skipped);
+    }
     branch::sort_branches(&mut branches);
 
+    let collisions = find_case_collisions(&branches);
+    ui::warn_case_collisions(&collisions);
+    if !matches!(mode, CleanMode::DryRun { .. }) {
+        confirm_case_collisions(&mut branches, &collisions, &mut skipped)?;
+    }
+
+    let explain = matches!(mode, CleanMode::DryRun { explain: true, .. });
+    if explain {
+        // The current branch never reaches the filters; explain it when it's old enough
+        let current = git::get_current_branch()
+            .ok()
+            .and_then(|name| git::get_local_branch(&name));
+        if let Some(current) = current.filter(|b| filter.in_window(b)) {
+            skipped.push((current, SkipReason::CurrentBranch));
+        }
+        skipped.sort_by(|a, b| a.0.name.cmp(&b.0.name));
+    }
+
     if branches.is_empty() {
-        ui::info("No branches to delete.");
+        ui::info(&format!(
+            "No branches to delete ({}).",
+            filter.describe_age()
+        ));
+        if explain {
+            ui::display_skipped_branches(&skipped);
+        }
+        if let Some(path) = &records.report {
+            write_clean_report(path, &default_branch, report_filters, &[], &[])?;
+        }
         return Ok(());
     }
 
     let local_branches: Vec<_> = branches.iter().filter(|b| !b.is_remote).cloned().collect();
     let remote_branches: Vec<_> = branches.iter().filter(|b| b.is_remote).cloned().collect();
 
-    if dry_run {
+    ui::info(&format!(
+        "Scope: {} (from {})",
+        scope_label(local_only, remote_only),
+        if scope_from_flags {
+            "command line"
+        } else {
+            "general.default_scope"
+        }
+    ));
+
+    if let CleanMode::DryRun {
+        explain,
+        eval,
+        set_e,
+    } = mode
+    {
         // For dry-run, show all tables upfront
         if !local_branches.is_empty() {
             let title = format!(
@@ -266,252 +1378,1022 @@ fn cmd_clean(
             ui::display_branches(&remote_branches, &title);
         }
 
-        // Count by operation type
+        // Count by the flag each deletion will use
         let local_safe: usize = local_branches
             .iter()
-            .filter(|b| force || b.is_merged)
+            .filter(|b| git::DeleteMode::for_branch(b, force) == git::DeleteMode::Safe)
             .count();
         let local_force: usize = local_branches.len() - local_safe;
-        let remote_count: usize = remote_branches.len();
-        let total = local_branches.len() + remote_count;
-
-        ui::print_dry_run_summary(total, local_safe, local_force, remote_count);
+        let remote_counts: Vec<(&str, usize)> = remote_names(&remote_branches)
+            .into_iter()
+            .map(|name| {
+                let count = remote_branches
+                    .iter()
+                    .filter(|b| b.remote.as_deref() == Some(name))
+                    .count();
+                (name, count)
+            })
+            .collect();
+        let total = local_branches.len() + remote_branches.len();
+
+        ui::print_dry_run_summary(total, local_safe, local_force, &remote_counts);
+        if explain {
+            ui::display_skipped_branches(&skipped);
+        }
+        if eval {
+            print_eval_commands(&local_branches, &remote_branches, force, set_e);
+        }
         return Ok(());
     }
 
-    // Handle local branches - show table right before confirmation
-    if !local_branches.is_empty() {
-        let title = format!(
-            "Local {} to Delete:",
-            ui::pluralize_branch_cap(local_branches.len())
-        );
-        ui::display_branches(&local_branches, &title);
-
-        if skip_confirm || ui::confirm_local_deletion(&local_branches) {
-            delete_branches_with_backup(&local_branches, force)?;
-        } else {
-            println!();
-            ui::info("Skipped local branch deletion.");
+    // `--force --yes` (or a policy reaching unmerged branches) would delete
+    // unmerged work without anyone looking at it
+    if (force || policy.is_some()) && skip_confirm && !confirmation.accept_data_loss {
+        let unmerged: Vec<&str> = branches
+            .iter()
+            .filter(|b| !b.is_merged)
+            .map(|b| b.name.as_str())
+            .collect();
+        if !unmerged.is_empty() {
+            anyhow::bail!(
+                "Refusing to force-delete {} unmerged {} without confirmation: {}. \
+                 Their commits are not on the default branch. To delete them anyway, add --i-understand-data-loss",
+                unmerged.len(),
+                ui::pluralize_branch(unmerged.len()),
+                unmerged.join(", ")
+            );
         }
     }
 
-    // Handle remote branches - show table as part of the warning
-    if !remote_branches.is_empty() {
-        // Add visual separation if we just handled local branches
+    let mut local_phase = DeletionPhase::default();
+    let mut remote_phase = DeletionPhase::default();
+    let mut phrase_mismatch = None;
+
+    // A failure part way still leaves a report of what was and wasn't deleted
+    let deletion = (|| -> Result<()> {
+        // Handle local branches - show table right before confirmation
         if !local_branches.is_empty() {
-            println!();
-            println!("{}", console::style("─".repeat(50)).dim());
-            println!();
+            let title = format!(
+                "Local {} to Delete:",
+                ui::pluralize_branch_cap(local_branches.len())
+            );
+            ui::display_branches(&local_branches, &title);
+
+            if skip_confirm || ui::confirm_local_deletion(&local_branches)? {
+                match delete_branches_with_backup(&local_branches, force, &records) {
+                    Ok(phase) => local_phase = phase,
+                    Err(e) => {
+                        local_phase = DeletionPhase::aborted(&local_branches, &e);
+                        return Err(e);
+                    }
+                }
+            } else {
+                outln!();
+                ui::info("Skipped local branch deletion.");
+            }
         }
 
-        // First, fetch and prune to ensure we have accurate data
-        let spinner = ui::spinner("Fetching remote to ensure data is up to date...");
-        match git::fetch_and_prune() {
-            Ok(()) => ui::spinner_success(&spinner, "Remote data is up to date"),
-            Err(e) => {
-                ui::spinner_warn(&spinner, "Could not fetch remote");
-                ui::warning(&format!("  {}", e));
-                ui::warning("  Remote branch data may be stale.");
+        // Handle remote branches - show table as part of the warning
+        if !remote_branches.is_empty() {
+            // Add visual separation if we just handled local branches
+            if !local_branches.is_empty() {
+                outln!();
+                outln!("{}", console::style(ui::RULE.as_str().repeat(50)).dim());
+                outln!();
+            }
+
+            let remote_names = remote_names(&remote_branches);
+
+            // First, fetch and prune to ensure we have accurate data
+            refresh_remote_data(&remote_names, offline);
+
+            // Show table and get confirmation
+            let title = format!(
+                "Remote {} to Delete:",
+                ui::pluralize_branch_cap(remote_branches.len())
+            );
+            ui::display_branches(&remote_branches, &title);
+
+            let urls: Vec<String> = remote_names
+                .iter()
+                .filter_map(|remote| git::get_remote_url(remote))
+                .collect();
+            let remote_url = (!urls.is_empty()).then(|| urls.join(", "));
+            // A phrase, when given, is always what decides; --yes only stands in for the prompt
+            let risk_accepted = confirmation.phrase.is_none()
+                && !skip_confirm
+                && remote_risk_accepted(confirmation.accept_remote_risk);
+            if risk_accepted {
+                // Leave a trace in CI logs of who skipped the typed phrase, and where
+                ui::warning(&format!(
+                    "Deleting {} remote {}{} without typed confirmation (--accept-risk, {}=1)",
+                    remote_branches.len(),
+                    ui::pluralize_branch(remote_branches.len()),
+                    remote_url
+                        .as_deref()
+                        .map(|url| format!(" on {}", url))
+                        .unwrap_or_default(),
+                    ACCEPT_REMOTE_RISK_ENV
+                ));
             }
+            let expected = ui::remote_confirmation_phrase(remote_branches.len());
+            let confirmed = match &confirmation.phrase {
+                // Checked like the typed answer, so the count must come from looking at the plan
+                Some(phrase) if phrase.trim() == expected => {
+                    ui::info(&format!(
+                        "Remote deletion confirmed with --confirm-phrase \"{}\"",
+                        expected
+                    ));
+                    true
+                }
+                Some(_) => {
+                    phrase_mismatch = Some(expected);
+                    false
+                }
+                None if skip_confirm || risk_accepted => true,
+                None => ui::confirm_remote_deletion(
+                    &remote_branches,
+                    &remote_names,
+                    remote_url.as_deref(),
+                )?,
+            };
+            if confirmed {
+                match delete_remote_branches_with_backup(
+                    &remote_branches,
+                    &records,
+                    config.branches.remote_delete_jobs,
+                ) {
+                    Ok(phase) => remote_phase = phase,
+                    Err(e) => {
+                        remote_phase = DeletionPhase::aborted(&remote_branches, &e);
+                        return Err(e);
+                    }
+                }
+            } else {
+                outln!();
+                ui::info("Skipped remote branch deletion.");
+            }
+        }
+        Ok(())
+    })();
+
+    if let Some(path) = &records.report {
+        let written = write_clean_report(
+            path,
+            &default_branch,
+            report_filters,
+            &branches,
+            &[&local_phase, &remote_phase],
+        );
+        deletion?;
+        written?;
+    } else {
+        deletion?;
+    }
+
+    // Checked before printing the backup paths, but reported after: the
+    // branches are gone either way and the paths are how to get them back
+    let verified = if mode == (CleanMode::Delete { verify: true }) {
+        verify_deletions(&local_phase.deleted, &remote_phase.deleted)
+    } else {
+        Ok(())
+    };
+
+    if records.print_path {
+        for path in [local_phase.backup, remote_phase.backup]
+            .into_iter()
+            .flatten()
+        {
+            let path = std::path::absolute(&path).unwrap_or_else(|_| path.into());
+            println!("{}", path.display());
         }
+    }
+    verified?;
 
-        // Show table and get confirmation
-        let title = format!(
-            "Remote {} to Delete:",
-            ui::pluralize_branch_cap(remote_branches.len())
+    if let Some(expected) = phrase_mismatch {
+        anyhow::bail!(
+            "--confirm-phrase does not match the plan; remote branches were not deleted (expected \"{}\")",
+            expected
         );
-        ui::display_branches(&remote_branches, &title);
+    }
+
+    Ok(())
+}
+
+/// "local branches only", "remote branches only" or "local and remote branches"
+fn scope_label(local_only: bool, remote_only: bool) -> &'static str {
+    match (local_only, remote_only) {
+        (true, false) => "local branches only",
+        (false, true) => "remote branches only",
+        _ => "local and remote branches",
+    }
+}
 
-        if skip_confirm || ui::confirm_remote_deletion(&remote_branches) {
-            delete_remote_branches_with_backup(&remote_branches)?;
+/// The filters behind a `clean` run, for `--report`; `selection` says how
+/// merged and unmerged branches were chosen
+fn describe_filters(
+    filter: &BranchFilter,
+    selection: String,
+    remotes: &[String],
+) -> Vec<(&'static str, String)> {
+    let list = |items: &[String]| {
+        if items.is_empty() {
+            "none".to_string()
         } else {
-            println!();
-            ui::info("Skipped remote branch deletion.");
+            items.join(", ")
         }
+    };
+    let mut filters = vec![
+        ("Age", filter.describe_age()),
+        (
+            "Scope",
+            scope_label(filter.local_only, filter.remote_only).to_string(),
+        ),
+        ("Selection", selection),
+        ("Protected", list(&filter.protected_branches)),
+    ];
+    if !filter.keep.protect.is_empty() {
+        filters.push(("Protected (--protect)", list(&filter.keep.protect)));
     }
+    filters.push(("Exclude patterns", list(&filter.exclude_patterns)));
+    filters.push(("Remotes", list(remotes)));
+    filters
+}
 
+/// `clean --report`: write what happened to each of `branches` in the
+/// deletion `phases` to `path`
+fn write_clean_report(
+    path: &std::path::Path,
+    default_branch: &str,
+    filters: Vec<(&'static str, String)>,
+    branches: &[branch::Branch],
+    phases: &[&DeletionPhase],
+) -> Result<()> {
+    let outcome = |name: &str| {
+        for phase in phases {
+            if phase.deleted.iter().any(|d| d == name) {
+                return report::Outcome::Deleted;
+            }
+            if let Some((_, reason)) = phase.failed.iter().find(|(f, _)| f == name) {
+                return report::Outcome::Failed(reason.clone());
+            }
+            if phase.already_gone.iter().any(|g| g == name) {
+                return report::Outcome::AlreadyGone;
+            }
+        }
+        report::Outcome::Kept
+    };
+    let report = report::CleanReport {
+        created: chrono::Local::now(),
+        repository: Config::get_repo_name(),
+        default_branch: default_branch.to_string(),
+        filters,
+        branches: branches
+            .iter()
+            .map(|b| (b.clone(), outcome(&b.name)))
+            .collect(),
+        backups: phases.iter().filter_map(|p| p.backup.clone()).collect(),
+        performance: perf::summary(),
+    };
+    report.write(path)?;
+    ui::info(&format!("Report written to {}", path.display()));
+    Ok(())
+}
+
+/// `clean --dry-run --eval`: the commands the run would execute, on the
+/// stdout kept by [`ui::reserve_stdout`], in the order it runs them
+fn print_eval_commands(
+    local: &[branch::Branch],
+    remote: &[branch::Branch],
+    force: bool,
+    set_e: bool,
+) {
+    if set_e {
+        println!("set -e");
+    }
+    for branch in local.iter().chain(remote) {
+        println!("{}", git::delete_command(branch, force));
+    }
+}
+
+/// Keep only the branches a `branches.delete_when` policy allows, recording
+/// the rest in `skipped`
+fn apply_delete_policy(
+    branches: &mut Vec<branch::Branch>,
+    policy: &policy::Policy,
+    skipped: &mut SkippedBranches,
+) -> Result<()> {
+    let gone = if policy.uses_gone() {
+        git::get_orphaned_local_branches()?
+    } else {
+        Default::default()
+    };
+    let rule = policy.to_string();
+    branches.retain(|b| {
+        let allowed = policy.allows(b, &gone);
+        if !allowed {
+            skipped.push((b.clone(), SkipReason::Policy(rule.clone())));
+        }
+        allowed
+    });
+    Ok(())
+}
+
+/// `clean --verify`: check that the branches reported deleted are really
+/// gone, asking remotes directly so that one recreated by a server-side hook
+/// (or another process) is caught. Fails if any still exists, or if a
+/// remote could not be asked.
+fn verify_deletions(local: &[String], remote: &[String]) -> Result<()> {
+    if local.is_empty() && remote.is_empty() {
+        return Ok(());
+    }
+
+    let mut still_present: Vec<String> = local
+        .iter()
+        .filter(|name| git::ref_exists(&format!("refs/heads/{}", name)))
+        .cloned()
+        .collect();
+
+    let remotes = if remote.is_empty() {
+        Vec::new()
+    } else {
+        git::list_remotes()?
+    };
+    let mut by_remote: std::collections::BTreeMap<&str, Vec<&str>> = Default::default();
+    for name in remote {
+        let (remote, branch) = git::split_remote_ref(name, &remotes);
+        by_remote.entry(remote).or_default().push(branch);
+    }
+    let mut unverified = 0;
+    for (remote, names) in &by_remote {
+        match git::remote_branches_present(remote, names) {
+            Ok(present) => still_present.extend(
+                names
+                    .iter()
+                    .filter(|name| present.contains(**name))
+                    .map(|name| format!("{}/{}", remote, name)),
+            ),
+            Err(e) => {
+                ui::warning(&format!(
+                    "Could not verify {} remote {} on '{}': {}",
+                    names.len(),
+                    ui::pluralize_branch(names.len()),
+                    remote,
+                    e
+                ));
+                unverified += names.len();
+            }
+        }
+    }
+
+    outln!();
+    let checked = local.len() + remote.len() - unverified;
+    if !still_present.is_empty() {
+        anyhow::bail!(
+            "{} of {} deleted {} still {}: {}. Something recreated them after deletion, \
+             such as a server-side hook or another process",
+            still_present.len(),
+            checked,
+            ui::pluralize_branch(checked),
+            ui::pluralize(still_present.len(), "exists", "exist"),
+            still_present.join(", ")
+        );
+    }
+    if unverified > 0 {
+        anyhow::bail!(
+            "Could not verify {} of {} deleted {}; the {} checked {} gone",
+            unverified,
+            local.len() + remote.len(),
+            ui::pluralize_branch(local.len() + remote.len()),
+            checked,
+            ui::pluralize(checked, "is", "are")
+        );
+    }
+    ui::success(&format!(
+        "Verified: {} deleted {} {} gone",
+        checked,
+        ui::pluralize_branch(checked),
+        ui::pluralize(checked, "is", "are")
+    ));
     Ok(())
 }
 
 /// Delete local branches and create backup file
-pub(crate) fn delete_branches_with_backup(branches: &[branch::Branch], force: bool) -> Result<()> {
-    let backup = create_backup_file(branches)?;
+fn delete_branches_with_backup(
+    branches: &[branch::Branch],
+    force: bool,
+    records: &RecordOptions,
+) -> Result<DeletionPhase> {
+    let backup = backup_before_deleting(branches, records, "local")?;
     let branch_word = ui::pluralize_branch(branches.len());
 
     // Visual separation after confirmation
-    println!();
-    println!("Deleting local {}...", branch_word);
+    outln!();
+    outln!("Deleting local {}...", branch_word);
 
-    let mut deleted = 0;
-    let mut failed = 0;
+    let mut deleted = Vec::new();
+    let mut failed = Vec::new();
+    let mut already_gone = Vec::new();
 
     for branch in branches {
-        match git::delete_local_branch(&branch.name, force || branch.merged_by_tree) {
+        match git::delete_local_branch(&branch.name, git::DeleteMode::for_branch(branch, force)) {
             Ok(()) => {
-                println!("  {} {}", console::style("✅").green(), branch.name);
-                deleted += 1;
+                ui::print_deleted_branch(branch);
+                deleted.push(branch.name.clone());
+            }
+            Err(e) if git::is_gone_locally(&e) => {
+                print_gone_branch(&branch.name);
+                already_gone.push(branch.name.clone());
             }
             Err(e) => {
-                println!("  {} {} ({})", console::style("❌").red(), branch.name, e);
-                failed += 1;
+                outln!(
+                    "  {} {} ({})",
+                    console::style(ui::ERROR).red(),
+                    branch.name,
+                    e
+                );
+                failed.push((branch.name.clone(), e.to_string()));
             }
         }
     }
 
-    // Summary footer
-    println!();
-    let branch_word = ui::pluralize_branch(deleted);
-    if failed == 0 {
-        ui::success(&format!("Deleted {} local {}", deleted, branch_word));
-    } else {
-        ui::warning(&format!(
-            "Deleted {} local {}, {} failed",
-            deleted, branch_word, failed
-        ));
-    }
-    println!(
-        "  {} Backup: {}",
-        console::style("↪").dim(),
-        console::style(&backup).dim()
-    );
-
-    Ok(())
+    let phase = DeletionPhase {
+        deleted,
+        failed,
+        already_gone,
+        backup,
+    };
+    print_deletion_summary(&phase, "local", branches);
+    Ok(phase)
 }
 
 /// Delete remote branches and create backup file.
-/// Uses batch `git push origin --delete` for a single network round-trip.
-pub(crate) fn delete_remote_branches_with_backup(branches: &[branch::Branch]) -> Result<()> {
-    let backup = create_backup_file(branches)?;
+/// Uses one batched `git push <remote> --delete` per remote, or parallel
+/// per-branch pushes when `branches.remote_delete_jobs` is set.
+fn delete_remote_branches_with_backup(
+    branches: &[branch::Branch],
+    records: &RecordOptions,
+    jobs: Option<usize>,
+) -> Result<DeletionPhase> {
+    let backup = backup_before_deleting(branches, records, "remote")?;
     let branch_word = ui::pluralize_branch(branches.len());
 
     // Visual separation after confirmation
-    println!();
-    println!("Deleting remote {}...", branch_word);
+    outln!();
+    outln!("Deleting remote {}...", branch_word);
 
     let names: Vec<String> = branches.iter().map(|b| b.name.clone()).collect();
-    let results = git::delete_remote_branches_batch(&names)?;
+    let results = git::delete_remote_branches(&names, jobs)?;
 
-    let mut deleted = 0;
-    let mut failed = 0;
+    let mut deleted = Vec::new();
+    let mut failed = Vec::new();
+    let mut already_gone = Vec::new();
 
     for (name, success, error) in &results {
         if *success {
-            println!("  {} {}", console::style("✅").green(), name);
-            deleted += 1;
+            match branches.iter().find(|b| &b.name == name) {
+                Some(branch) => ui::print_deleted_branch(branch),
+                None => outln!("  {} {}", console::style(ui::SUCCESS).green(), name),
+            }
+            deleted.push(name.clone());
+        } else if error.as_deref().is_some_and(git::is_gone_on_remote) {
+            print_gone_branch(name);
+            already_gone.push(name.clone());
         } else {
             let err_msg = error.as_deref().unwrap_or("unknown error");
-            println!("  {} {} ({})", console::style("❌").red(), name, err_msg);
-            failed += 1;
+            outln!(
+                "  {} {} ({})",
+                console::style(ui::ERROR).red(),
+                name,
+                err_msg
+            );
+            failed.push((name.clone(), err_msg.to_string()));
         }
     }
 
-    // Summary footer
-    println!();
-    let branch_word = ui::pluralize_branch(deleted);
-    if failed == 0 {
-        ui::success(&format!("Deleted {} remote {}", deleted, branch_word));
+    let phase = DeletionPhase {
+        deleted,
+        failed,
+        already_gone,
+        backup,
+    };
+    print_deletion_summary(&phase, "remote", branches);
+    Ok(phase)
+}
+
+/// A branch that was already deleted when its turn came: nothing went wrong
+fn print_gone_branch(name: &str) {
+    outln!(
+        "  {} {} {}",
+        console::style(ui::GONE).dim(),
+        name,
+        console::style("(already gone)").dim()
+    );
+}
+
+/// Footer of a deletion phase (`kind` is "local" or "remote"): the counts,
+/// then where the backup went and how to restore from it
+fn print_deletion_summary(phase: &DeletionPhase, kind: &str, branches: &[branch::Branch]) {
+    outln!();
+    let mut summary = format!(
+        "Deleted {} {} {}",
+        phase.deleted.len(),
+        kind,
+        ui::pluralize_branch(phase.deleted.len())
+    );
+    if !phase.already_gone.is_empty() {
+        summary.push_str(&format!(", {} already gone", phase.already_gone.len()));
+    }
+    if phase.failed.is_empty() {
+        ui::success(&summary);
     } else {
+        ui::warning(&format!("{}, {} failed", summary, phase.failed.len()));
+    }
+    if let Some(backup) = &phase.backup {
+        ui::print_backup_path(backup);
+        print_recovery_hint(backup, branches);
+    }
+}
+
+/// Write the backup for a deletion phase (`kind` is "local" or "remote"),
+/// unless `--no-backup` was given. If it can't be written, nothing in this
+/// phase is deleted and the error ends the run, so the remote phase never
+/// proceeds after the local one failed to back up.
+fn backup_before_deleting(
+    branches: &[branch::Branch],
+    options: &RecordOptions,
+    kind: &str,
+) -> Result<Option<String>> {
+    if options.skip {
         ui::warning(&format!(
-            "Deleted {} remote {}, {} failed",
-            deleted, branch_word, failed
+            "Not backing up {} {} {} (--no-backup); they can't be restored with `deadbranch backup restore`",
+            branches.len(),
+            kind,
+            ui::pluralize_branch(branches.len())
         ));
+        return Ok(None);
     }
-    println!(
-        "  {} Backup: {}",
-        console::style("↪").dim(),
-        console::style(&backup).dim()
+    create_backup_file(branches, options.label.as_deref())
+        .map(Some)
+        .with_context(|| {
+            let dir = Config::repo_backup_dir(&Config::get_repo_name())
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_else(|_| "the backup directory".to_string());
+            format!(
+                "Aborted before deleting any {} branches: the backup could not be written. \
+                 Make sure {} is writable, or pass --no-backup to delete without a backup",
+                kind, dir
+            )
+        })
+}
+
+/// Put a restored branch back on the remote it was deleted from, with
+/// `--push` or when confirmed. A failed push leaves the local branch restored.
+fn push_restored_branch(branch: &str, remote: &str, remote_branch: &str, push: bool) -> Result<()> {
+    ui::info(&format!(
+        "'{}' was deleted from {} (as {}/{})",
+        branch, remote, remote, remote_branch
+    ));
+    let command = format!(
+        "git push {} {}:{}",
+        shell::shell_quote(remote),
+        shell::shell_quote(branch),
+        shell::shell_quote(remote_branch)
     );
+    let push = push
+        || (console::Term::stdout().is_term()
+            && ui::confirm(&format!("Push it back to {}?", remote), false)?);
+    if !push {
+        ui::hint(&format!(
+            "To put it back on {}, rerun with --push or run: {}",
+            remote, command
+        ));
+        return Ok(());
+    }
 
+    git::push_branch(remote, branch, remote_branch).with_context(|| {
+        format!(
+            "Restored '{}' locally, but pushing it to {} failed (retry with: {})",
+            branch, remote, command
+        )
+    })?;
+    ui::success(&format!(
+        "Pushed '{}' to {}/{}",
+        branch, remote, remote_branch
+    ));
     Ok(())
 }
 
+/// `backup restore --matching`: preview the backup's branches that match
+/// `pattern`, confirm, then restore each one, reporting per branch. Fails when
+/// any of them couldn't be restored or pushed back.
+fn restore_matching(
+    pattern: &str,
+    from: Option<&str>,
+    repo: Option<&str>,
+    ignore_repo_mismatch: bool,
+    force: bool,
+    push: bool,
+    yes: bool,
+) -> Result<()> {
+    let find =
+        |ignore_mismatch: bool| backup::find_matching_entries(pattern, from, repo, ignore_mismatch);
+    let (backup_path, entries) = match find(ignore_repo_mismatch) {
+        // Interactive sessions may confirm instead of passing --ignore-repo-mismatch
+        Err(e @ backup::RestoreError::RepoMismatch { .. }) if console::Term::stdout().is_term() => {
+            ui::warning(&e.to_string());
+            if ui::confirm("Restore from this backup anyway?", false)? {
+                find(true)
+            } else {
+                Err(e)
+            }
+        }
+        other => other,
+    }?;
+
+    let file = backup_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| backup_path.display().to_string());
+    ui::display_restore_selection(pattern, &file, &entries);
+    let count = entries.len();
+    if !yes && !console::Term::stdout().is_term() {
+        anyhow::bail!(
+            "Restoring {} {} needs confirmation, and there is no terminal to ask on; pass --yes to restore them",
+            count,
+            ui::pluralize_branch(count)
+        );
+    }
+    if !yes
+        && !ui::confirm(
+            &format!("Restore {} {}?", count, ui::pluralize_branch(count)),
+            false,
+        )?
+    {
+        ui::info("Cancelled");
+        return Ok(());
+    }
+
+    let (mut failed, mut not_pushed, mut existing) = (0, 0, 0);
+    for entry in &entries {
+        let result = match backup::restore_entry(entry, &entry.name, force) {
+            Ok(result) => result,
+            Err(e) => {
+                if matches!(e, backup::RestoreError::BranchExists { .. }) {
+                    existing += 1;
+                }
+                ui::error(&e.to_string());
+                failed += 1;
+                continue;
+            }
+        };
+        ui::display_restore_success(&result);
+        if let Some((remote, remote_branch)) = &result.deleted_from {
+            if let Err(e) = push_restored_branch(&result.restored_name, remote, remote_branch, push)
+            {
+                ui::error(&format!("{:#}", e));
+                not_pushed += 1;
+            }
+        }
+    }
+
+    if existing > 0 {
+        ui::hint("To overwrite existing branches, rerun with --force");
+    }
+    let restored = count - failed;
+    if failed == 0 && not_pushed == 0 {
+        ui::success(&format!(
+            "Restored {} {} matching '{}'",
+            restored,
+            ui::pluralize_branch(restored),
+            pattern
+        ));
+        return Ok(());
+    }
+    let pushes = match not_pushed {
+        0 => String::new(),
+        n => format!(", {} not pushed back", n),
+    };
+    anyhow::bail!(
+        "Restored {} of {} {} matching '{}'{}",
+        restored,
+        count,
+        ui::pluralize_branch(count),
+        pattern,
+        pushes
+    )
+}
+
+/// Tell the user how to bring deleted branches back from `backup`
+fn print_recovery_hint(backup: &str, branches: &[branch::Branch]) {
+    let file = std::path::Path::new(backup)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| backup.to_string());
+    let branch = match branches {
+        [only] => only.short_name(),
+        _ => "<branch>",
+    };
+    ui::hint(&format!(
+        "To undo: deadbranch backup restore {} --from {}",
+        branch, file
+    ));
+}
+
 /// Create a backup file with branch SHAs for potential restoration
 /// Saves to ~/.deadbranch/backups/<repo-name>/backup-<timestamp>.txt
-pub(crate) fn create_backup_file(branches: &[branch::Branch]) -> Result<String> {
+pub(crate) fn create_backup_file(
+    branches: &[branch::Branch],
+    label: Option<&str>,
+) -> Result<String> {
+    let _timer = perf::phase(Phase::BackupWriting);
     let repo_name = Config::get_repo_name();
     let backup_dir = Config::repo_backup_dir(&repo_name)?;
+    // A broken template must stop the run here, before any branch is deleted
+    let backups_config = Config::load()?.backups;
+    backup::set_max_file_size_mb(backups_config.max_file_size_mb());
+    let template = backups_config
+        .template
+        .map(|path| backup::BackupTemplate::load(&path))
+        .transpose()?;
+
+    for other in Config::case_colliding_backup_dirs(&repo_name) {
+        ui::warning(&format!(
+            "Backups for '{}' and '{}' differ only by case and share a directory on case-insensitive filesystems",
+            repo_name, other
+        ));
+        ui::hint("Give this repository its own name with `deadbranch config set repo-name <name>`");
+    }
 
     // Create backup directory if it doesn't exist
-    fs::create_dir_all(&backup_dir)?;
+    fs::create_dir_all(&backup_dir)
+        .with_context(|| format!("Failed to create backup directory {}", backup_dir.display()))?;
 
-    let timestamp = Utc::now().format("%Y%m%d-%H%M%S");
-    let filename = format!("backup-{}.txt", timestamp);
-    let backup_path = backup_dir.join(&filename);
+    let repo_dir = git::get_repo_dir();
 
-    let mut file = fs::File::create(&backup_path)?;
+    let created = Utc::now();
+    let mut contents: Vec<u8> = Vec::new();
 
-    writeln!(file, "# deadbranch backup")?;
-    writeln!(file, "# Created: {}", Utc::now().to_rfc3339())?;
-    writeln!(file, "# Repository: {}", repo_name)?;
+    writeln!(contents, "{}", backup::BACKUP_HEADER)?;
+    writeln!(
+        contents,
+        "{} {}",
+        backup::FORMAT_HEADER,
+        backup::BACKUP_FORMAT_VERSION
+    )?;
+    writeln!(contents, "# Created: {}", created.to_rfc3339())?;
+    writeln!(contents, "# Repository: {}", repo_name)?;
+    if let Some(dir) = &repo_dir {
+        writeln!(contents, "{} {}", backup::REPO_PATH_HEADER, dir.display())?;
+    }
+    if let Some(label) = label {
+        writeln!(contents, "{} {}", backup::LABEL_HEADER, label)?;
+    }
     writeln!(
-        file,
+        contents,
         "# Working directory: {}",
         std::env::current_dir()?.display()
     )?;
-    writeln!(file, "#")?;
-    writeln!(file, "# To restore a branch, run the git command shown")?;
-    writeln!(file, "#")?;
-    writeln!(file)?;
+    writeln!(contents, "#")?;
+    if template.is_some() {
+        writeln!(
+            contents,
+            "# Written from a custom template; restore with `deadbranch backup restore`"
+        )?;
+    } else {
+        writeln!(contents, "# To restore a branch, run the git command shown")?;
+    }
+    writeln!(contents, "#")?;
+    writeln!(contents)?;
+
+    // Per-branch config (description, upstream, pushRemote) is removed by
+    // `git branch -d`, so record it alongside the SHA for restore.
+    let branch_configs = git::get_branch_configs();
 
     for branch in branches {
-        let sha =
-            git::get_branch_sha(&branch.name).unwrap_or_else(|_| branch.last_commit_sha.clone());
-        let restore_name = if branch.is_remote {
-            branch.name.strip_prefix("origin/").unwrap_or(&branch.name)
-        } else {
-            &branch.name
-        };
-        writeln!(file, "# {}", branch.name)?;
-        writeln!(file, "git branch {} {}", restore_name, sha)?;
-        writeln!(file)?;
+        let sha = git::get_branch_sha(&branch.full_ref())
+            .unwrap_or_else(|_| branch.last_commit_sha.clone());
+        let restore_name = branch.short_name();
+        writeln!(contents, "# {}", branch.name)?;
+        if !branch.is_remote {
+            for (key, value) in branch_configs.get(&branch.name).into_iter().flatten() {
+                writeln!(
+                    contents,
+                    "{}",
+                    backup::format_config_comment(restore_name, key, value)
+                )?;
+            }
+        }
+        match &template {
+            Some(template) => {
+                let remote = if let Some(remote) = &branch.remote {
+                    remote.as_str()
+                } else {
+                    branch_configs
+                        .get(&branch.name)
+                        .and_then(|config| config.iter().find(|(key, _)| key == "remote"))
+                        .map_or("", |(_, remote)| remote.as_str())
+                };
+                contents.extend_from_slice(template.render(restore_name, &sha, remote).as_bytes());
+            }
+            None => writeln!(contents, "git branch {} {}", restore_name, sha)?,
+        }
+        writeln!(contents)?;
+    }
+
+    // Seal the file with a checksum over the exact bytes above it
+    let checksum = backup::checksum_line(&contents);
+    contents.extend_from_slice(checksum.as_bytes());
+    let backup_path = backup::write_new_backup(&backup_dir, created, &contents)?;
+    if backup_path
+        .file_stem()
+        .is_some_and(|stem| stem.len() > "backup-YYYYMMDD-HHMMSS".len())
+    {
+        ui::verbose(&format!(
+            "Another backup was written this second; saved this one as {}",
+            backup_path.display()
+        ));
     }
 
     Ok(backup_path.display().to_string())
 }
 
+/// Print snippets that run `deadbranch prune --yes` in this repository on a
+/// schedule, or with `--install-hint` the steps to set one up. Nothing on the
+/// system is changed.
+fn cmd_schedule(install_hint: bool) -> Result<()> {
+    let repo = git::get_repo_dir().context("Could not determine the repository path")?;
+    let exe = std::env::current_exe().context("Could not determine the deadbranch executable")?;
+    let repo_name = Config::get_repo_name();
+    let unit = schedule::unit_name(&repo_name);
+    let cron = schedule::cron_entry(&repo, &exe);
+    let (service, timer) = schedule::systemd_units(&repo, &exe, &repo_name);
+
+    if install_hint {
+        ui::info(&format!(
+            "To clean up '{}' every week (deadbranch doesn't change your system itself):",
+            repo.display()
+        ));
+        outln!();
+        outln!("  With cron:");
+        outln!("    1. Run `crontab -e`");
+        outln!("    2. Add this line and save:");
+        outln!("       {}", cron);
+        outln!();
+        outln!("  With a systemd user timer:");
+        outln!(
+            "    1. Save the two units printed by `deadbranch schedule --show` as ~/.config/systemd/user/{}.service and {}.timer",
+            unit, unit
+        );
+        outln!("    2. Run `systemctl --user daemon-reload`");
+        outln!("    3. Run `systemctl --user enable --now {}.timer`", unit);
+        outln!("    4. Check it with `systemctl --user list-timers`");
+        outln!();
+        ui::hint("Each run deletes merged branches older than your default age threshold and writes a backup; try `deadbranch clean --dry-run` first");
+        return Ok(());
+    }
+
+    outln!("# crontab entry (every Monday at 09:00)");
+    outln!("{}", cron);
+    outln!();
+    outln!("# ~/.config/systemd/user/{}.service", unit);
+    out!("{}", service);
+    outln!();
+    outln!("# ~/.config/systemd/user/{}.timer", unit);
+    out!("{}", timer);
+    Ok(())
+}
+
 /// Handle config subcommands
 fn cmd_config(action: ConfigAction) -> Result<()> {
+    if matches!(
+        action,
+        ConfigAction::Set { .. }
+            | ConfigAction::Edit
+            | ConfigAction::Reset { .. }
+            | ConfigAction::Migrate { .. }
+            | ConfigAction::Import { .. }
+    ) {
+        config::ensure_file_in_use("change the config file")?;
+    }
     match action {
         ConfigAction::Show => {
             let config = Config::load()?;
-            let config_path = Config::config_path()
-                .map(|p| p.display().to_string())
-                .unwrap_or_else(|_| "(unknown)".to_string());
-
-            ui::display_config(
-                config.general.default_days,
-                &config.branches.protected,
-                &config.branches.exclude_patterns,
-                config.branches.default_branch.as_deref(),
-                &config_path,
-            );
+            let config_path = if config::ignores_file() {
+                "(not read: --ignore-config)".to_string()
+            } else {
+                match Config::config_path() {
+                    Ok(path) if path.exists() => path.display().to_string(),
+                    Ok(path) => format!("{} (not created yet: defaults)", path.display()),
+                    Err(_) => "(unknown)".to_string(),
+                }
+            };
+
+            ui::display_config(&config, &config_path);
         }
 
-        ConfigAction::Set { key, values } => {
+        ConfigAction::Set {
+            key,
+            values,
+            append,
+            remove,
+            yes,
+        } => {
             let mut config = Config::load()?;
+            let old = config.list_value(&key).map(<[String]>::to_vec);
+
+            let values = match &old {
+                None if append || remove => anyhow::bail!(
+                    "--append and --remove only work with list keys (protected-branches, exclude-patterns)"
+                ),
+                Some(old) if append => {
+                    let mut list = old.clone();
+                    for value in values {
+                        if !list.contains(&value) {
+                            list.push(value);
+                        }
+                    }
+                    list
+                }
+                Some(old) if remove => {
+                    if let Some(missing) = values.iter().find(|v| !old.contains(v)) {
+                        anyhow::bail!("'{}' is not in {}", missing, key);
+                    }
+                    old.iter()
+                        .filter(|v| !values.contains(v))
+                        .cloned()
+                        .collect()
+                }
+                _ => values,
+            };
             config.set(&key, &values)?;
-            config.save()?;
+            for warning in config.warnings() {
+                ui::warning(&warning);
+            }
 
             // Format display based on single value or list
-            let display_value = if values.len() == 1 {
-                values[0].clone()
-            } else {
-                values.join(", ")
+            let display_value = match config.list_value(&key) {
+                Some([]) => "(none)".to_string(),
+                Some(list) => list.join(", "),
+                None => values.join(", "),
             };
+
+            if let (Some(old), Some(new)) = (&old, config.list_value(&key)) {
+                ui::display_list_change(&key, old, new);
+            }
+
+            if readonly::is_enabled() {
+                ui::check_notice(&format!("Would set {} = {}", key, display_value));
+                return Ok(());
+            }
+
+            // Replacing a list by mistake is the easy way to lose settings;
+            // --remove already says entries should go
+            if let (Some(old), Some(new)) = (&old, config.list_value(&key)) {
+                let dropped = old.iter().filter(|v| !new.contains(v)).count();
+                if new.len() < old.len() && !remove && !yes {
+                    let prompt = format!(
+                        "Remove {} {} from {}?",
+                        dropped,
+                        if dropped == 1 { "entry" } else { "entries" },
+                        key
+                    );
+                    if !console::Term::stdout().is_term() {
+                        anyhow::bail!(
+                            "Removing {} {} from {} needs confirmation, and there is no terminal to ask on; pass --yes to replace the list (or --append to add values)",
+                            dropped,
+                            if dropped == 1 { "entry" } else { "entries" },
+                            key
+                        );
+                    }
+                    if !ui::confirm(&prompt, false)? {
+                        ui::info("Cancelled (use --append to add values, or --yes to replace)");
+                        return Ok(());
+                    }
+                }
+            }
+
+            config.save()?;
             ui::success(&format!("Set {} = {}", key, display_value));
         }
 
         ConfigAction::Edit => {
-            // Ensure config file exists
-            let _ = Config::load()?;
+            readonly::ensure_writable("open the config in an editor")?;
+
+            // Give the editor the defaults to start from
             let config_path = Config::config_path()?;
+            if !config_path.exists() {
+                Config::default().save()?;
+            }
 
             // Get editor from $EDITOR or $VISUAL, fallback to common editors
             let editor = std::env::var("EDITOR")
                 .or_else(|_| std::env::var("VISUAL"))
                 .unwrap_or_else(|_| {
-                    // Try common editors
-                    if which::which("nano").is_ok() {
+                    // Windows always has notepad; nano and vim rarely exist there
+                    if cfg!(windows) {
+                        "notepad".to_string()
+                    } else if which::which("nano").is_ok() {
                         "nano".to_string()
                     } else if which::which("vim").is_ok() {
                         "vim".to_string()
@@ -540,24 +2422,417 @@ fn cmd_config(action: ConfigAction) -> Result<()> {
             }
         }
 
-        ConfigAction::Reset => {
-            if ui::confirm("Reset configuration to defaults?", false) {
-                let config = Config::default();
-                config.save()?;
-                ui::success("Configuration reset to defaults");
-            } else {
+        ConfigAction::Reset { section, yes } => {
+            let what = match section {
+                Some(ConfigSection::General) => "the [general] section",
+                Some(ConfigSection::Branches) => "the [branches] section",
+                Some(ConfigSection::Ui) => "the [ui] section",
+                Some(ConfigSection::Backups) => "the [backups] section",
+                None => "configuration",
+            };
+            if readonly::is_enabled() {
+                ui::check_notice(&format!("Would reset {} to defaults", what));
+                return Ok(());
+            }
+            if !yes && !console::Term::stdout().is_term() {
+                anyhow::bail!(
+                    "Resetting {} needs confirmation, and there is no terminal to ask on; pass --yes to reset it",
+                    what
+                );
+            }
+            if !yes && !ui::confirm(&format!("Reset {} to defaults?", what), false)? {
+                ui::info("Cancelled");
+                return Ok(());
+            }
+
+            let config = match section {
+                Some(section) => {
+                    let mut config = Config::load()?;
+                    config.reset_section(section);
+                    config
+                }
+                None => Config::default(),
+            };
+            let backup_path = Config::backup_active("config reset")?;
+            config.save()?;
+            ui::success(&format!("Reset {} to defaults", what));
+            if let Some(backup_path) = backup_path {
+                ui::info(&format!(
+                    "Previous configuration saved to {}",
+                    backup_path.display()
+                ));
+            }
+        }
+
+        ConfigAction::Export { path, force } => {
+            if path.exists() && !force {
+                anyhow::bail!(
+                    "{} already exists (use --force to overwrite)",
+                    path.display()
+                );
+            }
+
+            if readonly::is_enabled() {
+                ui::check_notice(&format!("Would export configuration to {}", path.display()));
+                return Ok(());
+            }
+
+            let config = Config::load()?;
+            config.save_to(&path)?;
+            ui::success(&format!("Exported configuration to {}", path.display()));
+        }
+
+        ConfigAction::Migrate { yes } => {
+            let path = Config::config_path()?;
+            if !path.exists() {
+                ui::info("No config file yet; nothing to migrate");
+                return Ok(());
+            }
+
+            let mut config = Config::load_from(&path)?;
+            if !config.needs_migration() {
+                ui::success(&format!(
+                    "Config is already in the current format (version {})",
+                    config::CONFIG_VERSION
+                ));
+                return Ok(());
+            }
+            for note in &config.migration_notes {
+                ui::info(note);
+            }
+
+            if readonly::is_enabled() {
+                ui::check_notice(&format!(
+                    "Would rewrite {} as config version {}",
+                    path.display(),
+                    config::CONFIG_VERSION
+                ));
+                return Ok(());
+            }
+            if !yes && !console::Term::stdout().is_term() {
+                anyhow::bail!(
+                    "Rewriting {} needs confirmation, and there is no terminal to ask on; pass --yes to migrate it",
+                    path.display()
+                );
+            }
+            let prompt = format!("Rewrite {} in the current format?", path.display());
+            if !yes && !ui::confirm(&prompt, false)? {
+                ui::info("Cancelled");
+                return Ok(());
+            }
+
+            let backup_path = Config::backup_active("config migrate")?;
+            config.version = config::CONFIG_VERSION;
+            config.save()?;
+            ui::success(&format!(
+                "Migrated config to version {}",
+                config::CONFIG_VERSION
+            ));
+            if let Some(backup_path) = backup_path {
+                ui::info(&format!(
+                    "Previous configuration saved to {}",
+                    backup_path.display()
+                ));
+            }
+        }
+
+        ConfigAction::Validate { path } => {
+            let path = match path {
+                Some(path) => path,
+                None => Config::config_path()?,
+            };
+            let config = Config::load_from(&path)?;
+            if let Err(e) = config.validate() {
+                anyhow::bail!("Invalid configuration in {}: {}", path.display(), e);
+            }
+            for note in &config.migration_notes {
+                ui::warning(note);
+            }
+            for warning in config.warnings() {
+                ui::warning(&warning);
+            }
+            ui::success(&format!("Configuration in {} is valid", path.display()));
+        }
+
+        ConfigAction::Import { path, yes } => {
+            let config = Config::load_from(&path)?;
+            if let Err(e) = config.validate() {
+                anyhow::bail!("Invalid configuration in {}: {}", path.display(), e);
+            }
+            for warning in config.warnings() {
+                ui::warning(&warning);
+            }
+
+            if readonly::is_enabled() {
+                ui::check_notice(&format!(
+                    "Would replace the active configuration with {}",
+                    path.display()
+                ));
+                return Ok(());
+            }
+
+            if !yes && !console::Term::stdout().is_term() {
+                anyhow::bail!(
+                    "Replacing the active configuration with {} needs confirmation, and there is no terminal to ask on; pass --yes to import it",
+                    path.display()
+                );
+            }
+            let prompt = format!("Replace the active configuration with {}?", path.display());
+            if !yes && !ui::confirm(&prompt, false)? {
                 ui::info("Cancelled");
+                return Ok(());
+            }
+
+            let backup_path = Config::backup_active("config import")?;
+            config.save()?;
+            ui::success(&format!("Imported configuration from {}", path.display()));
+            if let Some(backup_path) = backup_path {
+                ui::info(&format!(
+                    "Previous configuration saved to {}",
+                    backup_path.display()
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Walk through the key settings and write the config file (`--defaults`
+/// writes the defaults without asking)
+fn cmd_init(defaults: bool) -> Result<()> {
+    readonly::ensure_writable("write the config file")?;
+    config::ensure_file_in_use("write the config file")?;
+    let path = Config::config_path()?;
+    let exists = path.exists();
+
+    if defaults {
+        if exists {
+            ui::info(&format!(
+                "Config already exists at {} (left unchanged)",
+                path.display()
+            ));
+        } else {
+            Config::default().save()?;
+            ui::success(&format!("Wrote default config to {}", path.display()));
+        }
+        return Ok(());
+    }
+
+    if !console::Term::stdout().is_term() {
+        anyhow::bail!(
+            "deadbranch init needs an interactive terminal (use `deadbranch init --defaults` to write the default config)"
+        );
+    }
+
+    let mut config = if exists {
+        if !ui::confirm(
+            &format!("A config already exists at {}. Update it?", path.display()),
+            true,
+        )? {
+            ui::info("Cancelled");
+            return Ok(());
+        }
+        Config::load_from(&path)?
+    } else {
+        Config::default()
+    };
+    let cancelled = || anyhow::anyhow!("Setup cancelled, nothing was written");
+
+    // Staleness threshold
+    loop {
+        let answer = ui::input(
+            "Treat branches as stale after (days, or a duration like 3w)",
+            &config.general.default_days.to_string(),
+        )?
+        .ok_or_else(cancelled)?;
+        match duration::parse_days(answer.trim()) {
+            Ok(days) => {
+                config.general.default_days = days;
+                break;
+            }
+            Err(e) => ui::warning(&e),
+        }
+    }
+
+    // Default branch, offering the one detected in this repository
+    let in_repo = git::is_git_repository();
+    let detected = in_repo.then(git::get_default_branch).and_then(Result::ok);
+    if let Some(branch) = &detected {
+        ui::info(&format!("Detected default branch: {}", branch));
+    }
+    let initial = config
+        .branches
+        .default_branch
+        .clone()
+        .or_else(|| detected.clone())
+        .unwrap_or_default();
+    let answer =
+        ui::input("Default branch (empty to auto-detect)", &initial)?.ok_or_else(cancelled)?;
+    let answer = answer.trim();
+    config.branches.default_branch = (!answer.is_empty()).then(|| answer.to_string());
+
+    // Long-lived branches in this repository that aren't protected yet
+    if in_repo {
+        let default_branch = config
+            .branches
+            .default_branch
+            .clone()
+            .or(detected)
+            .unwrap_or_else(|| "main".to_string());
+        let branches = git::list_branches(&default_branch).unwrap_or_default();
+        let candidates = branch::long_lived_candidates(
+            branches.iter().map(branch::Branch::short_name),
+            &config.branches.protected,
+        );
+        if !candidates.is_empty() {
+            let selected = ui::multi_select(
+                "Protect these long-lived branches? (space to toggle, enter to confirm)",
+                &candidates,
+                &vec![true; candidates.len()],
+            )?
+            .ok_or_else(cancelled)?;
+            for i in selected {
+                config.branches.protected.push(candidates[i].clone());
             }
         }
     }
 
+    // Exclude patterns
+    loop {
+        let answer = ui::input(
+            "Exclude patterns (comma-separated, e.g. wip/*)",
+            &config.branches.exclude_patterns.join(", "),
+        )?
+        .ok_or_else(cancelled)?;
+        let patterns: Vec<String> = answer
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(str::to_string)
+            .collect();
+        match config.set("exclude-patterns", &patterns) {
+            Ok(()) => break,
+            Err(e) => ui::warning(&e.to_string()),
+        }
+    }
+
+    config.validate()?;
+    if exists {
+        Config::backup_active("init")?;
+    }
+    config.save()?;
+    for warning in config.warnings() {
+        ui::warning(&warning);
+    }
+
+    ui::display_config(&config, &path.display().to_string());
+    ui::success(&format!("Saved config to {}", path.display()));
+    Ok(())
+}
+
+/// Longest `status` waits for the counts; a prompt can't wait for a huge repository
+const STATUS_TIME_BUDGET: std::time::Duration = std::time::Duration::from_millis(500);
+
+/// Count stale branches for a shell prompt. The counting runs on its own
+/// thread so that a slow repository costs at most [`STATUS_TIME_BUDGET`];
+/// past that, its git subprocesses are stopped rather than left running.
+fn cmd_status(short: bool, format: StatusFormat) -> Result<()> {
+    perf::set_stoppable(true);
+    let (sender, receiver) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(count_stale_branches());
+    });
+    let counts = receiver.recv_timeout(STATUS_TIME_BUDGET);
+    if counts.is_err() {
+        perf::stop_git();
+    }
+
+    if short {
+        // A prompt shows nothing rather than an error
+        if let Ok(Ok(counts)) = counts {
+            if counts.stale > 0 {
+                outln!("{}", counts.stale);
+            }
+        }
+        return Ok(());
+    }
+    let Ok(counts) = counts else {
+        ui::warning_stderr(&format!(
+            "Counting stale branches took longer than {}ms; run `deadbranch list` instead",
+            STATUS_TIME_BUDGET.as_millis()
+        ));
+        return Ok(());
+    };
+    let counts = counts?;
+    match format {
+        StatusFormat::Json => ui::print_json(&counts)?,
+        StatusFormat::Text if counts.stale == 0 => ui::info("No stale branches"),
+        StatusFormat::Text => ui::info(&format!(
+            "{} stale {} ({} local, {} remote; {} merged)",
+            counts.stale,
+            ui::pluralize_branch(counts.stale),
+            counts.local,
+            counts.remote,
+            counts.merged
+        )),
+    }
     Ok(())
 }
 
+/// The branches a plain `list` shows, counted without fetching or the
+/// squash-merge checks
+fn count_stale_branches() -> Result<stats::StaleCounts> {
+    if !git::has_commits() {
+        return Ok(stats::StaleCounts::default());
+    }
+    // Quietly: a prompt showing `status --short` has no room for notices
+    let config = Config::load_quietly()?;
+    let (local_only, remote_only) = config.general.default_scope.resolve(false, false);
+    let (min_age_days, remote_min_age_days) = age_thresholds(None, &config.general);
+    let default_branch = config
+        .branches
+        .default_branch
+        .clone()
+        .unwrap_or_else(|| git::get_default_branch().unwrap_or_else(|_| "main".to_string()));
+
+    let filter = BranchFilter {
+        min_age_days,
+        remote_min_age_days,
+        local_only,
+        remote_only,
+        merged_only: false,
+        protected_branches: config.branches.protected.clone(),
+        exclude_patterns: config.exclude_patterns_for_repo(),
+        keep: KeepRules::default(),
+        orphaned_local_only: false,
+        stale_remote_only: false,
+        min_depth: None,
+        max_depth: None,
+        commit_window: CommitWindow::default(),
+        min_commits: None,
+        max_commits: None,
+    };
+    let remotes = resolve_remotes(&RemoteArgs::default())?;
+    // Only the cheap filters: no keep rules and no squash-merge pass
+    let branches = git::list_branches_matching(
+        &default_branch,
+        !filter.remote_only,
+        if filter.local_only { &[] } else { &remotes },
+        |b| filter.matches_pre_merge(b),
+    )?;
+    Ok(stats::StaleCounts::of(&branches))
+}
+
 /// Show repository branch statistics
-fn cmd_stats(days: Option<u32>) -> Result<()> {
+fn cmd_stats(days: Option<u32>, remotes: &RemoteArgs) -> Result<()> {
+    if !git::has_commits() {
+        ui::info(NO_COMMITS_YET);
+        return Ok(());
+    }
     let config = Config::load()?;
-    let min_age = days.unwrap_or(config.general.default_days);
+    let remotes = resolve_remotes(remotes)?;
+    let (min_age, remote_min_age_days) = age_thresholds(days, &config.general);
 
     let default_branch = config
         .branches
@@ -565,103 +2840,425 @@ fn cmd_stats(days: Option<u32>) -> Result<()> {
         .clone()
         .unwrap_or_else(|| git::get_default_branch().unwrap_or_else(|_| "main".to_string()));
 
+    report_submodule();
     ui::info(&format!(
         "Using '{}' as the default branch for merge detection",
         default_branch
     ));
 
+    // The stale thresholds label branches; they don't filter them
+    let thresholds = BranchFilter {
+        min_age_days: min_age,
+        remote_min_age_days,
+        ..Default::default()
+    };
+    report_age_rules(days, &thresholds.describe_age(), None);
+
     // Apply the same visibility rules as list/clean: respect protected and
     // exclude_patterns, but no age filter — stats covers all visible branches.
     let filter = BranchFilter {
         min_age_days: 0,
+        remote_min_age_days: None,
         local_only: false,
         remote_only: false,
         merged_only: false,
-        protected_branches: config.branches.protected,
-        exclude_patterns: config.branches.exclude_patterns,
+        protected_branches: config.branches.protected.clone(),
+        exclude_patterns: config.exclude_patterns_for_repo(),
+        keep: KeepRules::default(),
+        orphaned_local_only: false,
+        stale_remote_only: false,
+        min_depth: None,
+        max_depth: None,
+        commit_window: CommitWindow::default(),
+        min_commits: None,
+        max_commits: None,
     };
 
-    let branches = load_filtered_branches(&filter, &default_branch)?;
+    let branches = load_filtered_branches(&filter, &default_branch, &remotes)?;
 
-    let repo_stats = stats::compute_stats(&branches, min_age);
+    let repo_stats = stats::compute_stats_per_scope(&branches, min_age, remote_min_age_days);
     ui::display_repo_stats(&repo_stats);
 
     Ok(())
 }
 
+/// Show, for each stale branch, which release branches already contain it
+fn cmd_audit(
+    release_patterns: &[String],
+    days: Option<u32>,
+    local_only: bool,
+    remote_only: bool,
+    remotes: &RemoteArgs,
+) -> Result<()> {
+    if !git::has_commits() {
+        ui::info(NO_COMMITS_YET);
+        return Ok(());
+    }
+    let config = Config::load()?;
+    let (local_only, remote_only) = config
+        .general
+        .default_scope
+        .resolve(local_only, remote_only);
+    let (min_age, remote_min_age_days) = age_thresholds(days, &config.general);
+    let remotes = resolve_remotes(remotes)?;
+
+    let matcher = branch::ReleaseMatcher::new(release_patterns);
+    let mut remote_refs = Vec::new();
+    for remote in &remotes {
+        remote_refs.extend(git::ref_names(&format!("refs/remotes/{}/", remote))?);
+    }
+    let releases =
+        matcher.release_branches(&git::ref_names("refs/heads/")?, &remote_refs, &remotes);
+    if releases.is_empty() {
+        ui::info(&format!(
+            "No release branches match {}",
+            release_patterns.join(", ")
+        ));
+        return Ok(());
+    }
+
+    let default_branch = config
+        .branches
+        .default_branch
+        .clone()
+        .unwrap_or_else(|| git::get_default_branch().unwrap_or_else(|_| "main".to_string()));
+    report_submodule();
+    ui::info(&format!(
+        "Checking {} release {}: {}",
+        releases.len(),
+        ui::pluralize_branch(releases.len()),
+        releases.join(", ")
+    ));
+
+    let filter = BranchFilter {
+        min_age_days: min_age,
+        remote_min_age_days,
+        local_only,
+        remote_only,
+        merged_only: false,
+        protected_branches: config.branches.protected.clone(),
+        exclude_patterns: config.exclude_patterns_for_repo(),
+        keep: KeepRules::default(),
+        orphaned_local_only: false,
+        stale_remote_only: false,
+        min_depth: None,
+        max_depth: None,
+        commit_window: CommitWindow::default(),
+        min_commits: None,
+        max_commits: None,
+    };
+    report_age_rules(days, &filter.describe_age(), None);
+    let mut branches = load_filtered_branches(&filter, &default_branch, &remotes)?;
+    // Release branches are what's being checked against, never candidates
+    branches.retain(|b| !matcher.matches(b.short_name()));
+    if branches.is_empty() {
+        ui::info(&format!(
+            "No stale branches found ({}).",
+            filter.describe_age()
+        ));
+        return Ok(());
+    }
+    branch::sort_branches(&mut branches);
+
+    let rows = branches
+        .into_iter()
+        .map(|b| {
+            let containing: Vec<String> =
+                git::get_containing_branches(&b.last_commit_sha, &remotes)?
+                    .into_iter()
+                    .map(|(name, _)| name)
+                    .collect();
+            let contained = releases.iter().map(|r| containing.contains(r)).collect();
+            Ok((b, contained))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    ui::display_release_audit(&rows, &releases);
+    Ok(())
+}
+
+/// Parse a `YYYY-MM-DD` date as local midnight
+fn parse_since_date(date: &str) -> Option<chrono::DateTime<Utc>> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)?
+        .and_local_timezone(Local)
+        .earliest()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Parse a `backup list` time bound: a `YYYY-MM-DD` date, or a duration
+/// (e.g. `1w`) counted back from now
+fn parse_backup_time(value: &str, flag: &str) -> Result<chrono::DateTime<Utc>> {
+    if let Some(dt) = parse_since_date(value) {
+        return Ok(dt);
+    }
+    match duration::parse_days(value) {
+        Ok(days) => Ok(Utc::now() - chrono::Duration::days(days as i64)),
+        Err(_) => anyhow::bail!(
+            "Invalid {} date (expected YYYY-MM-DD or a duration like 1w)",
+            flag
+        ),
+    }
+}
+
 /// Handle backup subcommands
 fn cmd_backup(action: BackupAction) -> Result<()> {
+    // Restores must keep working with a broken config; fall back to the default limit
+    if let Ok(config) = Config::load() {
+        backup::set_max_file_size_mb(config.backups.max_file_size_mb());
+    }
     match action {
-        BackupAction::List { current, repo } => {
+        BackupAction::List {
+            current,
+            repo,
+            since,
+            before,
+            sort,
+            limit,
+            contains,
+            label,
+            format,
+        } => {
             // Determine which repo to show (if any specific one)
             let target_repo = if current {
                 // Check if we're in a git repo for --current
                 if !git::is_git_repository() {
-                    ui::error("Not a git repository (or any parent up to mount point)");
-                    ui::info("Use 'deadbranch backup list' without --current to see all backups.");
-                    std::process::exit(1);
+                    return Err(DeadbranchError::not_a_git_repository().into());
                 }
                 Some(Config::get_repo_name())
             } else {
                 repo
             };
 
+            let filter = backup::BackupListFilter {
+                since: since
+                    .map(|s| parse_backup_time(&s, "--since"))
+                    .transpose()?,
+                before: before
+                    .map(|s| parse_backup_time(&s, "--before"))
+                    .transpose()?,
+                contains,
+                label,
+                limit,
+                sort,
+            };
+
             if let Some(repo_name) = target_repo {
                 // Show detailed view for specific repo
-                let backups = backup::list_repo_backups(&repo_name)?;
-
-                if backups.is_empty() {
-                    ui::info(&format!("No backups found for repository '{}'", repo_name));
-                    println!();
-                    println!(
-                        "  {} Backups are created automatically when running 'deadbranch clean'.",
-                        console::style("↪").dim()
-                    );
+                let all = backup::list_repo_backups(&repo_name)?;
+                let had_backups = !all.is_empty();
+                let backups = filter.apply(all);
+
+                if format == BackupFormat::Json {
+                    ui::print_json(&backups)?;
+                } else if backups.is_empty() {
+                    if had_backups && filter.is_active() {
+                        ui::info(&format!(
+                            "No backups for repository '{}' match the given filters",
+                            repo_name
+                        ));
+                    } else {
+                        ui::info(&format!("No backups found for repository '{}'", repo_name));
+                        outln!();
+                        outln!(
+                            "  {} Backups are created automatically when running 'deadbranch clean'.",
+                            console::style(ui::FOLLOW_UP).dim()
+                        );
+                    }
                 } else {
                     ui::display_repo_backups(&repo_name, &backups);
                 }
             } else {
                 // Show summary of all repos
                 let all_backups = backup::list_all_backups()?;
-
-                if all_backups.is_empty() {
-                    ui::info("No backups found.");
-                    println!();
-                    println!(
-                        "  {} Backups are created automatically when running 'deadbranch clean'.",
-                        console::style("↪").dim()
-                    );
+                let had_backups = !all_backups.is_empty();
+                let all_backups: std::collections::HashMap<_, _> = all_backups
+                    .into_iter()
+                    .map(|(repo_name, backups)| (repo_name, filter.apply(backups)))
+                    .filter(|(_, backups)| !backups.is_empty())
+                    .collect();
+
+                if format == BackupFormat::Json {
+                    let mut repos: Vec<_> = all_backups.into_iter().collect();
+                    repos.sort_by(|a, b| a.0.cmp(&b.0));
+                    let backups: Vec<_> = repos.into_iter().flat_map(|(_, b)| b).collect();
+                    ui::print_json(&backups)?;
+                } else if all_backups.is_empty() {
+                    if had_backups && filter.is_active() {
+                        ui::info("No backups match the given filters.");
+                    } else {
+                        ui::info("No backups found.");
+                        outln!();
+                        outln!(
+                            "  {} Backups are created automatically when running 'deadbranch clean'.",
+                            console::style(ui::FOLLOW_UP).dim()
+                        );
+                    }
                 } else {
                     ui::display_all_backups(&all_backups);
                 }
             }
         }
 
-        BackupAction::Stats => {
+        BackupAction::Stats { format } => {
             let stats = backup::get_backup_stats()?;
-            ui::display_backup_stats(&stats);
+            match format {
+                BackupFormat::Table => ui::display_backup_stats(&stats),
+                BackupFormat::Json => ui::print_json(&stats)?,
+            }
+        }
+
+        BackupAction::Verify { current, repo } => {
+            let target_repo = if current {
+                if !git::is_git_repository() {
+                    return Err(DeadbranchError::not_a_git_repository().into());
+                }
+                Some(Config::get_repo_name())
+            } else {
+                repo
+            };
+
+            let backups: Vec<backup::BackupInfo> = match target_repo {
+                Some(repo_name) => backup::list_repo_backups(&repo_name)?,
+                None => backup::list_all_backups()?
+                    .into_values()
+                    .flatten()
+                    .collect(),
+            };
+
+            let results: Vec<_> = backups.into_iter().map(backup::verify_backup).collect();
+            ui::display_verify_results(&results);
+
+            if results.iter().any(|r| r.error.is_some()) {
+                return Err(ExitStatus(1).into());
+            }
+        }
+
+        BackupAction::Migrate { current, repo } => {
+            readonly::ensure_writable("migrate backups")?;
+
+            let target_repo = if current {
+                if !git::is_git_repository() {
+                    return Err(DeadbranchError::not_a_git_repository().into());
+                }
+                Some(Config::get_repo_name())
+            } else {
+                repo.clone()
+            };
+
+            // Earlier versions could file this repository's backups under another name
+            if repo.is_none() {
+                if let Some(dir) = git::get_repo_dir() {
+                    let repo_name = Config::get_repo_name();
+                    for (other, count) in backup::adopt_split_backups(&repo_name, &dir)? {
+                        ui::info(&format!(
+                            "Moved {} {} of this repository from '{}' to '{}'",
+                            count,
+                            ui::pluralize(count, "backup", "backups"),
+                            other,
+                            repo_name
+                        ));
+                    }
+                }
+            }
+
+            let backups: Vec<backup::BackupInfo> = match target_repo {
+                Some(repo_name) => backup::list_repo_backups(&repo_name)?,
+                None => backup::list_all_backups()?
+                    .into_values()
+                    .flatten()
+                    .collect(),
+            };
+
+            let mut migrated = 0;
+            let mut current_count = 0;
+            for info in &backups {
+                match backup::migrate_backup_file(&info.path) {
+                    Ok(backup::MigrateOutcome::Migrated) => migrated += 1,
+                    Ok(backup::MigrateOutcome::AlreadyCurrent) => current_count += 1,
+                    Err(e) => ui::warning(&format!("Skipped {}: {}", info.filename(), e)),
+                }
+            }
+
+            ui::display_migrate_summary(migrated, current_count);
+        }
+
+        BackupAction::Show { backup: from } => {
+            // Without an explicit file, show the most recent backup of the current repo
+            if from.is_none() && !git::is_git_repository() {
+                return Err(DeadbranchError::not_a_git_repository().into());
+            }
+
+            let repo_name = Config::get_repo_name();
+            let parsed = backup::resolve_backup_path(&repo_name, from.as_deref())
+                .and_then(|path| backup::parse_backup_file(&path).map(|parsed| (path, parsed)));
+
+            let (path, parsed) = parsed?;
+            ui::display_backup_contents(&path, &parsed);
         }
 
         BackupAction::Restore {
             branch,
+            matching,
             from,
             r#as,
             force,
+            repo,
+            ignore_repo_mismatch,
+            push,
+            yes,
         } => {
+            readonly::ensure_writable("restore branches")?;
+
             // Restore requires being in a git repository
             if !git::is_git_repository() {
-                ui::error("Not a git repository (or any parent up to mount point)");
-                std::process::exit(1);
+                return Err(DeadbranchError::not_a_git_repository().into());
             }
 
-            match backup::restore_branch(&branch, from.as_deref(), r#as.as_deref(), force) {
-                Ok(result) => {
-                    ui::display_restore_success(&result);
-                }
-                Err(e) => {
-                    ui::display_restore_error(&e, &branch);
-                    std::process::exit(1);
+            if let Some(pattern) = matching {
+                return restore_matching(
+                    &pattern,
+                    from.as_deref(),
+                    repo.as_deref(),
+                    ignore_repo_mismatch,
+                    force,
+                    push,
+                    yes,
+                );
+            }
+            // clap requires one of the two
+            let branch = branch.context("A branch name or --matching is required")?;
+
+            let restore = |ignore_mismatch: bool| {
+                backup::restore_branch(
+                    &branch,
+                    from.as_deref(),
+                    r#as.as_deref(),
+                    force,
+                    repo.as_deref(),
+                    ignore_mismatch,
+                )
+            };
+
+            let result = match restore(ignore_repo_mismatch) {
+                // Interactive sessions may confirm instead of passing --ignore-repo-mismatch
+                Err(e @ backup::RestoreError::RepoMismatch { .. })
+                    if console::Term::stdout().is_term() =>
+                {
+                    ui::warning(&e.to_string());
+                    if ui::confirm("Restore from this backup anyway?", false)? {
+                        restore(true)
+                    } else {
+                        Err(e)
+                    }
                 }
+                other => other,
+            };
+
+            let result = result?;
+            ui::display_restore_success(&result);
+            if let Some((remote, remote_branch)) = &result.deleted_from {
+                push_restored_branch(&result.restored_name, remote, remote_branch, push)?;
             }
         }
 
@@ -669,26 +3266,45 @@ fn cmd_backup(action: BackupAction) -> Result<()> {
             current,
             repo,
             keep,
+            keep_days,
             dry_run,
             yes,
+            interactive,
+            format,
         } => {
+            let dry_run = dry_run || readonly::is_enabled();
+            if format == BackupFormat::Json && !dry_run {
+                anyhow::bail!("--format json is only available with --dry-run");
+            }
+
             // Determine target repo
             let repo_name = if current {
                 if !git::is_git_repository() {
-                    ui::error("Not a git repository (or any parent up to mount point)");
-                    ui::info("Use --repo <name> to specify a repository by name.");
-                    std::process::exit(1);
+                    return Err(DeadbranchError::not_a_git_repository().into());
                 }
                 Config::get_repo_name()
             } else if let Some(name) = repo {
                 name
             } else {
-                ui::error("Either --current or --repo <name> is required");
-                std::process::exit(1);
+                anyhow::bail!("Either --current or --repo <name> is required");
             };
 
+            if let Some(keep_days) = keep_days {
+                ui::verbose(&format!(
+                    "Keeping backups newer than {}",
+                    ui::format_day_count(keep_days)
+                ));
+            }
+
             // Get backups to clean
-            let backups_to_clean = backup::get_backups_to_clean(&repo_name, keep)?;
+            let mut backups_to_clean = backup::get_backups_to_clean(&repo_name, keep, keep_days)?;
+            if format == BackupFormat::Json {
+                return ui::print_json(&backup::CleanPlan {
+                    repo: &repo_name,
+                    keep,
+                    to_delete: &backups_to_clean,
+                });
+            }
 
             // Check if there are any backups at all for this repo
             let all_backups = backup::list_repo_backups(&repo_name)?;
@@ -697,22 +3313,54 @@ fn cmd_backup(action: BackupAction) -> Result<()> {
                 return Ok(());
             }
 
-            // Display what will be deleted
-            ui::display_backups_to_clean(&repo_name, &backups_to_clean, keep, dry_run);
+            let criteria = if interactive {
+                // Offer every backup, with the keep rules' choice pre-checked
+                let candidates: Vec<_> = all_backups
+                    .into_iter()
+                    .map(backup::BackupToDelete::from_info)
+                    .collect();
+                let preselected: Vec<bool> = candidates
+                    .iter()
+                    .map(|c| backups_to_clean.iter().any(|b| b.info.path == c.info.path))
+                    .collect();
+
+                let Some(chosen) = ui::select_backups_to_clean(&candidates, &preselected)? else {
+                    ui::info("Cancelled");
+                    return Ok(());
+                };
+                if chosen.is_empty() {
+                    ui::info("No backups selected");
+                    return Ok(());
+                }
 
-            if backups_to_clean.is_empty() {
-                return Ok(());
-            }
+                backups_to_clean = chosen.into_iter().map(|i| candidates[i].clone()).collect();
+                ui::display_backup_selection(&repo_name, &backups_to_clean);
+                "selected interactively".to_string()
+            } else {
+                // Display what will be deleted
+                ui::display_backups_to_clean(
+                    &repo_name,
+                    &backups_to_clean,
+                    keep,
+                    keep_days,
+                    dry_run,
+                );
+
+                if backups_to_clean.is_empty() {
+                    return Ok(());
+                }
+                ui::cleanup_criteria(keep, keep_days)
+            };
 
             if dry_run {
                 let total_size: u64 = backups_to_clean.iter().map(|b| b.size_bytes).sum();
-                ui::display_backup_clean_dry_run(backups_to_clean.len(), total_size);
+                ui::display_backup_clean_dry_run(backups_to_clean.len(), total_size, &criteria);
                 return Ok(());
             }
 
             // Confirm deletion unless --yes was provided
             let total_size: u64 = backups_to_clean.iter().map(|b| b.size_bytes).sum();
-            if !yes && !ui::confirm_backup_clean(backups_to_clean.len(), total_size) {
+            if !yes && !ui::confirm_backup_clean(backups_to_clean.len(), total_size, &criteria)? {
                 ui::info("Cancelled");
                 return Ok(());
             }
@@ -0,0 +1,538 @@
+//! Phase and subprocess timings for `--timing`, `--verbose` and JSON reports
+//!
+//! Major phases are wrapped in a [`phase`] guard that records wall-clock time
+//! and how many git subprocesses were started while it was alive. Every git
+//! subprocess is created through [`git`], which also times each call and adds
+//! it to per-command totals, so none of this needs bookkeeping at the call
+//! sites. Recording is cheap and always on; the flags only decide what's shown.
+//! Each thread keeps its own totals, so the parallel merge checks never wait
+//! on each other to record a call; [`summary`] adds them up.
+//!
+//! Going through [`git`] also lets a run that gives up on its git work
+//! (`status` past its time budget) stop the subprocesses still running,
+//! see [`set_stoppable`] and [`stop_git`].
+
+use serde::Serialize;
+use std::ffi::OsStr;
+use std::io::{self, Read};
+use std::process::{Child, ChildStdout, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, Weak};
+use std::time::{Duration, Instant};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static SUBPROCESSES: AtomicUsize = AtomicUsize::new(0);
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static PHASES: Mutex<Vec<PhaseTiming>> = Mutex::new(Vec::new());
+static THREAD_CALLS: Mutex<Vec<Arc<Mutex<GitCalls>>>> = Mutex::new(Vec::new());
+static STOPPABLE: AtomicBool = AtomicBool::new(false);
+static STOPPED: AtomicBool = AtomicBool::new(false);
+static RUNNING: Mutex<Vec<Weak<Mutex<Child>>>> = Mutex::new(Vec::new());
+
+/// How often a [`GitChild`] is checked for having exited while waiting for it
+const WAIT_POLL: Duration = Duration::from_millis(1);
+
+thread_local! {
+    /// This thread's git calls, also listed in `THREAD_CALLS` for [`summary`];
+    /// only `summary` ever contends for the lock
+    static CALLS: Arc<Mutex<GitCalls>> = {
+        let calls = Arc::new(Mutex::new(GitCalls::default()));
+        THREAD_CALLS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(Arc::clone(&calls));
+        calls
+    };
+}
+
+/// A part of a run that `--timing` reports on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    RefListing,
+    MergeAnalysis,
+    Fetch,
+    LocalDeletion,
+    RemoteDeletion,
+    BackupWriting,
+}
+
+impl Phase {
+    /// Label shown in the timing table and JSON
+    pub fn label(self) -> &'static str {
+        match self {
+            Phase::RefListing => "ref listing",
+            Phase::MergeAnalysis => "merge analysis",
+            Phase::Fetch => "fetch",
+            Phase::LocalDeletion => "local deletion",
+            Phase::RemoteDeletion => "remote deletion",
+            Phase::BackupWriting => "backup writing",
+        }
+    }
+}
+
+/// Time spent in one phase, summed over every time it ran
+#[derive(Debug, Clone)]
+pub struct PhaseTiming {
+    pub phase: Phase,
+    pub elapsed: Duration,
+    /// Git subprocesses started during the phase
+    pub subprocesses: usize,
+}
+
+impl Serialize for PhaseTiming {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("PhaseTiming", 3)?;
+        s.serialize_field("phase", self.phase.label())?;
+        s.serialize_field("ms", &(self.elapsed.as_secs_f64() * 1000.0))?;
+        s.serialize_field("git_calls", &self.subprocesses)?;
+        s.end()
+    }
+}
+
+/// Git calls of one kind (`push`, `for-each-ref`, ...), summed
+#[derive(Debug, Clone)]
+pub struct CommandTiming {
+    pub command: String,
+    pub calls: usize,
+    /// Time spent in the calls that were waited for (see [`GitChild`])
+    pub elapsed: Duration,
+}
+
+impl Serialize for CommandTiming {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("CommandTiming", 3)?;
+        s.serialize_field("command", &self.command)?;
+        s.serialize_field("calls", &self.calls)?;
+        s.serialize_field("ms", &(self.elapsed.as_secs_f64() * 1000.0))?;
+        s.end()
+    }
+}
+
+/// The longest single git call
+#[derive(Debug, Clone)]
+pub struct SlowestCall {
+    /// Arguments after `git`
+    pub args: Vec<String>,
+    pub elapsed: Duration,
+}
+
+impl Serialize for SlowestCall {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("SlowestCall", 2)?;
+        s.serialize_field("args", &self.args)?;
+        s.serialize_field("ms", &(self.elapsed.as_secs_f64() * 1000.0))?;
+        s.end()
+    }
+}
+
+#[derive(Default)]
+struct GitCalls {
+    commands: Vec<CommandTiming>,
+    slowest: Option<SlowestCall>,
+}
+
+/// Everything recorded so far in this run
+#[derive(Debug, Clone, Serialize)]
+pub struct Summary {
+    pub total_ms: f64,
+    pub git_calls: usize,
+    pub phases: Vec<PhaseTiming>,
+    /// Slowest kind of call first
+    pub commands: Vec<CommandTiming>,
+    pub slowest: Option<SlowestCall>,
+}
+
+/// Show the timing table (from the global `--timing` flag)
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+    STARTED.get_or_init(Instant::now);
+}
+
+/// Whether `--timing` is in effect
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Run every git call from now on as a [`GitChild`], so that [`stop_git`]
+/// can reach the ones still running. Waiting on a child this way costs a
+/// thread and some polling per call, so only `status` turns it on.
+pub fn set_stoppable(enabled: bool) {
+    STOPPABLE.store(enabled, Ordering::Relaxed);
+}
+
+/// Kill the git subprocesses still running and refuse to start any more.
+/// Only reaches the calls made after [`set_stoppable`].
+pub fn stop_git() {
+    let running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+    STOPPED.store(true, Ordering::Relaxed);
+    for child in running.iter().filter_map(Weak::upgrade) {
+        let _ = child.lock().unwrap_or_else(|e| e.into_inner()).kill();
+    }
+}
+
+/// A `git` command, counted towards the subprocess totals
+pub fn git() -> GitCommand {
+    SUBPROCESSES.fetch_add(1, Ordering::Relaxed);
+    GitCommand {
+        command: Command::new("git"),
+        args: Vec::new(),
+        stdio_set: [false; 3],
+    }
+}
+
+/// A git subprocess being set up, like [`Command`] with just the methods
+/// deadbranch uses, so every call can be timed and attributed
+pub struct GitCommand {
+    command: Command,
+    args: Vec<String>,
+    /// Whether stdin, stdout and stderr were set, for running [`Self::output`]
+    /// as a [`GitChild`] with the same defaults as [`Command::output`]
+    stdio_set: [bool; 3],
+}
+
+impl GitCommand {
+    pub fn arg(&mut self, arg: impl AsRef<OsStr>) -> &mut Self {
+        self.args.push(arg.as_ref().to_string_lossy().into_owned());
+        self.command.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(&mut self, args: I) -> &mut Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        for arg in args {
+            self.arg(arg);
+        }
+        self
+    }
+
+    pub fn env(&mut self, key: impl AsRef<OsStr>, value: impl AsRef<OsStr>) -> &mut Self {
+        self.command.env(key, value);
+        self
+    }
+
+    pub fn stdin(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.stdio_set[0] = true;
+        self.command.stdin(cfg);
+        self
+    }
+
+    pub fn stdout(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.stdio_set[1] = true;
+        self.command.stdout(cfg);
+        self
+    }
+
+    pub fn stderr(&mut self, cfg: impl Into<Stdio>) -> &mut Self {
+        self.stdio_set[2] = true;
+        self.command.stderr(cfg);
+        self
+    }
+
+    /// Run the command to completion, timing it
+    pub fn output(&mut self) -> io::Result<Output> {
+        if STOPPABLE.load(Ordering::Relaxed) {
+            let [stdin, stdout, stderr] = self.stdio_set;
+            if !stdin {
+                self.command.stdin(Stdio::null());
+            }
+            if !stdout {
+                self.command.stdout(Stdio::piped());
+            }
+            if !stderr {
+                self.command.stderr(Stdio::piped());
+            }
+            return self.spawn()?.wait_with_output();
+        }
+        let start = Instant::now();
+        let output = self.command.output();
+        record_call(&self.args, true, Some(start.elapsed()));
+        output
+    }
+
+    /// Start the command. It's counted now and timed from now until it is
+    /// waited for, through the returned [`GitChild`].
+    pub fn spawn(&mut self) -> io::Result<GitChild> {
+        record_call(&self.args, true, None);
+        let mut running = RUNNING.lock().unwrap_or_else(|e| e.into_inner());
+        if STOPPED.load(Ordering::Relaxed) {
+            return Err(io::Error::other("git calls were stopped"));
+        }
+        let child = Arc::new(Mutex::new(self.command.spawn()?));
+        running.retain(|child| child.strong_count() > 0);
+        running.push(Arc::downgrade(&child));
+        Ok(GitChild {
+            child,
+            args: self.args.clone(),
+            start: Instant::now(),
+            timed: false,
+        })
+    }
+}
+
+/// A running git subprocess, like [`Child`] with just the methods deadbranch
+/// uses. Its time is recorded once it has been seen to exit.
+///
+/// [`stop_git`] may kill it at any time, so waiting never holds on to the
+/// child: the pipes are read to the end, then it is polled until it exits.
+pub struct GitChild {
+    child: Arc<Mutex<Child>>,
+    args: Vec<String>,
+    start: Instant,
+    timed: bool,
+}
+
+impl GitChild {
+    fn lock(&self) -> MutexGuard<'_, Child> {
+        self.child.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    pub fn take_stdout(&mut self) -> Option<ChildStdout> {
+        self.lock().stdout.take()
+    }
+
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.lock().kill()
+    }
+
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let status = self.lock().try_wait();
+        if let Ok(Some(_)) = status {
+            self.record_exit();
+        }
+        status
+    }
+
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            std::thread::sleep(WAIT_POLL);
+        }
+    }
+
+    pub fn wait_with_output(mut self) -> io::Result<Output> {
+        let (stdout, stderr) = {
+            let mut child = self.lock();
+            (child.stdout.take(), child.stderr.take())
+        };
+        // Both pipes at once, so a child filling one isn't stuck on the other
+        let (stdout, stderr) = std::thread::scope(|scope| {
+            let stderr = scope.spawn(|| read_to_end(stderr));
+            (read_to_end(stdout), stderr.join())
+        });
+        let stderr = stderr.unwrap_or_else(|_| Err(io::Error::other("failed to read stderr")));
+        let status = self.wait()?;
+        Ok(Output {
+            status,
+            stdout: stdout?,
+            stderr: stderr?,
+        })
+    }
+
+    fn record_exit(&mut self) {
+        if !self.timed {
+            self.timed = true;
+            record_call(&self.args, false, Some(self.start.elapsed()));
+        }
+    }
+}
+
+/// Everything left in `pipe` (nothing if it wasn't piped)
+fn read_to_end(pipe: Option<impl Read>) -> io::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    if let Some(mut pipe) = pipe {
+        pipe.read_to_end(&mut buf)?;
+    }
+    Ok(buf)
+}
+
+/// The git subcommand of a call, skipping `-C <path>`, `-c <key=value>` and
+/// other global options
+fn command_name(args: &[String]) -> &str {
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-C" | "-c" => {
+                args.next();
+            }
+            option if option.starts_with('-') => {}
+            command => return command,
+        }
+    }
+    "(none)"
+}
+
+/// Add a call to this thread's totals (`started`) and/or the time it took,
+/// which for a spawned call is only known once it is waited for
+fn record_call(args: &[String], started: bool, elapsed: Option<Duration>) {
+    CALLS.with(|calls| {
+        let mut calls = calls.lock().unwrap_or_else(|e| e.into_inner());
+        calls.add(&CommandTiming {
+            command: command_name(args).to_string(),
+            calls: usize::from(started),
+            elapsed: elapsed.unwrap_or_default(),
+        });
+        if let Some(elapsed) = elapsed {
+            calls.add_slowest(SlowestCall {
+                args: args.to_vec(),
+                elapsed,
+            });
+        }
+    });
+}
+
+impl GitCalls {
+    fn add(&mut self, call: &CommandTiming) {
+        match self.commands.iter_mut().find(|t| t.command == call.command) {
+            Some(timing) => {
+                timing.calls += call.calls;
+                timing.elapsed += call.elapsed;
+            }
+            None => self.commands.push(call.clone()),
+        }
+    }
+
+    fn add_slowest(&mut self, call: SlowestCall) {
+        if self
+            .slowest
+            .as_ref()
+            .is_none_or(|s| call.elapsed > s.elapsed)
+        {
+            self.slowest = Some(call);
+        }
+    }
+}
+
+/// Git subprocesses started so far in this run
+pub fn subprocess_count() -> usize {
+    SUBPROCESSES.load(Ordering::Relaxed)
+}
+
+/// Records a phase when dropped
+pub struct PhaseGuard {
+    phase: Phase,
+    start: Instant,
+    subprocesses: usize,
+}
+
+/// Time `phase` until the returned guard is dropped. Phases should not nest,
+/// or the inner one's subprocesses are counted twice.
+pub fn phase(phase: Phase) -> PhaseGuard {
+    PhaseGuard {
+        phase,
+        start: Instant::now(),
+        subprocesses: subprocess_count(),
+    }
+}
+
+impl Drop for PhaseGuard {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let subprocesses = subprocess_count() - self.subprocesses;
+        let mut phases = PHASES.lock().unwrap_or_else(|e| e.into_inner());
+        match phases.iter_mut().find(|t| t.phase == self.phase) {
+            Some(timing) => {
+                timing.elapsed += elapsed;
+                timing.subprocesses += subprocesses;
+            }
+            None => phases.push(PhaseTiming {
+                phase: self.phase,
+                elapsed,
+                subprocesses,
+            }),
+        }
+    }
+}
+
+/// The phases recorded so far, in the order they first ran
+pub fn phases() -> Vec<PhaseTiming> {
+    PHASES.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Wall-clock time since the run started
+pub fn total_elapsed() -> Duration {
+    STARTED.get().map_or(Duration::ZERO, Instant::elapsed)
+}
+
+/// Phases, per-command git totals and the slowest git call so far
+pub fn summary() -> Summary {
+    let mut total = GitCalls::default();
+    for calls in THREAD_CALLS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+    {
+        let calls = calls.lock().unwrap_or_else(|e| e.into_inner());
+        for call in &calls.commands {
+            total.add(call);
+        }
+        if let Some(slowest) = &calls.slowest {
+            total.add_slowest(slowest.clone());
+        }
+    }
+    total
+        .commands
+        .sort_by(|a, b| b.elapsed.cmp(&a.elapsed).then(b.calls.cmp(&a.calls)));
+    Summary {
+        total_ms: total_elapsed().as_secs_f64() * 1000.0,
+        git_calls: subprocess_count(),
+        phases: phases(),
+        commands: total.commands,
+        slowest: total.slowest,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn command_name_skips_global_options() {
+        assert_eq!(
+            command_name(&args(&["push", "origin", "--delete", "x"])),
+            "push"
+        );
+        assert_eq!(
+            command_name(&args(&["-C", "/repo", "rev-parse", "--git-dir"])),
+            "rev-parse"
+        );
+        assert_eq!(
+            command_name(&args(&["-c", "core.quotepath=off", "--no-pager", "log"])),
+            "log"
+        );
+        assert_eq!(command_name(&args(&["--version"])), "(none)");
+    }
+
+    #[test]
+    fn spawned_calls_on_other_threads_are_timed() {
+        std::thread::spawn(|| {
+            let child = git()
+                .args(["--version"])
+                .stdout(Stdio::piped())
+                .spawn()
+                .unwrap();
+            child.wait_with_output().unwrap();
+        })
+        .join()
+        .unwrap();
+
+        let version = summary()
+            .commands
+            .into_iter()
+            .find(|c| c.command == "(none)")
+            .unwrap();
+        assert!(version.calls >= 1);
+        assert!(version.elapsed > Duration::ZERO);
+    }
+}
@@ -0,0 +1,297 @@
+//! Deletion policy for `clean`, from `branches.delete_when`
+//!
+//! A small expression language deciding which branches in the age window are
+//! deletable without `--force`:
+//!
+//! ```text
+//! policy := all ( "||" all )*
+//! all    := term ( "&&" term )*
+//! term   := "merged" | "gone" | "age" ">" DURATION | "(" policy ")"
+//! ```
+//!
+//! `merged` is merged into the default branch (squash and rebase merges
+//! included), `gone` is a local branch whose upstream no longer exists, and
+//! `age>N` is a last commit more than N days old (`60`, `8w` and `6m` all work).
+
+use std::collections::HashSet;
+use std::fmt;
+
+use crate::branch::Branch;
+use crate::duration::parse_days;
+
+/// A parsed `branches.delete_when` expression
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Policy {
+    Merged,
+    Gone,
+    /// Last commit more than this many days ago
+    OlderThan(u32),
+    And(Box<Policy>, Box<Policy>),
+    Or(Box<Policy>, Box<Policy>),
+}
+
+impl Policy {
+    /// Parse an expression such as `merged || gone` or `merged && age>60`
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let tokens = tokenize(input)?;
+        if tokens.is_empty() {
+            return Err("expected an expression such as `merged || gone`".to_string());
+        }
+        let mut parser = Parser { tokens, pos: 0 };
+        let policy = parser.any()?;
+        match parser.tokens.get(parser.pos) {
+            None => Ok(policy),
+            Some(token) => Err(format!("unexpected {} after a complete expression", token)),
+        }
+    }
+
+    /// Whether the policy needs to know which local branches lost their upstream
+    pub fn uses_gone(&self) -> bool {
+        match self {
+            Policy::Gone => true,
+            Policy::Merged | Policy::OlderThan(_) => false,
+            Policy::And(a, b) | Policy::Or(a, b) => a.uses_gone() || b.uses_gone(),
+        }
+    }
+
+    /// Whether `branch` may be deleted; `gone` holds the local branches whose
+    /// upstream no longer exists
+    pub fn allows(&self, branch: &Branch, gone: &HashSet<String>) -> bool {
+        match self {
+            Policy::Merged => branch.is_merged,
+            Policy::Gone => !branch.is_remote && gone.contains(&branch.name),
+            Policy::OlderThan(days) => branch.age_days > i64::from(*days),
+            Policy::And(a, b) => a.allows(branch, gone) && b.allows(branch, gone),
+            Policy::Or(a, b) => a.allows(branch, gone) || b.allows(branch, gone),
+        }
+    }
+}
+
+impl fmt::Display for Policy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Policy::Merged => write!(f, "merged"),
+            Policy::Gone => write!(f, "gone"),
+            Policy::OlderThan(days) => write!(f, "age>{}", days),
+            Policy::And(a, b) => {
+                // `||` binds looser than `&&`, so its operands need parentheses here
+                let side = |p: &Policy| match p {
+                    Policy::Or(..) => format!("({})", p),
+                    _ => p.to_string(),
+                };
+                write!(f, "{} && {}", side(a), side(b))
+            }
+            Policy::Or(a, b) => write!(f, "{} || {}", a, b),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    Word(String),
+    Greater,
+    And,
+    Or,
+    Open,
+    Close,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Greater => write!(f, "'>'"),
+            Token::And => write!(f, "'&&'"),
+            Token::Or => write!(f, "'||'"),
+            Token::Open => write!(f, "'('"),
+            Token::Close => write!(f, "')'"),
+        }
+    }
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '>' | '(' | ')' => {
+                chars.next();
+                tokens.push(match c {
+                    '>' => Token::Greater,
+                    '(' => Token::Open,
+                    _ => Token::Close,
+                });
+            }
+            '&' | '|' => {
+                chars.next();
+                if chars.next() != Some(c) {
+                    return Err(format!("expected '{}{}', found a single '{}'", c, c, c));
+                }
+                tokens.push(if c == '&' { Token::And } else { Token::Or });
+            }
+            c if c.is_ascii_alphanumeric() => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric()) {
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+            other => return Err(format!("unexpected character '{}'", other)),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat(&mut self, token: &Token) -> bool {
+        let found = self.tokens.get(self.pos) == Some(token);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn any(&mut self) -> Result<Policy, String> {
+        let mut policy = self.all()?;
+        while self.eat(&Token::Or) {
+            policy = Policy::Or(Box::new(policy), Box::new(self.all()?));
+        }
+        Ok(policy)
+    }
+
+    fn all(&mut self) -> Result<Policy, String> {
+        let mut policy = self.term()?;
+        while self.eat(&Token::And) {
+            policy = Policy::And(Box::new(policy), Box::new(self.term()?));
+        }
+        Ok(policy)
+    }
+
+    fn term(&mut self) -> Result<Policy, String> {
+        match self.next() {
+            Some(Token::Word(word)) => match word.as_str() {
+                "merged" => Ok(Policy::Merged),
+                "gone" => Ok(Policy::Gone),
+                "age" => {
+                    if !self.eat(&Token::Greater) {
+                        return Err("expected '>' after 'age' (e.g. age>60)".to_string());
+                    }
+                    match self.next() {
+                        Some(Token::Word(days)) => parse_days(&days)
+                            .map(Policy::OlderThan)
+                            .map_err(|e| format!("invalid age: {}", e)),
+                        _ => Err("expected a number of days after 'age>'".to_string()),
+                    }
+                }
+                _ => Err(format!(
+                    "unknown condition '{}' (expected merged, gone or age>N)",
+                    word
+                )),
+            },
+            Some(Token::Open) => {
+                let policy = self.any()?;
+                if !self.eat(&Token::Close) {
+                    return Err("missing ')'".to_string());
+                }
+                Ok(policy)
+            }
+            Some(token) => Err(format!("expected a condition, found {}", token)),
+            None => Err("expression ends where a condition was expected".to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn branch(name: &str, age_days: i64, is_merged: bool) -> Branch {
+        Branch {
+            name: name.to_string(),
+            age_days,
+            is_merged,
+            merged_by_tree: false,
+            is_remote: false,
+            remote: None,
+            last_commit_sha: "abc1234".to_string(),
+            last_commit_date: Utc::now(),
+            last_commit_author: "Test".to_string(),
+            last_commit_subject: None,
+            last_commit_committer: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_precedence_and_grouping() {
+        let policy = Policy::parse("merged || gone && age>60").unwrap();
+        assert_eq!(
+            policy,
+            Policy::Or(
+                Box::new(Policy::Merged),
+                Box::new(Policy::And(
+                    Box::new(Policy::Gone),
+                    Box::new(Policy::OlderThan(60))
+                ))
+            )
+        );
+        assert_eq!(policy.to_string(), "merged || gone && age>60");
+
+        let grouped = Policy::parse("(merged||gone)&&age > 2m").unwrap();
+        assert_eq!(grouped.to_string(), "(merged || gone) && age>60");
+        assert_eq!(Policy::parse(&grouped.to_string()).unwrap(), grouped);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        for (input, message) in [
+            ("", "expected an expression"),
+            ("merged &", "single '&'"),
+            ("merged ||", "ends where a condition"),
+            ("stale", "unknown condition 'stale'"),
+            ("age 60", "expected '>'"),
+            ("age>soon", "invalid age"),
+            ("(merged || gone", "missing ')'"),
+            ("merged gone", "unexpected 'gone'"),
+            ("merged && !gone", "unexpected character '!'"),
+        ] {
+            let err = Policy::parse(input).unwrap_err();
+            assert!(err.contains(message), "{:?}: {}", input, err);
+        }
+    }
+
+    #[test]
+    fn test_allows() {
+        let gone: HashSet<String> = ["orphan".to_string()].into();
+        let policy = Policy::parse("merged || gone").unwrap();
+        assert!(policy.uses_gone());
+        assert!(policy.allows(&branch("done", 10, true), &gone));
+        assert!(policy.allows(&branch("orphan", 10, false), &gone));
+        assert!(!policy.allows(&branch("wip", 10, false), &gone));
+
+        let mut remote = branch("orphan", 10, false);
+        remote.is_remote = true;
+        assert!(!policy.allows(&remote, &gone));
+
+        let policy = Policy::parse("merged && age>60").unwrap();
+        assert!(!policy.uses_gone());
+        assert!(!policy.allows(&branch("done", 60, true), &gone));
+        assert!(policy.allows(&branch("done", 61, true), &gone));
+        assert!(!policy.allows(&branch("old", 90, false), &gone));
+    }
+}
@@ -0,0 +1,113 @@
+//! Clean presets - named bundles of `clean` flags for common workflows
+
+use crate::cli::{CommitCountArgs, DepthArgs, Preset};
+
+/// Scope and merge flags for `clean`, after expanding any `--preset`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CleanFlags {
+    /// Only delete merged branches
+    pub merged: bool,
+    /// Include unmerged branches and force-delete them
+    pub force: bool,
+    /// Only delete local branches
+    pub local_only: bool,
+    /// Only delete remote branches
+    pub remote_only: bool,
+    /// Limits on the number of name segments (`--min-depth`, `--max-depth`)
+    pub depth: DepthArgs,
+    /// Limits on the number of unique commits (`--min-commits`, `--max-commits`)
+    pub commits: CommitCountArgs,
+}
+
+impl CleanFlags {
+    /// Fill in the preset's defaults. Explicitly passed flags win: a preset
+    /// never overrides a scope or merge flag the user already chose.
+    ///
+    /// - `safe`: merged-only, local branches only
+    /// - `aggressive`: unmerged included (force), scope from the flags or
+    ///   `general.default_scope` (local and remote by default)
+    ///
+    /// Presets never skip confirmation and backups are always written.
+    pub fn with_preset(self, preset: Option<Preset>) -> Self {
+        let mut flags = self;
+        let scope_chosen = flags.local_only || flags.remote_only;
+        let merge_chosen = flags.merged || flags.force;
+
+        match preset {
+            Some(Preset::Safe) => {
+                if !merge_chosen {
+                    flags.merged = true;
+                }
+                if !scope_chosen {
+                    flags.local_only = true;
+                }
+            }
+            // Scope comes from the flags or `general.default_scope`
+            Some(Preset::Aggressive) if !merge_chosen => flags.force = true,
+            Some(Preset::Aggressive) | None => {}
+        }
+
+        flags
+    }
+
+    /// Whether only merged branches are candidates (the default unless --force)
+    pub fn merged_only(&self) -> bool {
+        self.merged || !self.force
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_preset_keeps_flags() {
+        let flags = CleanFlags::default().with_preset(None);
+        assert_eq!(flags, CleanFlags::default());
+        assert!(flags.merged_only());
+    }
+
+    #[test]
+    fn test_safe_preset() {
+        let flags = CleanFlags::default().with_preset(Some(Preset::Safe));
+        assert!(flags.merged_only());
+        assert!(!flags.force);
+        assert!(flags.local_only);
+        assert!(!flags.remote_only);
+    }
+
+    #[test]
+    fn test_aggressive_preset() {
+        let flags = CleanFlags::default().with_preset(Some(Preset::Aggressive));
+        assert!(!flags.merged_only());
+        assert!(flags.force);
+        assert!(!flags.local_only);
+        assert!(!flags.remote_only);
+    }
+
+    #[test]
+    fn test_explicit_flags_override_safe_preset() {
+        let flags = CleanFlags {
+            remote_only: true,
+            force: true,
+            ..Default::default()
+        }
+        .with_preset(Some(Preset::Safe));
+        assert!(!flags.local_only);
+        assert!(flags.remote_only);
+        assert!(!flags.merged_only());
+    }
+
+    #[test]
+    fn test_explicit_flags_override_aggressive_preset() {
+        let flags = CleanFlags {
+            merged: true,
+            local_only: true,
+            ..Default::default()
+        }
+        .with_preset(Some(Preset::Aggressive));
+        assert!(flags.merged_only());
+        assert!(!flags.force);
+        assert!(flags.local_only);
+    }
+}
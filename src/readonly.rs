@@ -0,0 +1,28 @@
+//! Global read-only mode (`--check`)
+//!
+//! Every helper that changes a repository, a backup or the config calls
+//! [`ensure_writable`] first, so a `--check` run cannot mutate anything even
+//! if a command forgets to map the flag to its own dry-run behavior.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Turn read-only mode on or off (from the global `--check` flag)
+pub fn set_enabled(enabled: bool) {
+    READ_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether `--check` is in effect
+pub fn is_enabled() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Fail if read-only mode is on; `action` describes the refused change
+/// (e.g. "delete branch 'feature/x'")
+pub fn ensure_writable(action: &str) -> anyhow::Result<()> {
+    if is_enabled() {
+        anyhow::bail!("Refusing to {}: --check is read-only", action);
+    }
+    Ok(())
+}
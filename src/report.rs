@@ -0,0 +1,386 @@
+//! Cleanup reports for `clean --report <path>`
+//!
+//! A record of one run for change management: where it ran, which filters
+//! chose the branches, and what happened to each of them. The file is HTML
+//! when the path ends in `.html` or `.htm`, JSON for `.json` (which also
+//! records where the run's time went, for bug reports), plain text otherwise.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::atomic_write;
+use crate::branch::Branch;
+use crate::perf;
+
+/// What happened to one candidate branch
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Deleted,
+    /// Deletion was attempted and failed, with git's reason
+    Failed(String),
+    /// The branch was deleted by someone else before this run got to it
+    AlreadyGone,
+    /// The deletion was declined at the prompt
+    Kept,
+}
+
+impl Outcome {
+    fn label(&self) -> String {
+        match self {
+            Outcome::Deleted => "deleted".to_string(),
+            Outcome::Failed(reason) => format!("failed: {}", reason),
+            Outcome::AlreadyGone => "already gone".to_string(),
+            Outcome::Kept => "kept (declined)".to_string(),
+        }
+    }
+}
+
+/// Everything `clean --report` writes
+#[derive(Debug)]
+pub struct CleanReport {
+    pub created: DateTime<Local>,
+    pub repository: String,
+    pub default_branch: String,
+    /// `(filter, value)`, e.g. `("Age", "older than 30 days")`
+    pub filters: Vec<(&'static str, String)>,
+    pub branches: Vec<(Branch, Outcome)>,
+    /// Backup files written before deleting
+    pub backups: Vec<String>,
+    /// Timings and git calls up to writing the report (JSON only)
+    pub performance: perf::Summary,
+}
+
+/// The file formats `write` picks from by extension
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Text,
+    Html,
+    Json,
+}
+
+impl Format {
+    fn for_path(path: &Path) -> Self {
+        let ext = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match ext.as_str() {
+            "html" | "htm" => Format::Html,
+            "json" => Format::Json,
+            _ => Format::Text,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonFilter<'a> {
+    filter: &'a str,
+    value: &'a str,
+}
+
+#[derive(Serialize)]
+struct JsonBranch<'a> {
+    #[serde(flatten)]
+    branch: &'a Branch,
+    result: String,
+}
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    created: String,
+    repository: &'a str,
+    default_branch: &'a str,
+    filters: Vec<JsonFilter<'a>>,
+    branches: Vec<JsonBranch<'a>>,
+    backups: &'a [String],
+    performance: &'a perf::Summary,
+}
+
+impl CleanReport {
+    /// Write the report to `path`, as HTML, JSON or text depending on its extension
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let content = match Format::for_path(path) {
+            Format::Html => self.render_html(),
+            Format::Json => self.render_json()?,
+            Format::Text => self.render_text(),
+        };
+        atomic_write::write(path, content.as_bytes())
+            .with_context(|| format!("Failed to write report to {}", path.display()))
+    }
+
+    fn deleted_count(&self) -> usize {
+        self.branches
+            .iter()
+            .filter(|(_, outcome)| *outcome == Outcome::Deleted)
+            .count()
+    }
+
+    /// `(header, cells)` for the branch table, shared by both formats
+    fn rows(&self) -> (Vec<&'static str>, Vec<Vec<String>>) {
+        let header = vec!["Branch", "Type", "Status", "Age", "SHA", "Result"];
+        let rows = self
+            .branches
+            .iter()
+            .map(|(branch, outcome)| {
+                vec![
+                    branch.name.clone(),
+                    if branch.is_remote { "remote" } else { "local" }.to_string(),
+                    if branch.is_merged {
+                        "merged"
+                    } else {
+                        "unmerged"
+                    }
+                    .to_string(),
+                    branch.format_age(),
+                    branch.last_commit_sha.clone(),
+                    outcome.label(),
+                ]
+            })
+            .collect();
+        (header, rows)
+    }
+
+    fn render_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "deadbranch clean report");
+        let _ = writeln!(out, "Created:        {}", self.created.to_rfc3339());
+        let _ = writeln!(out, "Repository:     {}", self.repository);
+        let _ = writeln!(out, "Default branch: {}", self.default_branch);
+
+        let _ = writeln!(out, "\nFilters:");
+        let key_width = self.filters.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        for (key, value) in &self.filters {
+            let _ = writeln!(out, "  {:width$}  {}", key, value, width = key_width);
+        }
+
+        let _ = writeln!(
+            out,
+            "\nBranches: {} deleted of {}",
+            self.deleted_count(),
+            self.branches.len()
+        );
+        let (header, rows) = self.rows();
+        let mut widths: Vec<usize> = header.iter().map(|h| h.len()).collect();
+        for row in &rows {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+        let line = |cells: Vec<&str>| {
+            let padded: Vec<String> = cells
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:width$}", cell, width = width))
+                .collect();
+            format!("  {}", padded.join("  ").trim_end())
+        };
+        let _ = writeln!(out, "{}", line(header));
+        for row in &rows {
+            let _ = writeln!(out, "{}", line(row.iter().map(String::as_str).collect()));
+        }
+
+        let _ = writeln!(out, "\nBackups:");
+        if self.backups.is_empty() {
+            let _ = writeln!(out, "  (none written)");
+        }
+        for backup in &self.backups {
+            let _ = writeln!(out, "  {}", backup);
+        }
+        out
+    }
+
+    fn render_json(&self) -> Result<String> {
+        let report = JsonReport {
+            created: self.created.to_rfc3339(),
+            repository: &self.repository,
+            default_branch: &self.default_branch,
+            filters: self
+                .filters
+                .iter()
+                .map(|(filter, value)| JsonFilter { filter, value })
+                .collect(),
+            branches: self
+                .branches
+                .iter()
+                .map(|(branch, outcome)| JsonBranch {
+                    branch,
+                    result: outcome.label(),
+                })
+                .collect(),
+            backups: &self.backups,
+            performance: &self.performance,
+        };
+        let mut json = serde_json::to_string_pretty(&report)?;
+        json.push('\n');
+        Ok(json)
+    }
+
+    fn render_html(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "<!DOCTYPE html>");
+        let _ = writeln!(out, "<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">");
+        let _ = writeln!(
+            out,
+            "<title>deadbranch clean report: {}</title>",
+            escape_html(&self.repository)
+        );
+        let _ = writeln!(
+            out,
+            "<style>body{{font-family:sans-serif}}table{{border-collapse:collapse}}\
+             td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}</style>"
+        );
+        let _ = writeln!(out, "</head>\n<body>\n<h1>deadbranch clean report</h1>");
+
+        let mut facts = vec![
+            ("Created", self.created.to_rfc3339()),
+            ("Repository", self.repository.clone()),
+            ("Default branch", self.default_branch.clone()),
+        ];
+        facts.extend(self.filters.iter().cloned());
+        let _ = writeln!(out, "<table>");
+        for (key, value) in &facts {
+            let _ = writeln!(
+                out,
+                "<tr><th>{}</th><td>{}</td></tr>",
+                escape_html(key),
+                escape_html(value)
+            );
+        }
+        let _ = writeln!(out, "</table>");
+
+        let _ = writeln!(
+            out,
+            "<h2>Branches: {} deleted of {}</h2>\n<table>",
+            self.deleted_count(),
+            self.branches.len()
+        );
+        let (header, rows) = self.rows();
+        let cells = |tag: &str, cells: &[&str]| {
+            let cells: Vec<String> = cells
+                .iter()
+                .map(|cell| format!("<{tag}>{}</{tag}>", escape_html(cell)))
+                .collect();
+            format!("<tr>{}</tr>", cells.concat())
+        };
+        let _ = writeln!(out, "{}", cells("th", &header));
+        for row in &rows {
+            let row: Vec<&str> = row.iter().map(String::as_str).collect();
+            let _ = writeln!(out, "{}", cells("td", &row));
+        }
+        let _ = writeln!(out, "</table>");
+
+        let _ = writeln!(out, "<h2>Backups</h2>\n<ul>");
+        if self.backups.is_empty() {
+            let _ = writeln!(out, "<li>(none written)</li>");
+        }
+        for backup in &self.backups {
+            let _ = writeln!(out, "<li>{}</li>", escape_html(backup));
+        }
+        let _ = writeln!(out, "</ul>\n</body>\n</html>");
+        out
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn branch(name: &str, is_remote: bool) -> Branch {
+        Branch {
+            name: name.to_string(),
+            age_days: 45,
+            is_merged: true,
+            merged_by_tree: false,
+            is_remote,
+            remote: name
+                .split_once('/')
+                .filter(|_| is_remote)
+                .map(|(remote, _)| remote.to_string()),
+            last_commit_sha: "abc1234".to_string(),
+            last_commit_date: Utc::now(),
+            last_commit_author: "Test".to_string(),
+            last_commit_subject: None,
+            last_commit_committer: None,
+        }
+    }
+
+    fn report() -> CleanReport {
+        CleanReport {
+            created: Local::now(),
+            repository: "app".to_string(),
+            default_branch: "main".to_string(),
+            filters: vec![("Age", "older than 30 days".to_string())],
+            branches: vec![
+                (branch("feature/<old>", false), Outcome::Deleted),
+                (
+                    branch("origin/busy", true),
+                    Outcome::Failed("rejected".to_string()),
+                ),
+            ],
+            backups: vec!["/home/me/.deadbranch/backups/app/backup-1.txt".to_string()],
+            performance: perf::summary(),
+        }
+    }
+
+    #[test]
+    fn test_format_for_path() {
+        assert_eq!(Format::for_path(Path::new("r.HTML")), Format::Html);
+        assert_eq!(Format::for_path(Path::new("r.htm")), Format::Html);
+        assert_eq!(Format::for_path(Path::new("r.json")), Format::Json);
+        assert_eq!(Format::for_path(Path::new("r.txt")), Format::Text);
+        assert_eq!(Format::for_path(Path::new("report")), Format::Text);
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&report().render_json().unwrap()).unwrap();
+        assert_eq!(json["default_branch"], "main");
+        assert_eq!(json["filters"][0]["filter"], "Age");
+        assert_eq!(json["branches"][0]["name"], "feature/<old>");
+        assert_eq!(json["branches"][0]["age_days"], 45);
+        assert_eq!(json["branches"][1]["result"], "failed: rejected");
+        assert!(json["performance"]["git_calls"].is_u64());
+        assert!(json["performance"]["commands"].is_array());
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = report().render_text();
+        assert!(text.contains("Default branch: main"));
+        assert!(text.contains("Age  older than 30 days"));
+        assert!(text.contains("Branches: 1 deleted of 2"));
+        assert!(text.contains("feature/<old>  local   merged  45 days  abc1234  deleted"));
+        assert!(text.contains("failed: rejected"));
+        assert!(text.contains("backup-1.txt"));
+    }
+
+    #[test]
+    fn test_render_html_escapes() {
+        let html = report().render_html();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<td>feature/&lt;old&gt;</td>"));
+        assert!(!html.contains("<old>"));
+        assert!(html.contains("<th>Age</th><td>older than 30 days</td>"));
+    }
+}
@@ -0,0 +1,114 @@
+//! Snippets for running `deadbranch prune --yes` on a schedule (`deadbranch schedule`)
+
+use std::path::Path;
+
+use crate::shell::shell_quote;
+
+/// Command the scheduled job runs in the repository
+pub const SCHEDULED_ARGS: &str = "prune --yes";
+
+/// Name for the systemd units, from the repository name (`app/vendor` -> `deadbranch-app-vendor`)
+pub fn unit_name(repo_name: &str) -> String {
+    let name: String = repo_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("deadbranch-{}", name.trim_matches('-'))
+}
+
+/// A crontab line running the cleanup every Monday at 09:00. Cron turns an
+/// unescaped `%` into a newline, even inside quotes, so each one is written as `\%`.
+pub fn cron_entry(repo: &Path, exe: &Path) -> String {
+    let command = format!(
+        "cd {} && {} {}",
+        shell_quote(&repo.display().to_string()),
+        shell_quote(&exe.display().to_string()),
+        SCHEDULED_ARGS
+    );
+    format!("0 9 * * 1 {}", command.replace('%', "\\%"))
+}
+
+/// Escape `%` as `%%`, since systemd expands `%` specifiers in unit file values
+fn systemd_escape(value: &str) -> String {
+    value.replace('%', "%%")
+}
+
+/// A systemd user service and a weekly timer for it, as `(service, timer)` file contents
+pub fn systemd_units(repo: &Path, exe: &Path, repo_name: &str) -> (String, String) {
+    let name = systemd_escape(repo_name);
+    let service = format!(
+        "[Unit]\n\
+         Description=Clean up stale branches in {name}\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         WorkingDirectory={repo}\n\
+         ExecStart=\"{exe}\" {args}\n",
+        name = name,
+        repo = systemd_escape(&repo.display().to_string()),
+        exe = systemd_escape(&exe.display().to_string()),
+        args = SCHEDULED_ARGS
+    );
+    let timer = format!(
+        "[Unit]\n\
+         Description=Weekly stale branch cleanup in {name}\n\
+         \n\
+         [Timer]\n\
+         OnCalendar=weekly\n\
+         Persistent=true\n\
+         \n\
+         [Install]\n\
+         WantedBy=timers.target\n",
+        name = name
+    );
+    (service, timer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cron_entry_quotes_paths() {
+        assert_eq!(
+            cron_entry(
+                Path::new("/src/it's here"),
+                Path::new("/usr/bin/deadbranch")
+            ),
+            r"0 9 * * 1 cd '/src/it'\''s here' && '/usr/bin/deadbranch' prune --yes"
+        );
+    }
+
+    #[test]
+    fn test_cron_entry_escapes_percent_signs() {
+        assert_eq!(
+            cron_entry(Path::new("/src/100%"), Path::new("/usr/bin/deadbranch")),
+            r"0 9 * * 1 cd '/src/100\%' && '/usr/bin/deadbranch' prune --yes"
+        );
+    }
+
+    #[test]
+    fn test_unit_name_is_safe_for_systemd() {
+        assert_eq!(unit_name("app/vendor"), "deadbranch-app-vendor");
+        assert_eq!(unit_name("my repo!"), "deadbranch-my-repo");
+    }
+
+    #[test]
+    fn test_systemd_units_escape_percent_signs() {
+        let (service, timer) = systemd_units(
+            Path::new("/src/100%"),
+            Path::new("/opt/50%/deadbranch"),
+            "100%",
+        );
+        assert!(service.contains("Description=Clean up stale branches in 100%%\n"));
+        assert!(service.contains("WorkingDirectory=/src/100%%\n"));
+        assert!(service.contains("ExecStart=\"/opt/50%%/deadbranch\" prune --yes\n"));
+        assert!(timer.contains("Description=Weekly stale branch cleanup in 100%%\n"));
+    }
+}
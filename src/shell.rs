@@ -0,0 +1,17 @@
+//! Quoting for the shell commands deadbranch prints or writes out
+
+/// Quote `text` for a POSIX shell, e.g. `it's` -> `'it'\''s'`
+pub fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("feature/x"), "'feature/x'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
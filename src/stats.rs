@@ -1,5 +1,7 @@
 //! Branch statistics computation
 
+use serde::Serialize;
+
 use crate::branch::Branch;
 
 /// Aggregated branch statistics for the current repository.
@@ -34,14 +36,37 @@ pub struct RepoStats {
     /// Branches with age_days >= 90
     pub age_gt90: usize,
     pub threshold_days: u32,
+    /// Staleness boundary for remote branches, when it differs from
+    /// `threshold_days` (`general.default_days_remote`)
+    pub remote_threshold_days: Option<u32>,
+}
+
+impl RepoStats {
+    /// The staleness boundary for local or remote branches
+    pub fn threshold_for(&self, remote: bool) -> u32 {
+        match self.remote_threshold_days {
+            Some(days) if remote => days,
+            _ => self.threshold_days,
+        }
+    }
 }
 
 /// Compute statistics from a pre-filtered branch list.
 /// `branches` should already have protected/excluded branches removed.
 /// `threshold_days` defines the staleness boundary.
 pub fn compute_stats(branches: &[Branch], threshold_days: u32) -> RepoStats {
+    compute_stats_per_scope(branches, threshold_days, None)
+}
+
+/// [`compute_stats`] with a separate staleness boundary for remote branches
+pub fn compute_stats_per_scope(
+    branches: &[Branch],
+    threshold_days: u32,
+    remote_threshold_days: Option<u32>,
+) -> RepoStats {
     let mut s = RepoStats {
         threshold_days,
+        remote_threshold_days,
         ..RepoStats::default()
     };
 
@@ -71,7 +96,7 @@ pub fn compute_stats(branches: &[Branch], threshold_days: u32) -> RepoStats {
         }
 
         // Negative age_days (clock-skewed commits) is treated as not-stale, which is correct.
-        let is_stale = branch.age_days >= threshold_days as i64;
+        let is_stale = branch.age_days >= s.threshold_for(branch.is_remote) as i64;
         if is_stale {
             s.stale += 1;
             if branch.is_remote {
@@ -101,6 +126,62 @@ pub fn compute_stats(branches: &[Branch], threshold_days: u32) -> RepoStats {
     s
 }
 
+/// The counts `status` reports, for branches already narrowed down to the
+/// stale ones `list` would show
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize)]
+pub struct StaleCounts {
+    pub stale: usize,
+    pub local: usize,
+    pub remote: usize,
+    /// Merged per `git branch --merged` only; squash merges aren't looked for
+    pub merged: usize,
+}
+
+impl StaleCounts {
+    pub fn of(branches: &[Branch]) -> Self {
+        let stats = compute_stats(branches, 0);
+        StaleCounts {
+            stale: stats.total,
+            local: stats.local,
+            remote: stats.remote,
+            merged: stats.merged,
+        }
+    }
+}
+
+/// Format branch counts as Prometheus text exposition lines, for
+/// `list --format metrics`. Emits per-type totals plus a merged/unmerged
+/// breakdown of each type.
+pub fn format_metrics(stats: &RepoStats) -> String {
+    let mut out = String::new();
+    out.push_str("# HELP deadbranch_stale_branches Branches matching the list filters\n");
+    out.push_str("# TYPE deadbranch_stale_branches gauge\n");
+
+    let rows = [
+        ("local", None, stats.local),
+        ("remote", None, stats.remote),
+        ("local", Some(true), stats.merged_local),
+        ("local", Some(false), stats.unmerged_local),
+        ("remote", Some(true), stats.merged_remote),
+        ("remote", Some(false), stats.unmerged_remote),
+    ];
+
+    for (kind, merged, count) in rows {
+        match merged {
+            Some(merged) => out.push_str(&format!(
+                "deadbranch_stale_branches{{type=\"{}\",merged=\"{}\"}} {}\n",
+                kind, merged, count
+            )),
+            None => out.push_str(&format!(
+                "deadbranch_stale_branches{{type=\"{}\"}} {}\n",
+                kind, count
+            )),
+        }
+    }
+
+    out
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -113,12 +194,53 @@ mod tests {
             is_merged,
             merged_by_tree: false,
             is_remote,
+            remote: name
+                .split_once('/')
+                .filter(|_| is_remote)
+                .map(|(remote, _)| remote.to_string()),
             last_commit_sha: "abc123".to_string(),
             last_commit_date: Utc::now(),
             last_commit_author: "testuser".to_string(),
+            last_commit_subject: None,
+            last_commit_committer: None,
         }
     }
 
+    #[test]
+    fn test_stale_counts() {
+        let branches = vec![
+            test_branch("a", 40, true, false),
+            test_branch("b", 90, false, false),
+            test_branch("origin/c", 60, true, true),
+        ];
+        assert_eq!(
+            StaleCounts::of(&branches),
+            StaleCounts {
+                stale: 3,
+                local: 2,
+                remote: 1,
+                merged: 2,
+            }
+        );
+        assert_eq!(StaleCounts::of(&[]), StaleCounts::default());
+    }
+
+    #[test]
+    fn test_per_scope_threshold() {
+        let branches = vec![
+            test_branch("local", 45, true, false),
+            test_branch("origin/remote", 1, true, true),
+        ];
+        let stats = compute_stats_per_scope(&branches, 60, Some(0));
+        assert_eq!(stats.stale_local, 0);
+        assert_eq!(stats.stale_remote, 1);
+        assert_eq!(stats.safe_to_delete, 1);
+
+        let stats = compute_stats(&branches, 30);
+        assert_eq!(stats.stale_local, 1);
+        assert_eq!(stats.stale_remote, 0);
+    }
+
     #[test]
     fn test_empty() {
         let stats = compute_stats(&[], 30);
@@ -205,4 +327,30 @@ mod tests {
         assert_eq!(stats.age_30_90, 2);
         assert_eq!(stats.age_gt90, 2);
     }
+
+    #[test]
+    fn test_format_metrics() {
+        let branches = vec![
+            test_branch("a", 40, true, false),
+            test_branch("b", 40, false, false),
+            test_branch("c", 40, false, false),
+            test_branch("origin/d", 40, true, true),
+        ];
+        let metrics = format_metrics(&compute_stats(&branches, 30));
+        let lines: Vec<&str> = metrics.lines().collect();
+
+        assert_eq!(
+            lines,
+            vec![
+                "# HELP deadbranch_stale_branches Branches matching the list filters",
+                "# TYPE deadbranch_stale_branches gauge",
+                "deadbranch_stale_branches{type=\"local\"} 3",
+                "deadbranch_stale_branches{type=\"remote\"} 1",
+                "deadbranch_stale_branches{type=\"local\",merged=\"true\"} 1",
+                "deadbranch_stale_branches{type=\"local\",merged=\"false\"} 2",
+                "deadbranch_stale_branches{type=\"remote\",merged=\"true\"} 1",
+                "deadbranch_stale_branches{type=\"remote\",merged=\"false\"} 0",
+            ]
+        );
+    }
 }
@@ -4,7 +4,7 @@ use std::sync::mpsc;
 
 use ratatui::widgets::TableState;
 
-use crate::branch::{Branch, BranchFilter};
+use crate::branch::{Branch, BranchFilter, CommitWindow, KeepRules};
 
 /// Current mode of the TUI
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -125,6 +125,10 @@ pub struct App {
     pub deletion_results: Vec<DeletionResult>,
     /// Path to the backup file created before deletion
     pub backup_path: Option<String>,
+    /// Label written into the backup file (--label)
+    pub backup_label: Option<String>,
+    /// Parallel per-branch remote deletions (`branches.remote_delete_jobs`)
+    pub remote_delete_jobs: Option<usize>,
     /// Whether the help overlay is shown
     pub show_help: bool,
     /// Table state for the branch list (manages scroll offset)
@@ -177,6 +181,8 @@ impl App {
             confirm_input: String::new(),
             deletion_results: Vec::new(),
             backup_path: None,
+            backup_label: None,
+            remote_delete_jobs: None,
             show_help: false,
             table_state: TableState::default(),
             pending_deletions: Vec::new(),
@@ -201,11 +207,20 @@ impl App {
     pub fn update_visible(&mut self) {
         let filter = BranchFilter {
             min_age_days: 0,
+            remote_min_age_days: None,
             local_only: self.filter_local_only,
             remote_only: self.filter_remote_only,
             merged_only: self.filter_merged_only,
             protected_branches: Vec::new(),
             exclude_patterns: Vec::new(),
+            keep: KeepRules::default(),
+            orphaned_local_only: false,
+            stale_remote_only: false,
+            min_depth: None,
+            max_depth: None,
+            commit_window: CommitWindow::default(),
+            min_commits: None,
+            max_commits: None,
         };
 
         let query = &self.search_query;
@@ -231,7 +246,7 @@ impl App {
                     sublime_fuzzy::best_match(query, &b.name).map(|m| (i, m.score()))
                 })
                 .collect();
-            scored.sort_by(|a, b| b.1.cmp(&a.1));
+            scored.sort_by_key(|&(_, score)| std::cmp::Reverse(score));
             self.visible = scored.into_iter().map(|(i, _)| i).collect();
         }
 
@@ -588,9 +603,15 @@ mod tests {
             is_merged,
             merged_by_tree: false,
             is_remote,
+            remote: name
+                .split_once('/')
+                .filter(|_| is_remote)
+                .map(|(remote, _)| remote.to_string()),
             last_commit_sha: "abc123".to_string(),
             last_commit_date: Utc::now(),
             last_commit_author: "testuser".to_string(),
+            last_commit_subject: None,
+            last_commit_committer: None,
         }
     }
 
@@ -212,11 +212,9 @@ fn handle_browse_key(app: &mut App, key: KeyEvent) -> bool {
         KeyCode::Char('n') => app.deselect_all(),
         KeyCode::Char('i') => app.invert_selection(),
         KeyCode::Char('V') => app.enter_visual_select(),
-        KeyCode::Char('d') => {
-            if app.selected_count() > 0 {
-                app.confirm_input.clear();
-                app.mode = Mode::Confirm;
-            }
+        KeyCode::Char('d') if app.selected_count() > 0 => {
+            app.confirm_input.clear();
+            app.mode = Mode::Confirm;
         }
         KeyCode::Char('/') => {
             app.mode = Mode::Filter;
@@ -376,7 +374,7 @@ fn collect_snap_cells(app: &App) -> Vec<(usize, Vec<(char, ratatui::style::Color
             // Age
             chars.push((' ', Color::DarkGray));
             let age_str = format!("{}d", branch.age_days);
-            let age_color = match AgeSeverity::from_days(branch.age_days) {
+            let age_color = match branch.age_severity() {
                 AgeSeverity::Fresh => Color::Green,
                 AgeSeverity::Moderate => Color::Yellow,
                 AgeSeverity::Stale => Color::Red,
@@ -430,6 +428,7 @@ fn collect_snap_cells(app: &App) -> Vec<(usize, Vec<(char, ratatui::style::Color
 fn start_background_deletions(app: &mut App) {
     let branches: Vec<Branch> = app.pending_deletions.drain(..).collect();
     let force = app.force;
+    let remote_jobs = app.remote_delete_jobs;
     app.deletion_total = branches.len();
 
     let (tx, rx) = mpsc::channel();
@@ -441,8 +440,8 @@ fn start_background_deletions(app: &mut App) {
 
         // Delete local branches one by one
         for branch in local {
-            let result =
-                crate::git::delete_local_branch(&branch.name, force || branch.merged_by_tree);
+            let mode = crate::git::DeleteMode::for_branch(&branch, force);
+            let result = crate::git::delete_local_branch(&branch.name, mode);
             let _ = tx.send(DeletionResult {
                 branch,
                 success: result.is_ok(),
@@ -454,7 +453,7 @@ fn start_background_deletions(app: &mut App) {
         if !remote.is_empty() {
             let _ = crate::git::fetch_and_prune();
             let names: Vec<String> = remote.iter().map(|b| b.name.clone()).collect();
-            match crate::git::delete_remote_branches_batch(&names) {
+            match crate::git::delete_remote_branches(&names, remote_jobs) {
                 Ok(results) => {
                     for ((_, success, error), branch) in results.into_iter().zip(remote) {
                         let _ = tx.send(DeletionResult {
@@ -497,7 +496,7 @@ fn prepare_deletions(app: &mut App) {
     // Create backup for all selected branches
     let all_to_backup: Vec<_> = local.iter().chain(remote.iter()).cloned().collect();
     if !all_to_backup.is_empty() {
-        match crate::create_backup_file(&all_to_backup) {
+        match crate::create_backup_file(&all_to_backup, app.backup_label.as_deref()) {
             Ok(path) => app.backup_path = Some(path),
             Err(e) => app.backup_path = Some(format!("backup failed: {}", e)),
         }
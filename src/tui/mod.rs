@@ -16,7 +16,11 @@ pub fn run_interactive(
     initial_filter: &BranchFilter,
     default_branch: &str,
     force: bool,
+    backup_label: Option<String>,
+    remote_delete_jobs: Option<usize>,
 ) -> Result<()> {
     let mut app = app::App::new(all_branches, initial_filter, default_branch, force);
+    app.backup_label = backup_label;
+    app.remote_delete_jobs = remote_delete_jobs;
     event::run(&mut app)
 }
@@ -1,21 +1,75 @@
 //! UI utilities - output formatting, prompts, tables
 
-use comfy_table::{presets::UTF8_FULL, Attribute, Cell, Color, Table};
+use comfy_table::{
+    presets::{UTF8_FULL, UTF8_FULL_CONDENSED},
+    Attribute, Cell, Color, ColumnConstraint, Table, Width,
+};
 use console::style;
-use dialoguer::{theme::ColorfulTheme, Confirm, Input};
+use dialoguer::{theme::ColorfulTheme, Confirm, Input, MultiSelect};
 use indicatif::{ProgressBar, ProgressStyle};
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, OnceLock};
 use std::time::Duration;
 
 use crate::backup::format_bytes;
 use crate::backup::BackupInfo;
 use crate::backup::{
-    BackupBranchEntry, BackupStats, BackupToDelete, CleanResult, RestoreError, RestoreResult,
-    SkippedLine,
+    BackupBranchEntry, BackupStats, BackupToDelete, ChecksumStatus, CleanResult, ParsedBackup,
+    RepoStatus, RestoreError, RestoreResult, SkippedLine, VerifyResult,
 };
-use crate::branch::{AgeSeverity, Branch};
+use crate::branch::{age_grade_bounds, AgeSeverity, Branch, SkipReason};
+use crate::config::{Config, UiConfig};
+use crate::error::DeadbranchError;
+use crate::perf::{subprocess_count, PhaseTiming, Summary};
 use crate::stats::RepoStats;
 
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+/// Keep stdout for machine output (`clean --print-backup-path`, `--eval`):
+/// everything printed through [`outln!`] and [`out!`] goes to stderr from now on
+pub fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
+/// Whether [`reserve_stdout`] is in effect
+pub fn stdout_reserved() -> bool {
+    STDOUT_RESERVED.load(Ordering::Relaxed)
+}
+
+/// `println!` for human-facing output; goes to stderr once stdout is reserved
+macro_rules! outln {
+    ($($arg:tt)*) => {
+        if $crate::ui::stdout_reserved() {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
+/// `print!` for human-facing output; goes to stderr once stdout is reserved
+macro_rules! out {
+    ($($arg:tt)*) => {
+        if $crate::ui::stdout_reserved() {
+            eprint!($($arg)*)
+        } else {
+            print!($($arg)*)
+        }
+    };
+}
+
+pub(crate) use {out, outln};
+
+/// The terminal human-facing output goes to
+fn output_term() -> console::Term {
+    if stdout_reserved() {
+        console::Term::stderr()
+    } else {
+        console::Term::stdout()
+    }
+}
+
 /// Generic pluralization helper
 pub fn pluralize<'a>(count: usize, singular: &'a str, plural: &'a str) -> &'a str {
     if count == 1 {
@@ -66,79 +120,478 @@ pub fn spinner(message: &str) -> ProgressBar {
 /// Finish spinner with success
 pub fn spinner_success(spinner: &ProgressBar, message: &str) {
     spinner.finish_and_clear();
-    println!("{} {}", style("✅").green(), message);
+    outln!("{} {}", style(SUCCESS).green(), message);
 }
 
 /// Finish spinner with warning
 pub fn spinner_warn(spinner: &ProgressBar, message: &str) {
     spinner.finish_and_clear();
-    println!("{} {}", style("⚠️").yellow(), message);
+    outln!("{} {}", style(WARNING).yellow(), message);
+}
+
+/// A message prefix, shown as an emoji/symbol or, with `--plain` or
+/// `ui.symbols = false`, as text
+#[derive(Clone, Copy)]
+pub struct Symbol {
+    fancy: &'static str,
+    plain: &'static str,
+}
+
+pub const SUCCESS: Symbol = Symbol {
+    fancy: "✅",
+    plain: "OK:",
+};
+pub const WARNING: Symbol = Symbol {
+    fancy: "⚠️",
+    plain: "WARN:",
+};
+pub const ERROR: Symbol = Symbol {
+    fancy: "❌",
+    plain: "ERROR:",
+};
+pub const INFO: Symbol = Symbol {
+    fancy: "ℹ️",
+    plain: "INFO:",
+};
+pub const HINT: Symbol = Symbol {
+    fancy: "💡",
+    plain: "HINT:",
+};
+/// A branch someone else deleted before we got to it
+pub const GONE: Symbol = Symbol {
+    fancy: "○",
+    plain: "GONE:",
+};
+/// Follow-up detail under a message (backup path, restored config, ...)
+pub const FOLLOW_UP: Symbol = Symbol {
+    fancy: "↪",
+    plain: "->",
+};
+/// Item in a short list under a message
+pub const ITEM: Symbol = Symbol {
+    fancy: "→",
+    plain: "-",
+};
+/// Bullet of a point in a warning
+pub const BULLET: Symbol = Symbol {
+    fancy: "•",
+    plain: "-",
+};
+/// Repeated to draw a horizontal rule between sections
+pub const RULE: Symbol = Symbol {
+    fancy: "─",
+    plain: "-",
+};
+/// Between two branch names that differ only by case
+pub const CASE_PAIR: Symbol = Symbol {
+    fancy: "↔",
+    plain: "<->",
+};
+/// Prefix of `--verbose` detail lines
+pub const DETAIL: Symbol = Symbol {
+    fancy: "·",
+    plain: "-",
+};
+/// Status marks inside table cells (dropped in plain mode)
+pub const CHECK: Symbol = Symbol {
+    fancy: "✓",
+    plain: "",
+};
+pub const CROSS: Symbol = Symbol {
+    fancy: "✗",
+    plain: "",
+};
+pub const ALERT: Symbol = Symbol {
+    fancy: "⚠",
+    plain: "",
+};
+
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Force textual prefixes (from the global `--plain` flag)
+pub fn set_plain(enabled: bool) {
+    PLAIN.store(enabled, Ordering::Relaxed);
+}
+
+impl Symbol {
+    pub fn as_str(self) -> &'static str {
+        if PLAIN.load(Ordering::Relaxed) || !table_options().symbols {
+            self.plain
+        } else {
+            self.fancy
+        }
+    }
+
+    /// `text` marked with this symbol, e.g. "✓ valid" (just "valid" in plain mode)
+    pub fn mark(self, text: &str) -> String {
+        match self.as_str() {
+            "" => text.to_string(),
+            symbol => format!("{} {}", symbol, text),
+        }
+    }
+}
+
+impl std::fmt::Display for Symbol {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+static DISPLAY_CONFIG: OnceLock<Config> = OnceLock::new();
+
+/// The config that shapes output (`[ui]`, age colors), read on first use
+fn output_config() -> &'static Config {
+    let mut first_use = false;
+    let config = DISPLAY_CONFIG.get_or_init(|| {
+        first_use = true;
+        Config::load_for_display()
+    });
+    // Warn only once the config is set, since `warning` reads it too
+    if first_use {
+        for note in &config.ui_notes {
+            warning(&format!("Config: {}", note));
+        }
+    }
+    config
+}
+
+/// The `[ui]` config section
+fn table_options() -> &'static UiConfig {
+    &output_config().ui
+}
+
+/// Color for an age in days, graded against `general.default_days`
+fn age_color(age_days: i64) -> Color {
+    match AgeSeverity::graded(age_days, output_config().general.default_days) {
+        AgeSeverity::Fresh => Color::Green,
+        AgeSeverity::Moderate => Color::Yellow,
+        AgeSeverity::Stale => Color::Red,
+    }
+}
+
+/// One-line key for the age colors, printed under tables that use them
+fn print_age_legend() {
+    let (moderate_from, stale_from) = age_grade_bounds(output_config().general.default_days);
+    outln!(
+        "{} {} / {} / {}",
+        style("age:").dim(),
+        style(format!("<{}d", moderate_from)).green(),
+        style(format!("<{}d", stale_from)).yellow(),
+        style("older").red()
+    );
+}
+
+/// Create a table in the configured style (`ui.compact` drops the row separators),
+/// uncolored when colors are off (`--no-color`)
+pub fn new_table() -> Table {
+    let mut table = Table::new();
+    table.load_preset(if table_options().compact {
+        UTF8_FULL_CONDENSED
+    } else {
+        UTF8_FULL
+    });
+    if !console::colors_enabled() {
+        table.force_no_tty();
+    }
+    table
+}
+
+/// Cap the branch-name column at `ui.max_branch_width`; longer names wrap.
+/// Call after the header is set so the column exists.
+fn limit_branch_column(table: &mut Table, index: usize) {
+    if let Some(width) = table_options().max_branch_width {
+        let width = u16::try_from(width).unwrap_or(u16::MAX);
+        if let Some(column) = table.column_mut(index) {
+            column.set_constraint(ColumnConstraint::UpperBoundary(Width::Fixed(width)));
+        }
+    }
+}
+
+/// A commit SHA as configured: the first 8 characters, or in full with `ui.full_sha`
+pub fn format_sha(sha: &str) -> &str {
+    if table_options().full_sha {
+        sha
+    } else {
+        &sha[..8.min(sha.len())]
+    }
 }
 
 /// Display a list of branches in a table
 pub fn display_branches(branches: &[Branch], title: &str) {
     if branches.is_empty() {
-        println!("{}", style("No stale branches found.").dim());
+        outln!("{}", style("No stale branches found.").dim());
         return;
     }
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+    let mut table = new_table();
 
-    table.set_header(vec![
-        Cell::new("#").add_attribute(Attribute::Bold),
-        Cell::new("Branch").add_attribute(Attribute::Bold),
-        Cell::new("Age").add_attribute(Attribute::Bold),
-        Cell::new("Status").add_attribute(Attribute::Bold),
-        Cell::new("Type").add_attribute(Attribute::Bold),
-        Cell::new("Last Commit").add_attribute(Attribute::Bold),
-        Cell::new("Author").add_attribute(Attribute::Bold),
-    ]);
+    let mut header = branch_header_cells();
+    header.push(Cell::new("Last Commit").add_attribute(Attribute::Bold));
+    header.push(Cell::new("Author").add_attribute(Attribute::Bold));
+    table.set_header(header);
+    limit_branch_column(&mut table, 1);
 
     for (i, branch) in branches.iter().enumerate() {
-        let status = if branch.is_merged {
-            Cell::new("merged").fg(Color::Green)
-        } else {
-            Cell::new("unmerged").fg(Color::Yellow)
-        };
+        let mut row = branch_row_cells(i, branch);
+        row.push(
+            Cell::new(branch.last_commit_date.format("%Y-%m-%d").to_string()).fg(Color::DarkGrey),
+        );
+        row.push(Cell::new(&branch.last_commit_author));
+        table.add_row(row);
+    }
 
-        let branch_type = if branch.is_remote {
-            Cell::new("remote").fg(Color::Blue)
-        } else {
-            Cell::new("local").fg(Color::Cyan)
+    if branches.iter().any(|b| b.last_commit_subject.is_some()) {
+        add_subject_column(&mut table, branches);
+    }
+
+    outln!("\n{}", style(title).bold());
+    outln!("{table}");
+    print_age_legend();
+    outln!();
+}
+
+/// Append a "Subject" column, cutting subjects so the table fits the terminal.
+/// When stdout isn't a terminal the subjects are left whole.
+fn add_subject_column(table: &mut Table, branches: &[Branch]) {
+    let used = table.column_max_content_widths();
+    let width = output_term()
+        .size_checked()
+        .map(|(_, cols)| subject_width(cols, &used));
+
+    if let Some(header) = table.header() {
+        let mut header = header.clone();
+        header.add_cell(Cell::new("Subject").add_attribute(Attribute::Bold));
+        table.set_header(header);
+    }
+    for (i, branch) in branches.iter().enumerate() {
+        let subject = branch.last_commit_subject.as_deref().unwrap_or("");
+        let subject = match width {
+            Some(width) => console::truncate_str(subject, width, "…"),
+            None => subject.into(),
         };
+        if let Some(row) = table.row_mut(i) {
+            row.add_cell(Cell::new(subject).fg(Color::DarkGrey));
+        }
+    }
+}
 
-        table.add_row(vec![
-            Cell::new((i + 1).to_string()).fg(Color::DarkGrey),
-            Cell::new(&branch.name),
-            Cell::new(branch.format_age()).fg(match branch.age_severity() {
-                AgeSeverity::Fresh => Color::Green,
-                AgeSeverity::Moderate => Color::Yellow,
-                AgeSeverity::Stale => Color::Red,
-            }),
-            status,
-            branch_type,
-            Cell::new(branch.last_commit_date.format("%Y-%m-%d").to_string()).fg(Color::DarkGrey),
-            Cell::new(&branch.last_commit_author),
-        ]);
+/// Room left for a new last column in a `term_width` terminal, given the
+/// content widths of the existing ones. Each column costs its content plus
+/// one space of padding either side and a border; never less than 10.
+fn subject_width(term_width: u16, used: &[u16]) -> usize {
+    let columns: usize = used.iter().map(|w| usize::from(*w) + 3).sum();
+    // Left border, plus the new column's padding and right border
+    let taken = 1 + columns + 3;
+    usize::from(term_width).saturating_sub(taken).max(10)
+}
+
+/// One-line count of the stale branches `list` found, printed before the
+/// tables, e.g. "42 local stale (30 merged, 12 unmerged), 18 remote stale (...)"
+pub fn print_list_census(local: &[Branch], remote: &[Branch]) {
+    if let Some(census) = list_census(local, remote) {
+        outln!();
+        info(&census);
     }
+}
 
-    println!("\n{}", style(title).bold());
-    println!("{table}\n");
+/// The census line for [`print_list_census`]; None when nothing was found
+fn list_census(local: &[Branch], remote: &[Branch]) -> Option<String> {
+    let part = |branches: &[Branch], kind: &str| {
+        let merged = branches.iter().filter(|b| b.is_merged).count();
+        format!(
+            "{} {} stale ({} merged, {} unmerged)",
+            branches.len(),
+            kind,
+            merged,
+            branches.len() - merged
+        )
+    };
+    let parts: Vec<String> = [(local, "local"), (remote, "remote")]
+        .into_iter()
+        .filter(|(branches, _)| !branches.is_empty())
+        .map(|(branches, kind)| part(branches, kind))
+        .collect();
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// Warn about branch names that differ only by case, given as
+/// `(candidate, other)` pairs
+pub fn warn_case_collisions(collisions: &[(String, String)]) {
+    if collisions.is_empty() {
+        return;
+    }
+    let mut pairs: Vec<(&str, &str)> = collisions
+        .iter()
+        .map(|(a, b)| {
+            if a <= b {
+                (a.as_str(), b.as_str())
+            } else {
+                (b.as_str(), a.as_str())
+            }
+        })
+        .collect();
+    pairs.sort_unstable();
+    pairs.dedup();
+
+    outln!();
+    warning(&format!(
+        "{} {} differ only by case; on case-insensitive filesystems (macOS, Windows) deleting one can affect the other:",
+        pairs.len(),
+        if pairs.len() == 1 { "branch pair" } else { "branch pairs" }
+    ));
+    for (a, b) in pairs {
+        outln!(
+            "    {} {} {}",
+            style(a).bold(),
+            style(CASE_PAIR).dim(),
+            style(b).bold()
+        );
+    }
+}
+
+/// Display branches `clean` left out, with the reason for each (`--explain`)
+pub fn display_skipped_branches(skipped: &[(Branch, SkipReason)]) {
+    if skipped.is_empty() {
+        outln!(
+            "{}",
+            style("No branches in the age window were skipped.").dim()
+        );
+        return;
+    }
+
+    let mut table = new_table();
+    let mut header = branch_header_cells();
+    header.push(Cell::new("Reason").add_attribute(Attribute::Bold));
+    table.set_header(header);
+    limit_branch_column(&mut table, 1);
+
+    for (i, (branch, reason)) in skipped.iter().enumerate() {
+        let mut row = branch_row_cells(i, branch);
+        row.push(Cell::new(reason.to_string()));
+        table.add_row(row);
+    }
+
+    outln!(
+        "\n{}",
+        style(format!("Skipped {}:", pluralize_branch_cap(skipped.len()))).bold()
+    );
+    outln!("{table}");
+    outln!();
+}
+
+/// Header cells shared by the branch tables: #, Branch, Age, Status, Type
+fn branch_header_cells() -> Vec<Cell> {
+    ["#", "Branch", "Age", "Status", "Type"]
+        .into_iter()
+        .map(|title| Cell::new(title).add_attribute(Attribute::Bold))
+        .collect()
+}
+
+/// Row cells matching [`branch_header_cells`]
+fn branch_row_cells(index: usize, branch: &Branch) -> Vec<Cell> {
+    let status = if branch.is_merged {
+        Cell::new("merged").fg(Color::Green)
+    } else {
+        Cell::new("unmerged").fg(Color::Yellow)
+    };
+
+    let branch_type = if branch.is_remote {
+        Cell::new("remote").fg(Color::Blue)
+    } else {
+        Cell::new("local").fg(Color::Cyan)
+    };
+
+    vec![
+        Cell::new((index + 1).to_string()).fg(Color::DarkGrey),
+        Cell::new(&branch.name),
+        Cell::new(branch.format_age()).fg(age_color(branch.age_days)),
+        status,
+        branch_type,
+    ]
+}
+
+/// Run a blocking prompt, giving up after `general.prompt_timeout_secs` when set.
+///
+/// On timeout this fails with [`DeadbranchError::UserCancelled`]. The thread
+/// stuck reading the terminal can't be interrupted; it is left behind, and
+/// the process exits once the error has ended the run.
+fn run_prompt<T: Send + 'static>(prompt: impl FnOnce() -> T + Send + 'static) -> anyhow::Result<T> {
+    let Some(secs) = output_config().general.prompt_timeout_secs else {
+        return Ok(prompt());
+    };
+
+    match answer_within(Duration::from_secs(secs), prompt) {
+        Some(answer) => Ok(answer),
+        None => {
+            let _ = output_term().show_cursor();
+            outln!();
+            Err(
+                DeadbranchError::UserCancelled("timed out waiting for confirmation".to_string())
+                    .into(),
+            )
+        }
+    }
+}
+
+/// Run `prompt` on its own thread and wait at most `timeout` for its answer
+fn answer_within<T: Send + 'static>(
+    timeout: Duration,
+    prompt: impl FnOnce() -> T + Send + 'static,
+) -> Option<T> {
+    let (sender, receiver) = mpsc::channel();
+    std::thread::spawn(move || {
+        let _ = sender.send(prompt());
+    });
+    receiver.recv_timeout(timeout).ok()
 }
 
 /// Ask for confirmation with nice themed UI
-pub fn confirm(prompt: &str, default: bool) -> bool {
-    Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(prompt)
-        .default(default)
-        .wait_for_newline(true)
-        .interact()
-        .unwrap_or(false)
+pub fn confirm(prompt: &str, default: bool) -> anyhow::Result<bool> {
+    let prompt = prompt.to_string();
+    run_prompt(move || {
+        Confirm::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .default(default)
+            .wait_for_newline(true)
+            .interact()
+            .unwrap_or(false)
+    })
+}
+
+/// Ask for a line of text, pre-filled with `initial` (None if the prompt was aborted)
+pub fn input(prompt: &str, initial: &str) -> anyhow::Result<Option<String>> {
+    let (prompt, initial) = (prompt.to_string(), initial.to_string());
+    run_prompt(move || {
+        Input::<String>::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .with_initial_text(initial)
+            .allow_empty(true)
+            .interact_text()
+            .ok()
+    })
+}
+
+/// Pick any number of `items`, those in `checked` starting selected
+/// (None if the prompt was cancelled)
+pub fn multi_select(
+    prompt: &str,
+    items: &[String],
+    checked: &[bool],
+) -> anyhow::Result<Option<Vec<usize>>> {
+    let (prompt, items, checked) = (prompt.to_string(), items.to_vec(), checked.to_vec());
+    run_prompt(move || {
+        MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt(prompt)
+            .items(&items)
+            .defaults(&checked)
+            .interact_opt()
+            .ok()
+            .flatten()
+    })
 }
 
 /// Ask for confirmation to delete local branches with visual summary
-pub fn confirm_local_deletion(branches: &[Branch]) -> bool {
+pub fn confirm_local_deletion(branches: &[Branch]) -> anyhow::Result<bool> {
     let total = branches.len();
     let merged_count = branches.iter().filter(|b| b.is_merged).count();
     let unmerged_count = total - merged_count;
@@ -163,49 +616,159 @@ pub fn confirm_local_deletion(branches: &[Branch]) -> bool {
         )
     };
 
-    Confirm::with_theme(&ColorfulTheme::default())
-        .with_prompt(summary)
-        .default(false)
-        .wait_for_newline(true)
-        .interact()
-        .unwrap_or(false)
+    confirm(&summary, false)
+}
+
+/// Print a deletion progress line with the branch's short SHA and age,
+/// e.g. `✅ feature/x (a1b2c3d4, 214d)`, so the scrollback records what went
+pub fn print_deleted_branch(branch: &Branch) {
+    outln!(
+        "  {} {} {}",
+        style(SUCCESS).green(),
+        branch.name,
+        style(format!(
+            "({}, {}d)",
+            format_sha(&branch.last_commit_sha),
+            branch.age_days
+        ))
+        .dim()
+    );
+}
+
+/// Print the backup file written by a deletion, as a clickable `file://` link
+/// when stdout is a terminal that takes escape sequences
+pub fn print_backup_path(path: &str) {
+    let term = output_term();
+    let linkable = term.is_term()
+        && console::colors_enabled()
+        && std::env::var("TERM").map_or(true, |t| t != "dumb");
+    let shown = if linkable {
+        hyperlink(&file_url(path), path)
+    } else {
+        path.to_string()
+    };
+    outln!(
+        "  {} Backup: {}",
+        style(FOLLOW_UP).dim(),
+        style(shown).dim()
+    );
+}
+
+/// Wrap `text` in an OSC 8 hyperlink to `url`; terminals without link support
+/// show just the text
+fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// `file://` URL for an absolute path, percent-encoding what URLs can't hold
+fn file_url(path: &str) -> String {
+    let mut url = String::from("file://");
+    if !path.starts_with('/') {
+        // Windows drive paths (C:\...) need a leading slash and forward slashes
+        url.push('/');
+    }
+    for c in path.chars() {
+        match c {
+            '\\' => url.push('/'),
+            ' ' | '%' | '#' | '?' | '"' | '<' | '>' => url.push_str(&format!("%{:02X}", c as u32)),
+            _ => url.push(c),
+        }
+    }
+    url
+}
+
+/// Print `value` as pretty JSON on stdout
+pub fn print_json<T: serde::Serialize + ?Sized>(value: &T) -> anyhow::Result<()> {
+    outln!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
 }
 
 /// Display success message
 pub fn success(message: &str) {
-    println!("{} {}", style("✅").green().bold(), message);
+    outln!("{} {}", style(SUCCESS).green().bold(), message);
 }
 
 /// Display warning message
 pub fn warning(message: &str) {
-    println!("{} {}", style("⚠️").yellow().bold(), message);
+    outln!("{} {}", style(WARNING).yellow().bold(), message);
+}
+
+/// Display a warning on stderr, for output that stdout must keep machine-readable
+pub fn warning_stderr(message: &str) {
+    eprintln!("{} {}", style(WARNING).yellow().bold(), message);
+}
+
+/// Display a follow-up line under a stderr message
+pub fn follow_up_stderr(message: &str) {
+    eprintln!("  {} {}", style(FOLLOW_UP).dim(), message);
 }
 
 /// Display error message
 pub fn error(message: &str) {
-    eprintln!("{} {}", style("❌").red().bold(), message);
+    eprintln!("{} {}", style(ERROR).red().bold(), message);
 }
 
 /// Display info message
 pub fn info(message: &str) {
-    println!("{} {}", style("ℹ️").blue().bold(), message);
+    outln!("{} {}", style(INFO).blue().bold(), message);
+}
+
+/// Display what a command would have done under `--check`
+pub fn check_notice(message: &str) {
+    outln!("{} {}", style("[CHECK]").yellow().bold(), message);
+}
+
+static VERBOSE: AtomicBool = AtomicBool::new(false);
+
+/// Turn `verbose` output on or off (from the global `--verbose` flag)
+pub fn set_verbose(enabled: bool) {
+    VERBOSE.store(enabled, Ordering::Relaxed);
+}
+
+/// Display a detail line, only shown with `--verbose`.
+/// Goes to stderr so machine-readable output on stdout stays clean.
+pub fn verbose(message: &str) {
+    if VERBOSE.load(Ordering::Relaxed) {
+        eprintln!("{} {}", style(DETAIL).dim(), style(message).dim());
+    }
+}
+
+static HINTS: AtomicBool = AtomicBool::new(true);
+
+/// Turn follow-up hints on or off (from the global `--no-hints` flag)
+pub fn set_hints(enabled: bool) {
+    HINTS.store(enabled, Ordering::Relaxed);
+}
+
+/// Display a follow-up hint, such as how to undo what just happened.
+/// Suppressed by `--no-hints`.
+pub fn hint(message: &str) {
+    if HINTS.load(Ordering::Relaxed) {
+        outln!("  {} {}", style(HINT).dim(), style(message).dim());
+    }
 }
 
 /// Print a grouped dry-run summary instead of listing every command.
-pub fn print_dry_run_summary(total: usize, local_safe: usize, local_force: usize, remote: usize) {
-    println!(
+/// `remote` holds how many branches would be deleted from each remote.
+pub fn print_dry_run_summary(
+    total: usize,
+    local_safe: usize,
+    local_force: usize,
+    remote: &[(&str, usize)],
+) {
+    outln!(
         "\n{}\n",
         style("[DRY RUN] No branches will be deleted.")
             .yellow()
             .bold()
     );
-    println!(
+    outln!(
         "Would execute {} {}:",
         style(total).bold(),
         pluralize(total, "command", "commands")
     );
     if local_safe > 0 {
-        println!(
+        outln!(
             "  {}  ({} local {})",
             style("git branch -d").dim(),
             local_safe,
@@ -213,81 +776,114 @@ pub fn print_dry_run_summary(total: usize, local_safe: usize, local_force: usize
         );
     }
     if local_force > 0 {
-        println!(
-            "  {}  ({} local unmerged {})",
+        outln!(
+            "  {}  ({} local {}, forced)",
             style("git branch -D").dim(),
             local_force,
             pluralize(local_force, "branch", "branches")
         );
     }
-    if remote > 0 {
-        println!(
+    for &(name, count) in remote {
+        outln!(
             "  {}  ({} remote {})",
-            style("git push origin --delete").dim(),
-            remote,
-            pluralize(remote, "branch", "branches")
+            style(format!("git push {} --delete", name)).dim(),
+            count,
+            pluralize(count, "branch", "branches")
         );
     }
-    println!("\nRun without {} to execute.", style("--dry-run").bold());
+    outln!("\nRun without {} to execute.", style("--dry-run").bold());
+}
+
+/// The phrase typed (or passed with `--confirm-phrase`) to delete `count`
+/// remote branches, e.g. "delete 17 remote branches"
+pub fn remote_confirmation_phrase(count: usize) -> String {
+    format!("delete {} remote {}", count, pluralize_branch(count))
 }
 
-/// Display remote deletion warning and get confirmation
-/// Returns true if user confirms, false otherwise
-pub fn confirm_remote_deletion(branches: &[Branch]) -> bool {
+/// Display remote deletion warning and get confirmation for deleting
+/// `branches` from `remotes`. Returns true if user confirms, false otherwise
+pub fn confirm_remote_deletion(
+    branches: &[Branch],
+    remotes: &[&str],
+    remote_url: Option<&str>,
+) -> anyhow::Result<bool> {
     let count = branches.len();
     let branch_word = pluralize_branch(count);
 
-    println!();
-    println!(
+    outln!();
+    outln!(
         "{}",
         style(format!(
-            "⚠️  WARNING: You are about to delete remote {}!",
-            branch_word
+            "{}  WARNING: You are about to delete remote {}!",
+            WARNING, branch_word
         ))
         .yellow()
         .bold()
     );
-    println!();
-    println!("This action:");
-    println!("  • {} easily", style("Cannot be undone").red());
-    println!("  • Will {} all team members", style("affect").red());
-    println!(
-        "  • Removes {} from origin {}",
+    if let Some(url) = remote_url {
+        outln!("You are deleting branches on {}", style(url).cyan().bold());
+    }
+    outln!();
+    outln!("This action:");
+    outln!("  {} {} easily", BULLET, style("Cannot be undone").red());
+    outln!(
+        "  {} Will {} all team members",
+        BULLET,
+        style("affect").red()
+    );
+    outln!(
+        "  {} Removes {} from {} {}",
+        BULLET,
         branch_word,
+        remotes.join(", "),
         style("permanently").red()
     );
-    println!();
+    outln!();
 
-    // Simple confirmation text with just the count
-    let expected = format!("delete {} remote {}", count, branch_word);
-    println!(
+    let expected = remote_confirmation_phrase(count);
+    outln!(
         "To confirm, type exactly: {}",
         style(format!("\"{}\"", expected)).yellow()
     );
-    println!();
-
-    let term = console::Term::stdout();
-    let _ = term.show_cursor();
-
-    let input: String = Input::with_theme(&ColorfulTheme::default())
-        .with_prompt("Type confirmation")
-        .allow_empty(true)
-        .interact_on(&term)
-        .unwrap_or_default();
+    outln!();
+
+    let input: String = run_prompt(|| {
+        let term = output_term();
+        let _ = term.show_cursor();
+        Input::with_theme(&ColorfulTheme::default())
+            .with_prompt("Type confirmation")
+            .allow_empty(true)
+            .interact_on(&term)
+            .unwrap_or_default()
+    })?;
+
+    Ok(input.trim() == expected)
+}
 
-    input.trim() == expected
+/// Show how a list-valued config key changes: `+` added, `-` removed
+pub fn display_list_change(key: &str, old: &[String], new: &[String]) {
+    outln!("{}", style(format!("{}:", key)).bold());
+    for value in old {
+        if new.contains(value) {
+            outln!("    {}", style(value).dim());
+        } else {
+            outln!("  {} {}", style("-").red().bold(), style(value).red());
+        }
+    }
+    for value in new.iter().filter(|v| !old.contains(v)) {
+        outln!("  {} {}", style("+").green().bold(), style(value).green());
+    }
+    if old.is_empty() && new.is_empty() {
+        outln!("    {}", style("(none)").dim());
+    }
+    outln!();
 }
 
 /// Display configuration in a table
-pub fn display_config(
-    default_days: u32,
-    protected_branches: &[String],
-    exclude_patterns: &[String],
-    default_branch: Option<&str>,
-    config_path: &str,
-) {
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+pub fn display_config(config: &Config, config_path: &str) {
+    let general = &config.general;
+    let branches = &config.branches;
+    let mut table = new_table();
 
     table.set_header(vec![
         Cell::new("Section").add_attribute(Attribute::Bold),
@@ -299,20 +895,72 @@ pub fn display_config(
     table.add_row(vec![
         Cell::new("general").fg(Color::Yellow),
         Cell::new("default_days"),
-        Cell::new(default_days.to_string()).fg(Color::Cyan),
+        Cell::new(general.default_days.to_string()).fg(Color::Cyan),
+    ]);
+    // The effective per-scope thresholds, noting where each comes from
+    for (key, scoped, remote) in [
+        ("default_days_local", general.default_days_local, false),
+        ("default_days_remote", general.default_days_remote, true),
+    ] {
+        let value = match scoped {
+            Some(days) => days.to_string(),
+            None => format!("{} (default_days)", general.days_for(remote)),
+        };
+        table.add_row(vec![
+            Cell::new("general").fg(Color::Yellow),
+            Cell::new(key),
+            Cell::new(value).fg(Color::Cyan),
+        ]);
+    }
+    table.add_row(vec![
+        Cell::new("general").fg(Color::Yellow),
+        Cell::new("default_command"),
+        Cell::new(&general.default_command).fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("general").fg(Color::Yellow),
+        Cell::new("default_scope"),
+        Cell::new(general.default_scope.as_str()).fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("general").fg(Color::Yellow),
+        Cell::new("prompt_timeout_secs"),
+        Cell::new(
+            general
+                .prompt_timeout_secs
+                .map_or("(wait forever)".to_string(), |secs| secs.to_string()),
+        )
+        .fg(Color::Cyan),
+    ]);
+
+    table.add_row(vec![
+        Cell::new("general").fg(Color::Yellow),
+        Cell::new("offline"),
+        Cell::new(general.offline.to_string()).fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("general").fg(Color::Yellow),
+        Cell::new("repo_name"),
+        Cell::new(general.repo_name.as_deref().unwrap_or("(directory name)")).fg(Color::Cyan),
     ]);
 
     // Branches section
     table.add_row(vec![
         Cell::new("branches").fg(Color::Yellow),
         Cell::new("default_branch"),
-        Cell::new(default_branch.unwrap_or("(auto-detect)")).fg(Color::Cyan),
+        Cell::new(
+            branches
+                .default_branch
+                .as_deref()
+                .unwrap_or("(auto-detect)"),
+        )
+        .fg(Color::Cyan),
     ]);
 
-    let protected_display = if protected_branches.is_empty() {
+    let protected_display = if branches.protected.is_empty() {
         "(none)".to_string()
     } else {
-        protected_branches.join(", ")
+        branches.protected.join(", ")
     };
     table.add_row(vec![
         Cell::new("branches").fg(Color::Yellow),
@@ -320,80 +968,148 @@ pub fn display_config(
         Cell::new(protected_display).fg(Color::Cyan),
     ]);
 
-    let exclude_display = if exclude_patterns.is_empty() {
+    let exclude_display = if branches.exclude_patterns.is_empty() {
         "(none)".to_string()
     } else {
-        exclude_patterns.join(", ")
+        branches.exclude_patterns.join(", ")
     };
     table.add_row(vec![
         Cell::new("branches").fg(Color::Yellow),
         Cell::new("exclude_patterns"),
         Cell::new(exclude_display).fg(Color::Cyan),
     ]);
+    table.add_row(vec![
+        Cell::new("branches").fg(Color::Yellow),
+        Cell::new("remote_require_merged"),
+        Cell::new(branches.remote_require_merged.to_string()).fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("branches").fg(Color::Yellow),
+        Cell::new("remote_delete_jobs"),
+        Cell::new(
+            branches
+                .remote_delete_jobs
+                .map_or("(batched)".to_string(), |jobs| jobs.to_string()),
+        )
+        .fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("branches").fg(Color::Yellow),
+        Cell::new("delete_when"),
+        Cell::new(branches.delete_when.as_deref().unwrap_or("merged")).fg(Color::Cyan),
+    ]);
+
+    // Backups section
+    table.add_row(vec![
+        Cell::new("backups").fg(Color::Yellow),
+        Cell::new("template"),
+        Cell::new(
+            config
+                .backups
+                .template
+                .as_ref()
+                .map_or("(built-in)".to_string(), |path| path.display().to_string()),
+        )
+        .fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("backups").fg(Color::Yellow),
+        Cell::new("max_file_size_mb"),
+        Cell::new(config.backups.max_file_size_mb().to_string()).fg(Color::Cyan),
+    ]);
 
-    println!("\n{}", style("Configuration:").bold());
-    println!("{table}");
-    println!(
+    // UI section
+    let ui = &config.ui;
+    table.add_row(vec![
+        Cell::new("ui").fg(Color::Yellow),
+        Cell::new("max_branch_width"),
+        Cell::new(
+            ui.max_branch_width
+                .map_or("(no limit)".to_string(), |w| w.to_string()),
+        )
+        .fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("ui").fg(Color::Yellow),
+        Cell::new("full_sha"),
+        Cell::new(ui.full_sha.to_string()).fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("ui").fg(Color::Yellow),
+        Cell::new("compact"),
+        Cell::new(ui.compact.to_string()).fg(Color::Cyan),
+    ]);
+    table.add_row(vec![
+        Cell::new("ui").fg(Color::Yellow),
+        Cell::new("symbols"),
+        Cell::new(ui.symbols.to_string()).fg(Color::Cyan),
+    ]);
+
+    outln!("\n{}", style("Configuration:").bold());
+    outln!("{table}");
+    outln!(
         "{} {}",
         style("Config file:").dim(),
         style(config_path).dim()
     );
-    println!();
+    outln!();
 }
 
 /// Display backups for a single repository
 pub fn display_repo_backups(repo_name: &str, backups: &[BackupInfo]) {
     if backups.is_empty() {
-        println!(
+        outln!(
             "{}",
             style(format!("No backups found for '{}'.", repo_name)).dim()
         );
         return;
     }
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+    let mut table = new_table();
 
     table.set_header(vec![
         Cell::new("#").add_attribute(Attribute::Bold),
         Cell::new("Backup").add_attribute(Attribute::Bold),
         Cell::new("Age").add_attribute(Attribute::Bold),
         Cell::new("Branches").add_attribute(Attribute::Bold),
+        Cell::new("Label").add_attribute(Attribute::Bold),
     ]);
 
     for (i, backup) in backups.iter().enumerate() {
         table.add_row(vec![
             Cell::new((i + 1).to_string()).fg(Color::DarkGrey),
             Cell::new(backup.filename()),
-            Cell::new(backup.format_age()).fg(Color::Cyan),
+            Cell::new(backup.format_age()).fg(age_color(backup.age_days())),
             Cell::new(backup.branch_count.to_string()).fg(Color::Yellow),
+            Cell::new(backup.label.as_deref().unwrap_or("")).fg(Color::DarkGrey),
         ]);
     }
 
-    println!(
+    outln!(
         "\n{}",
         style(format!("Backups for '{}':", repo_name)).bold()
     );
-    println!("{table}");
+    outln!("{table}");
+    print_age_legend();
 
     // Show restore hint
-    println!();
-    println!("{}", style("To restore a branch:").dim());
-    println!(
+    outln!();
+    outln!("{}", style("To restore a branch:").dim());
+    outln!(
         "  {}",
         style("deadbranch backup restore <branch-name>").dim()
     );
-    println!(
+    outln!(
         "  {}",
         style("deadbranch backup restore <branch-name> --from <backup-file>").dim()
     );
-    println!();
+    outln!();
 }
 
 /// Display all backups as a summary grouped by repository
 pub fn display_all_backups(all_backups: &HashMap<String, Vec<BackupInfo>>) {
     if all_backups.is_empty() {
-        println!("{}", style("No backups found.").dim());
+        outln!("{}", style("No backups found.").dim());
         return;
     }
 
@@ -401,8 +1117,7 @@ pub fn display_all_backups(all_backups: &HashMap<String, Vec<BackupInfo>>) {
     let mut repos: Vec<_> = all_backups.keys().collect();
     repos.sort();
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+    let mut table = new_table();
 
     table.set_header(vec![
         Cell::new("#").add_attribute(Attribute::Bold),
@@ -431,11 +1146,11 @@ pub fn display_all_backups(all_backups: &HashMap<String, Vec<BackupInfo>>) {
         ]);
     }
 
-    println!("\n{}", style("All backups:").bold());
-    println!("{table}");
+    outln!("\n{}", style("All backups:").bold());
+    outln!("{table}");
 
     // Summary
-    println!(
+    outln!(
         "\n{} {} {} across {} {}",
         style("Total:").dim(),
         style(total_backups).cyan(),
@@ -445,19 +1160,19 @@ pub fn display_all_backups(all_backups: &HashMap<String, Vec<BackupInfo>>) {
     );
 
     // Hint
-    println!();
-    println!("{}", style("To see details for a repository:").dim());
-    println!("  {}", style("deadbranch backup list --repo <name>").dim());
-    println!(
+    outln!();
+    outln!("{}", style("To see details for a repository:").dim());
+    outln!("  {}", style("deadbranch backup list --repo <name>").dim());
+    outln!(
         "  {}",
         style("deadbranch backup list --current  (for current repo)").dim()
     );
-    println!();
+    outln!();
 }
 
 /// Display restore success message
 pub fn display_restore_success(result: &RestoreResult) {
-    let short_sha = &result.commit_sha[..8.min(result.commit_sha.len())];
+    let sha = format_sha(&result.commit_sha);
     let renamed = result.original_name != result.restored_name;
     let overwrote = result.overwrote_existing;
 
@@ -469,40 +1184,115 @@ pub fn display_restore_success(result: &RestoreResult) {
 
     if renamed {
         // Restored with different name (--as flag)
-        println!(
+        outln!(
             "{} Restored branch '{}' as '{}' at commit {}{}",
-            style("✅").green().bold(),
+            style(SUCCESS).green().bold(),
             style(&result.original_name).cyan(),
             style(&result.restored_name).cyan().bold(),
-            style(short_sha).yellow(),
+            style(sha).yellow(),
             suffix
         );
     } else {
         // Normal restore (same name)
-        println!(
+        outln!(
             "{} Restored branch '{}' at commit {}{}",
-            style("✅").green().bold(),
+            style(SUCCESS).green().bold(),
             style(&result.restored_name).cyan().bold(),
-            style(short_sha).yellow(),
+            style(sha).yellow(),
             suffix
         );
     }
+
+    if !result.restored_config.is_empty() {
+        outln!(
+            "  {} Restored branch config: {}",
+            style(FOLLOW_UP).dim(),
+            result.restored_config.join(", ")
+        );
+    }
+    if !result.skipped_config.is_empty() {
+        outln!(
+            "  {} Skipped branch config (remote or upstream no longer exists): {}",
+            style(FOLLOW_UP).dim(),
+            style(result.skipped_config.join(", ")).yellow()
+        );
+    }
+}
+
+/// Display the branches recorded in a backup file
+pub fn display_backup_contents(path: &std::path::Path, parsed: &ParsedBackup) {
+    let filename = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string());
+
+    if parsed.entries.is_empty() {
+        info(&format!("No branch entries found in '{}'", filename));
+    } else {
+        let mut table = new_table();
+
+        table.set_header(vec![
+            Cell::new("#").add_attribute(Attribute::Bold),
+            Cell::new("Branch").add_attribute(Attribute::Bold),
+            Cell::new("Commit").add_attribute(Attribute::Bold),
+            Cell::new("Description").add_attribute(Attribute::Bold),
+        ]);
+        limit_branch_column(&mut table, 1);
+
+        for (i, entry) in parsed.entries.iter().enumerate() {
+            let sha = format_sha(&entry.commit_sha);
+            // Only the first line of a multi-line description fits in a cell
+            let description = entry
+                .description()
+                .and_then(|d| d.lines().next())
+                .unwrap_or("");
+            table.add_row(vec![
+                Cell::new((i + 1).to_string()).fg(Color::DarkGrey),
+                Cell::new(&entry.name).fg(Color::Cyan),
+                Cell::new(sha).fg(Color::Yellow),
+                Cell::new(description).fg(Color::DarkGrey),
+            ]);
+        }
+
+        outln!("\n{}", style(format!("Backup '{}':", filename)).bold());
+        if let Some(label) = &parsed.label {
+            outln!("{} {}", style("Label:").dim(), style(label).cyan());
+        }
+        outln!("{table}");
+    }
+
+    if !parsed.skipped_lines.is_empty() {
+        outln!();
+        display_skipped_lines(&parsed.skipped_lines, parsed.skipped_line_count);
+    }
+
+    outln!();
+    outln!("{}", style("To restore a branch:").dim());
+    outln!(
+        "  {}",
+        style(format!(
+            "deadbranch backup restore <branch-name> --from {}",
+            filename
+        ))
+        .dim()
+    );
+    outln!();
 }
 
 /// Display restore error with helpful suggestions
-pub fn display_restore_error(err: &RestoreError, branch_name: &str) {
+pub fn display_restore_error(err: &RestoreError) {
     match err {
         RestoreError::BranchExists { branch_name } => {
             error(&format!("Branch '{}' already exists", branch_name));
-            println!();
-            println!("To overwrite it, use {}:", style("--force").yellow());
-            println!(
+            outln!();
+            outln!("To overwrite it, use {}:", style("--force").yellow());
+            outln!(
                 "  {}",
                 style(format!("deadbranch backup restore {} --force", branch_name)).dim()
             );
-            println!();
-            println!("To restore with a different name:");
-            println!(
+            outln!();
+            outln!("To restore with a different name:");
+            outln!(
                 "  {}",
                 style(format!(
                     "deadbranch backup restore {} --as {}-restored",
@@ -516,68 +1306,103 @@ pub fn display_restore_error(err: &RestoreError, branch_name: &str) {
             branch_name,
             commit_sha,
         } => {
-            let short_sha = &commit_sha[..8.min(commit_sha.len())];
+            let sha = format_sha(commit_sha);
             error(&format!(
                 "Cannot restore '{}': commit {} no longer exists",
-                branch_name, short_sha
+                branch_name, sha
             ));
-            println!("  {}", style("(Git may have garbage collected it)").dim());
-            println!();
-            println!(
+            outln!("  {}", style("(Git may have garbage collected it)").dim());
+            outln!();
+            outln!(
                 "{}",
                 style("Tip: Try restoring from an older backup with --from").dim()
             );
-            println!(
+            outln!(
                 "     {}",
                 style("Run 'git fsck --unreachable' to check for dangling commits").dim()
             );
         }
 
+        RestoreError::NotACommit {
+            branch_name,
+            commit_sha,
+            object_type,
+        } => {
+            error(&format!(
+                "Cannot restore '{}': {} is a {}, not a commit",
+                branch_name,
+                format_sha(commit_sha),
+                object_type
+            ));
+            outln!(
+                "  {}",
+                style(
+                    "(A branch can only point at a commit; the backup entry may have been edited)"
+                )
+                .dim()
+            );
+        }
+
         RestoreError::BranchNotInBackup {
-            branch_name: _,
+            branch_name,
             available_branches,
             skipped_lines,
+            skipped_line_count,
         } => {
             error(&format!("Branch '{}' not found in backup", branch_name));
-            println!();
-
-            // Show warning about skipped/corrupted lines first
-            if !skipped_lines.is_empty() {
-                display_skipped_lines(skipped_lines);
-            }
+            outln!();
+            display_missing_branch_help(available_branches, skipped_lines, *skipped_line_count);
+        }
 
-            if !available_branches.is_empty() {
-                display_available_branches(available_branches);
-            } else if !skipped_lines.is_empty() {
-                // No valid entries and we have skipped lines - the backup might be corrupted
-                println!(
-                    "{}",
-                    style("No valid branch entries found in backup.").yellow()
-                );
-                println!();
-                println!(
-                    "{}",
-                    style("The backup file may be corrupted. Try a different backup:").dim()
-                );
-                println!("  {}", style("deadbranch backup list --current").dim());
-            }
+        RestoreError::NoBranchesMatch {
+            pattern,
+            available_branches,
+            skipped_lines,
+            skipped_line_count,
+        } => {
+            error(&format!("No branches in backup match '{}'", pattern));
+            outln!();
+            display_missing_branch_help(available_branches, skipped_lines, *skipped_line_count);
         }
 
         RestoreError::NoBackupsFound { repo_name } => {
             error(&format!("No backups found for repository '{}'", repo_name));
-            println!();
-            println!(
+            outln!();
+            outln!(
                 "  {} Backups are created automatically when running 'deadbranch clean'.",
-                style("↪").dim()
+                style(FOLLOW_UP).dim()
             );
         }
 
         RestoreError::BackupCorrupted { message } => {
             error("Backup file is corrupted or invalid format");
-            println!("  {}", style(message).dim());
-            println!();
-            println!("Try a different backup:");
-            println!("  {}", style("deadbranch backup list --current").dim());
+            outln!("  {}", style(message).dim());
+            outln!();
+            outln!("Try a different backup:");
+            outln!("  {}", style("deadbranch backup list --current").dim());
+        }
+
+        RestoreError::UnsupportedFormat { .. } => {
+            error(&err.to_string());
+            outln!();
+            outln!(
+                "{}",
+                style("Upgrade deadbranch to read backups written by newer versions.").dim()
+            );
+        }
+
+        RestoreError::RepoMismatch { .. } => {
+            error(&err.to_string());
+            outln!();
+            outln!(
+                "{}",
+                style("Restoring commits from another project's backup is usually a mistake.")
+                    .dim()
+            );
+            outln!(
+                "To restore anyway, use {}",
+                style("--ignore-repo-mismatch").yellow()
+            );
         }
 
         RestoreError::Other(e) => {
@@ -586,27 +1411,160 @@ pub fn display_restore_error(err: &RestoreError, branch_name: &str) {
     }
 }
 
+/// Display the result of `backup migrate`
+pub fn display_migrate_summary(migrated: usize, already_current: usize) {
+    if migrated == 0 {
+        info(&format!(
+            "All {} {} already use the current format",
+            already_current,
+            pluralize(already_current, "backup", "backups")
+        ));
+        return;
+    }
+
+    success(&format!(
+        "Migrated {} {} to the current format",
+        migrated,
+        pluralize(migrated, "backup", "backups")
+    ));
+    outln!(
+        "  {} Originals kept alongside as {}",
+        style(FOLLOW_UP).dim(),
+        style("*.bak").dim()
+    );
+}
+
+/// Display per-file results of `backup verify`
+pub fn display_verify_results(results: &[VerifyResult]) {
+    if results.is_empty() {
+        info("No backups found.");
+        return;
+    }
+
+    let mut table = new_table();
+
+    table.set_header(vec![
+        Cell::new("Repository").add_attribute(Attribute::Bold),
+        Cell::new("Backup").add_attribute(Attribute::Bold),
+        Cell::new("Branches").add_attribute(Attribute::Bold),
+        Cell::new("Checksum").add_attribute(Attribute::Bold),
+        Cell::new("Problem").add_attribute(Attribute::Bold),
+    ]);
+
+    for result in results {
+        let checksum = match result.info.checksum {
+            ChecksumStatus::Valid => Cell::new(CHECK.mark("valid")).fg(Color::Green),
+            ChecksumStatus::NotRecorded => Cell::new("not recorded").fg(Color::DarkGrey),
+            ChecksumStatus::Truncated => Cell::new(CROSS.mark("truncated")).fg(Color::Red),
+            ChecksumStatus::Modified => Cell::new(CROSS.mark("modified")).fg(Color::Red),
+        };
+        table.add_row(vec![
+            Cell::new(result.info.repo_name()).fg(Color::Cyan),
+            Cell::new(result.info.filename()),
+            Cell::new(result.info.branch_count.to_string()).fg(Color::Yellow),
+            checksum,
+            Cell::new(result.error.as_deref().unwrap_or("")).fg(Color::Red),
+        ]);
+    }
+
+    outln!("{table}");
+    outln!();
+
+    let failed = results.iter().filter(|r| r.error.is_some()).count();
+    let word = pluralize(results.len(), "backup", "backups");
+    if failed == 0 {
+        success(&format!("All {} {} verified", results.len(), word));
+    } else {
+        error(&format!(
+            "{} of {} {} failed verification",
+            failed,
+            results.len(),
+            word
+        ));
+    }
+}
+
+/// Preview the branches `backup restore --matching` is about to restore
+pub fn display_restore_selection(pattern: &str, backup: &str, entries: &[BackupBranchEntry]) {
+    let mut table = new_table();
+
+    table.set_header(vec![
+        Cell::new("Branch").add_attribute(Attribute::Bold),
+        Cell::new("Commit").add_attribute(Attribute::Bold),
+    ]);
+    limit_branch_column(&mut table, 0);
+
+    for entry in entries {
+        table.add_row(vec![
+            Cell::new(&entry.name).fg(Color::Cyan),
+            Cell::new(format_sha(&entry.commit_sha)).fg(Color::Yellow),
+        ]);
+    }
+
+    info(&format!(
+        "{} {} in {} {} '{}':",
+        entries.len(),
+        pluralize_branch(entries.len()),
+        backup,
+        if entries.len() == 1 {
+            "matches"
+        } else {
+            "match"
+        },
+        pattern
+    ));
+    outln!("{table}");
+}
+
+/// Show what a backup holds when the requested branches weren't in it:
+/// skipped lines first, then the branches there are
+fn display_missing_branch_help(
+    available_branches: &[BackupBranchEntry],
+    skipped_lines: &[SkippedLine],
+    skipped_line_count: usize,
+) {
+    if !skipped_lines.is_empty() {
+        display_skipped_lines(skipped_lines, skipped_line_count);
+    }
+
+    if !available_branches.is_empty() {
+        display_available_branches(available_branches);
+    } else if !skipped_lines.is_empty() {
+        // No valid entries and we have skipped lines - the backup might be corrupted
+        outln!(
+            "{}",
+            style("No valid branch entries found in backup.").yellow()
+        );
+        outln!();
+        outln!(
+            "{}",
+            style("The backup file may be corrupted. Try a different backup:").dim()
+        );
+        outln!("  {}", style("deadbranch backup list --current").dim());
+    }
+}
+
 /// Display available branches in a table format
 fn display_available_branches(branches: &[BackupBranchEntry]) {
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+    let mut table = new_table();
 
     table.set_header(vec![
         Cell::new("Branch").add_attribute(Attribute::Bold),
         Cell::new("Commit").add_attribute(Attribute::Bold),
     ]);
+    limit_branch_column(&mut table, 0);
 
     // Show up to 10 branches
     let display_count = branches.len().min(10);
     for entry in branches.iter().take(display_count) {
-        let short_sha = &entry.commit_sha[..8.min(entry.commit_sha.len())];
+        let sha = format_sha(&entry.commit_sha);
         table.add_row(vec![
             Cell::new(&entry.name).fg(Color::Cyan),
-            Cell::new(short_sha).fg(Color::Yellow),
+            Cell::new(sha).fg(Color::Yellow),
         ]);
     }
 
-    println!(
+    outln!(
         "{}",
         style(format!(
             "Available {} in this backup:",
@@ -614,26 +1572,26 @@ fn display_available_branches(branches: &[BackupBranchEntry]) {
         ))
         .dim()
     );
-    println!("{table}");
+    outln!("{table}");
 
     if branches.len() > 10 {
-        println!(
+        outln!(
             "  {} ... and {} more",
-            style("↪").dim(),
+            style(FOLLOW_UP).dim(),
             branches.len() - 10
         );
     }
-    println!();
+    outln!();
 }
 
 /// Display warning about skipped/corrupted lines in backup file
-fn display_skipped_lines(skipped: &[SkippedLine]) {
-    let count = skipped.len();
+/// `count` is the total, which can exceed the lines the parser kept
+fn display_skipped_lines(skipped: &[SkippedLine], count: usize) {
     let line_word = pluralize(count, "line", "lines");
 
-    println!(
+    outln!(
         "{} {} {} in backup file:",
-        style("⚠️").yellow().bold(),
+        style(WARNING).yellow().bold(),
         style(format!("{} corrupted", count)).yellow(),
         line_word
     );
@@ -641,23 +1599,23 @@ fn display_skipped_lines(skipped: &[SkippedLine]) {
     // Show up to 3 skipped lines as examples
     for line in skipped.iter().take(3) {
         // Truncate long lines for display
-        let display_content = if line.content.len() > 60 {
-            format!("{}...", &line.content[..57])
+        let display_content = if line.content.chars().count() > 60 {
+            format!("{}...", line.content.chars().take(57).collect::<String>())
         } else {
             line.content.clone()
         };
-        println!(
+        outln!(
             "  {} Line {}: {}",
-            style("→").dim(),
+            style(ITEM).dim(),
             style(line.line_number).yellow(),
             style(display_content).dim()
         );
     }
 
     if count > 3 {
-        println!("  {} ... and {} more", style("→").dim(), count - 3);
+        outln!("  {} ... and {} more", style(ITEM).dim(), count - 3);
     }
-    println!();
+    outln!();
 }
 
 /// Display backups that will be deleted in a table format
@@ -665,23 +1623,40 @@ pub fn display_backups_to_clean(
     repo_name: &str,
     backups: &[BackupToDelete],
     keep: usize,
+    keep_days: Option<u32>,
     _dry_run: bool,
 ) {
-    println!(
-        "Cleaning backups for '{}' (keeping {} most recent)...\n",
+    let keeping = match keep_days {
+        Some(days) => format!(
+            "keeping {} most recent and any from the last {}",
+            keep,
+            format_day_count(days)
+        ),
+        None => format!("keeping {} most recent", keep),
+    };
+    outln!(
+        "Cleaning backups for '{}' ({})...\n",
         style(repo_name).cyan(),
-        keep
+        keeping
     );
 
     if backups.is_empty() {
-        println!("  {} No old backups to clean\n", style("ℹ️").blue());
+        outln!("  {} No old backups to clean\n", style(INFO).blue());
         return;
     }
 
-    println!("{}", style("Backups to Delete:").bold());
+    outln!("{}", style("Backups to Delete:").bold());
+    outln!("{}\n", backups_to_delete_table(backups));
+}
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+/// Display the backups picked in `backup clean --interactive`
+pub fn display_backup_selection(repo_name: &str, backups: &[BackupToDelete]) {
+    outln!("Selected backups for '{}':\n", style(repo_name).cyan());
+    outln!("{}\n", backups_to_delete_table(backups));
+}
+
+fn backups_to_delete_table(backups: &[BackupToDelete]) -> Table {
+    let mut table = new_table();
 
     table.set_header(vec![
         Cell::new("Backup").add_attribute(Attribute::Bold),
@@ -699,27 +1674,73 @@ pub fn display_backups_to_clean(
         ]);
     }
 
-    println!("{table}\n");
+    table
+}
+
+/// Let the user pick which backups to delete, with `preselected` checked initially
+///
+/// Returns `None` if the prompt was cancelled (or there is no terminal to prompt on).
+pub fn select_backups_to_clean(
+    backups: &[BackupToDelete],
+    preselected: &[bool],
+) -> anyhow::Result<Option<Vec<usize>>> {
+    let items: Vec<String> = backups
+        .iter()
+        .map(|b| {
+            format!(
+                "{}  {}  {} {}  {}",
+                b.info.filename(),
+                b.info.format_age(),
+                b.info.branch_count,
+                pluralize_branch(b.info.branch_count),
+                b.format_size()
+            )
+        })
+        .collect();
+
+    multi_select(
+        "Select backups to delete (space to toggle, enter to confirm, esc to cancel)",
+        &items,
+        preselected,
+    )
 }
 
 /// Ask for confirmation to delete backups
-pub fn confirm_backup_clean(count: usize, total_size: u64) -> bool {
+pub fn confirm_backup_clean(count: usize, total_size: u64, criteria: &str) -> anyhow::Result<bool> {
     let file_word = pluralize(count, "backup", "backups");
     let prompt = format!(
-        "Delete {} {} ({})?",
+        "Delete {} {} ({}) {}?",
         count,
         file_word,
-        format_bytes(total_size)
+        format_bytes(total_size),
+        criteria
     );
     confirm(&prompt, false)
 }
 
+/// Describe which backups a cleanup removes, e.g. "beyond the 10 most recent and older than 90 days"
+pub fn cleanup_criteria(keep: usize, keep_days: Option<u32>) -> String {
+    match keep_days {
+        Some(days) => format!(
+            "beyond the {} most recent and older than {}",
+            keep,
+            format_day_count(days)
+        ),
+        None => format!("beyond the {} most recent", keep),
+    }
+}
+
+/// Format a day count as "1 day" / "21 days"
+pub fn format_day_count(days: u32) -> String {
+    format!("{} {}", days, pluralize(days as usize, "day", "days"))
+}
+
 /// Display cleanup success message
 pub fn display_backup_clean_success(result: &CleanResult) {
     let file_word = pluralize(result.deleted_count, "backup", "backups");
-    println!(
+    outln!(
         "{} Deleted {} {} (freed {})",
-        style("✅").green().bold(),
+        style(SUCCESS).green().bold(),
         style(result.deleted_count).cyan(),
         file_word,
         style(format_bytes(result.bytes_freed)).cyan()
@@ -727,29 +1748,30 @@ pub fn display_backup_clean_success(result: &CleanResult) {
 }
 
 /// Display cleanup dry-run header and footer (styled like branch clean)
-pub fn display_backup_clean_dry_run(count: usize, total_size: u64) {
+pub fn display_backup_clean_dry_run(count: usize, total_size: u64, criteria: &str) {
     let file_word = pluralize(count, "backup", "backups");
-    println!(
+    outln!(
         "{}",
         style("[DRY RUN] No backups will be deleted.")
             .yellow()
             .bold()
     );
-    println!();
-    println!(
-        "{} Would delete {} {} ({})",
-        style("ℹ️").blue(),
+    outln!();
+    outln!(
+        "{} Would delete {} {} ({}) {}",
+        style(INFO).blue(),
         style(count).cyan(),
         file_word,
-        style(format_bytes(total_size)).cyan()
+        style(format_bytes(total_size)).cyan(),
+        criteria
     );
 }
 
 /// Display message when no backups found for cleanup
 pub fn display_no_backups_for_repo(repo_name: &str) {
-    println!(
+    outln!(
         "{} No backups found for repository '{}'",
-        style("ℹ️").blue(),
+        style(INFO).blue(),
         repo_name
     );
 }
@@ -758,49 +1780,121 @@ pub fn display_no_backups_for_repo(repo_name: &str) {
 pub fn display_backup_stats(stats: &BackupStats) {
     if stats.repos.is_empty() {
         info("No backups found.");
-        println!();
-        println!(
+        outln!();
+        outln!(
             "  {} Backups are created automatically when running 'deadbranch clean'.",
-            style("↪").dim()
+            style(FOLLOW_UP).dim()
         );
         return;
     }
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+    let mut table = new_table();
 
     table.set_header(vec![
         Cell::new("#").add_attribute(Attribute::Bold),
         Cell::new("Repository").add_attribute(Attribute::Bold),
         Cell::new("Backups").add_attribute(Attribute::Bold),
         Cell::new("Size").add_attribute(Attribute::Bold),
+        Cell::new("Repository Status").add_attribute(Attribute::Bold),
     ]);
 
     for (i, repo) in stats.repos.iter().enumerate() {
+        let status = match repo.status {
+            RepoStatus::Present => Cell::new("ok").fg(Color::Green),
+            RepoStatus::Orphaned => Cell::new(ALERT.mark("orphaned")).fg(Color::Red),
+            RepoStatus::Unknown => Cell::new("unknown").fg(Color::DarkGrey),
+        };
         table.add_row(vec![
             Cell::new((i + 1).to_string()).fg(Color::DarkGrey),
             Cell::new(&repo.repo_name).fg(Color::Yellow),
             Cell::new(repo.backup_count.to_string()).fg(Color::Cyan),
             Cell::new(format_bytes(repo.total_bytes)).fg(Color::DarkGrey),
+            status,
         ]);
     }
 
-    println!("\n{}", style("Backup storage statistics:").bold());
-    println!(
+    outln!("\n{}", style("Backup storage statistics:").bold());
+    outln!(
         "{} {}",
         style("Location:").dim(),
         style(stats.backups_dir.display()).dim()
     );
-    println!("{table}");
+    outln!("{table}");
 
-    println!(
+    outln!(
         "{} {} {}, {}",
         style("Total:").dim(),
         style(stats.total_backups()).cyan(),
         pluralize(stats.total_backups(), "backup", "backups"),
         style(format_bytes(stats.total_bytes())).cyan()
     );
-    println!();
+
+    let orphaned: Vec<_> = stats.orphaned_repos().collect();
+    if !orphaned.is_empty() {
+        outln!(
+            "{} {}",
+            style("Reclaimable from orphaned repos:").dim(),
+            style(format_bytes(stats.orphaned_bytes())).yellow()
+        );
+        outln!();
+        outln!(
+            "{}",
+            style("These repositories no longer exist on disk. To remove their backups:").dim()
+        );
+        for repo in orphaned {
+            outln!(
+                "  {}",
+                style(format!(
+                    "deadbranch backup clean --repo {} --keep 0",
+                    repo.repo_name
+                ))
+                .dim()
+            );
+        }
+    }
+    outln!();
+}
+
+/// Display `audit`: one row per branch, one column per release branch
+pub fn display_release_audit(rows: &[(Branch, Vec<bool>)], releases: &[String]) {
+    let mut table = new_table();
+    let mut header = vec![
+        Cell::new("Branch").add_attribute(Attribute::Bold),
+        Cell::new("Age").add_attribute(Attribute::Bold),
+    ];
+    header.extend(
+        releases
+            .iter()
+            .map(|release| Cell::new(release).add_attribute(Attribute::Bold)),
+    );
+    table.set_header(header);
+
+    for (branch, contained) in rows {
+        let mut cells = vec![
+            Cell::new(&branch.name),
+            Cell::new(branch.format_age()).fg(age_color(branch.age_days)),
+        ];
+        cells.extend(contained.iter().map(|&yes| {
+            if yes {
+                Cell::new(CHECK.mark("yes")).fg(Color::Green)
+            } else {
+                Cell::new(CROSS.mark("no")).fg(Color::DarkGrey)
+            }
+        }));
+        table.add_row(cells);
+    }
+    outln!("{}", table);
+
+    let released = rows
+        .iter()
+        .filter(|(_, contained)| contained.contains(&true))
+        .count();
+    outln!(
+        "{} of {} {} contained in at least one release branch",
+        style(released).cyan(),
+        rows.len(),
+        pluralize(rows.len(), "branch is", "branches are")
+    );
 }
 
 /// Display repository branch statistics and age distribution in tables
@@ -810,8 +1904,7 @@ pub fn display_repo_stats(stats: &RepoStats) {
         return;
     }
 
-    let mut table = Table::new();
-    table.load_preset(UTF8_FULL);
+    let mut table = new_table();
     table.set_header(vec![
         Cell::new("Category").add_attribute(Attribute::Bold),
         Cell::new("Total").add_attribute(Attribute::Bold),
@@ -838,7 +1931,13 @@ pub fn display_repo_stats(stats: &RepoStats) {
         Cell::new(stats.unmerged_remote.to_string()).fg(Color::Yellow),
     ]);
     table.add_row(vec![
-        Cell::new(format!("Stale (>{}d)", stats.threshold_days)),
+        Cell::new(match stats.remote_threshold_days {
+            Some(remote) => format!(
+                "Stale (local >{}d, remote >{}d)",
+                stats.threshold_days, remote
+            ),
+            None => format!("Stale (>{}d)", stats.threshold_days),
+        }),
         Cell::new(stats.stale.to_string()).fg(Color::DarkGrey),
         Cell::new(stats.stale_local.to_string()).fg(Color::DarkGrey),
         Cell::new(stats.stale_remote.to_string()).fg(Color::DarkGrey),
@@ -850,11 +1949,10 @@ pub fn display_repo_stats(stats: &RepoStats) {
         Cell::new(stats.safe_remote.to_string()).fg(Color::Cyan),
     ]);
 
-    println!("\n{}", style("Repository Statistics:").bold());
-    println!("{table}");
+    outln!("\n{}", style("Repository Statistics:").bold());
+    outln!("{table}");
 
-    let mut age_table = Table::new();
-    age_table.load_preset(UTF8_FULL);
+    let mut age_table = new_table();
     age_table.set_header(vec![
         Cell::new("Age Range").add_attribute(Attribute::Bold),
         Cell::new("Count").add_attribute(Attribute::Bold),
@@ -871,11 +1969,13 @@ pub fn display_repo_stats(stats: &RepoStats) {
     ];
 
     for (label, count, bucket_min) in buckets {
-        let is_stale = *bucket_min >= stats.threshold_days;
-        let status_cell = if is_stale {
-            Cell::new("stale").fg(Color::Yellow)
-        } else {
-            Cell::new("fresh").fg(Color::Green)
+        let stale_local = *bucket_min >= stats.threshold_for(false);
+        let stale_remote = *bucket_min >= stats.threshold_for(true);
+        let status_cell = match (stale_local, stale_remote) {
+            (true, true) => Cell::new("stale").fg(Color::Yellow),
+            (true, false) => Cell::new("stale (local)").fg(Color::Yellow),
+            (false, true) => Cell::new("stale (remote)").fg(Color::Yellow),
+            (false, false) => Cell::new("fresh").fg(Color::Green),
         };
         age_table.add_row(vec![
             Cell::new(*label),
@@ -884,19 +1984,155 @@ pub fn display_repo_stats(stats: &RepoStats) {
         ]);
     }
 
-    println!("\n{}", style("Age Distribution:").bold());
-    println!("{age_table}");
+    outln!("\n{}", style("Age Distribution:").bold());
+    outln!("{age_table}");
 
     if stats.safe_to_delete > 0 {
-        println!();
-        println!(
+        outln!();
+        outln!(
             "{} Run '{}' to remove {} safe-to-delete {}",
-            style("💡"),
+            style(HINT),
             style("deadbranch clean").cyan(),
             style(stats.safe_to_delete).cyan(),
             pluralize_branch(stats.safe_to_delete)
         );
     }
 
-    println!();
+    outln!();
+}
+
+/// Format a phase duration for the `--timing` table, e.g. "12ms" or "1.42s"
+fn format_phase_time(elapsed: Duration) -> String {
+    if elapsed < Duration::from_secs(1) {
+        format!("{}ms", elapsed.as_millis())
+    } else {
+        format!("{:.2}s", elapsed.as_secs_f64())
+    }
+}
+
+/// Print the `--timing` table to stderr, so it never mixes with JSON or CSV output
+pub fn display_timing(phases: &[PhaseTiming], total: Duration) {
+    let mut table = new_table();
+    table.set_header(vec![
+        Cell::new("Phase").add_attribute(Attribute::Bold),
+        Cell::new("Time").add_attribute(Attribute::Bold),
+        Cell::new("Git calls").add_attribute(Attribute::Bold),
+    ]);
+    for timing in phases {
+        table.add_row(vec![
+            Cell::new(timing.phase.label()),
+            Cell::new(format_phase_time(timing.elapsed)).fg(Color::Cyan),
+            Cell::new(timing.subprocesses.to_string()).fg(Color::Yellow),
+        ]);
+    }
+    table.add_row(vec![
+        Cell::new("total").add_attribute(Attribute::Bold),
+        Cell::new(format_phase_time(total)).add_attribute(Attribute::Bold),
+        Cell::new(subprocess_count().to_string()).add_attribute(Attribute::Bold),
+    ]);
+
+    eprintln!("\n{}", style("Timing:").bold());
+    eprintln!("{}", table);
+}
+
+/// Longest git command line shown as the slowest call
+const SLOWEST_CALL_WIDTH: usize = 120;
+
+/// Where a run's time went, for `--verbose` and `--timing`: git calls per
+/// command and the slowest one, plus the phases unless the `--timing` table
+/// already shows them. Goes to stderr like the table.
+pub fn display_run_summary(summary: &Summary, with_phases: bool) {
+    let detail = |message: String| eprintln!("{} {}", style(DETAIL).dim(), style(message).dim());
+    let total = Duration::from_secs_f64(summary.total_ms / 1000.0);
+    let commands: Vec<String> = summary
+        .commands
+        .iter()
+        .map(|c| {
+            format!(
+                "{} {} ({})",
+                c.command,
+                c.calls,
+                format_phase_time(c.elapsed)
+            )
+        })
+        .collect();
+    detail(format!(
+        "Spawned {} git {} in {}{}",
+        summary.git_calls,
+        pluralize(summary.git_calls, "process", "processes"),
+        format_phase_time(total),
+        if commands.is_empty() {
+            String::new()
+        } else {
+            format!(": {}", commands.join(", "))
+        }
+    ));
+    if with_phases && !summary.phases.is_empty() {
+        let phases: Vec<String> = summary
+            .phases
+            .iter()
+            .map(|p| format!("{} {}", p.phase.label(), format_phase_time(p.elapsed)))
+            .collect();
+        detail(format!("Phases: {}", phases.join(", ")));
+    }
+    if let Some(slowest) = &summary.slowest {
+        let mut line = format!("git {}", slowest.args.join(" "));
+        if line.chars().count() > SLOWEST_CALL_WIDTH {
+            line = line
+                .chars()
+                .take(SLOWEST_CALL_WIDTH - 1)
+                .collect::<String>()
+                + "…";
+        }
+        detail(format!(
+            "Slowest git call ({}): {}",
+            format_phase_time(slowest.elapsed),
+            line
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn phase_times_switch_to_seconds() {
+        assert_eq!(format_phase_time(Duration::from_millis(12)), "12ms");
+        assert_eq!(format_phase_time(Duration::from_millis(1420)), "1.42s");
+    }
+
+    #[test]
+    fn subject_width_fills_the_rest_of_the_line() {
+        // │ 1 │ feature │ = 1 + (1 + 3) + (7 + 3), then "│ " + subject + " │"
+        assert_eq!(subject_width(40, &[1, 7]), 40 - 15 - 3);
+        assert_eq!(subject_width(20, &[1, 7]), 10);
+    }
+
+    #[test]
+    fn file_url_encodes_paths() {
+        assert_eq!(
+            file_url("/home/me/.deadbranch/backups/my repo/backup.txt"),
+            "file:///home/me/.deadbranch/backups/my%20repo/backup.txt"
+        );
+        assert_eq!(
+            file_url(r"C:\Users\me\backup#1.txt"),
+            "file:///C:/Users/me/backup%231.txt"
+        );
+        assert_eq!(
+            hyperlink("file:///tmp/b.txt", "/tmp/b.txt"),
+            "\x1b]8;;file:///tmp/b.txt\x1b\\/tmp/b.txt\x1b]8;;\x1b\\"
+        );
+    }
+
+    #[test]
+    fn answer_within_gives_up_on_slow_prompts() {
+        assert_eq!(answer_within(Duration::from_secs(5), || true), Some(true));
+
+        let slow = answer_within(Duration::from_millis(20), || {
+            std::thread::sleep(Duration::from_secs(2));
+            true
+        });
+        assert_eq!(slow, None);
+    }
 }
@@ -142,13 +142,16 @@ fn get_repo_name(repo_path: &std::path::Path) -> String {
         .to_string()
 }
 
-/// Get the backup directory for a repo
+/// Get the backup directory for a repo, resolved the way deadbranch does it
 fn get_backup_dir(repo_name: &str) -> PathBuf {
-    let home = std::env::var("HOME")
-        .or_else(|_| std::env::var("USERPROFILE"))
-        .expect("HOME or USERPROFILE not set");
-    PathBuf::from(home)
-        .join(".deadbranch")
+    std::env::var_os("DEADBRANCH_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| {
+            dirs::home_dir()
+                .expect("Could not determine home directory")
+                .join(".deadbranch")
+        })
         .join("backups")
         .join(repo_name)
 }
@@ -374,6 +377,384 @@ fn test_backup_list_repo_not_found() {
         .stdout(predicate::str::contains("No backups found"));
 }
 
+/// Write a backup file directly into a repo's backup directory
+fn write_backup_file(repo_name: &str, filename: &str, created: &str, branches: &[&str]) {
+    let backup_dir = get_backup_dir(repo_name);
+    fs::create_dir_all(&backup_dir).unwrap();
+
+    let mut content = format!(
+        "# deadbranch backup\n# Created: {}\n# Repository: {}\n\n",
+        created, repo_name
+    );
+    for name in branches {
+        content.push_str(&format!("# {}\ngit branch {} abc1234\n\n", name, name));
+    }
+    fs::write(backup_dir.join(filename), content).unwrap();
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_list_filters_compose() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    write_backup_file(
+        &repo_name,
+        "backup-20240901-120000.txt",
+        "2024-09-01T12:00:00+00:00",
+        &["feature/login", "wip/spike"],
+    );
+    write_backup_file(
+        &repo_name,
+        "backup-20240701-120000.txt",
+        "2024-07-01T12:00:00+00:00",
+        &["feature/search"],
+    );
+    write_backup_file(
+        &repo_name,
+        "backup-20240501-120000.txt",
+        "2024-05-01T12:00:00+00:00",
+        &["feature/old"],
+    );
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "list",
+            "--repo",
+            &repo_name,
+            "--since",
+            "2024-06-01",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backup-20240901-120000.txt"))
+        .stdout(predicate::str::contains("backup-20240701-120000.txt"))
+        .stdout(predicate::str::contains("backup-20240501-120000.txt").not());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "list",
+            "--repo",
+            &repo_name,
+            "--contains",
+            "feature/*",
+            "--limit",
+            "2",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backup-20240901-120000.txt"))
+        .stdout(predicate::str::contains("backup-20240701-120000.txt"))
+        .stdout(predicate::str::contains("backup-20240501-120000.txt").not());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "list",
+            "--repo",
+            &repo_name,
+            "--contains",
+            "wip/*",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backup-20240901-120000.txt"))
+        .stdout(predicate::str::contains("backup-20240701-120000.txt").not());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "list",
+            "--repo",
+            &repo_name,
+            "--contains",
+            "hotfix/*",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("match the given filters"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_list_duration_bounds_and_sort() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    let days_ago = |days: i64| (chrono::Utc::now() - chrono::Duration::days(days)).to_rfc3339();
+    write_backup_file(
+        &repo_name,
+        "backup-recent.txt",
+        &days_ago(3),
+        &["feature/a"],
+    );
+    write_backup_file(
+        &repo_name,
+        "backup-older.txt",
+        &days_ago(60),
+        &["feature/b", "feature/c", "feature/d"],
+    );
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--repo", &repo_name, "--since", "1w"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backup-recent.txt"))
+        .stdout(predicate::str::contains("backup-older.txt").not());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--repo", &repo_name, "--before", "1m"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("backup-older.txt"))
+        .stdout(predicate::str::contains("backup-recent.txt").not());
+
+    let output = Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--repo", &repo_name, "--sort", "branches"])
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let older = stdout.find("backup-older.txt").unwrap();
+    let recent = stdout.find("backup-recent.txt").unwrap();
+    assert!(
+        older < recent,
+        "most branches should come first:\n{}",
+        stdout
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_list_rejects_invalid_since() {
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--since", "last-tuesday"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid --since date"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_migrate_rewrites_legacy_backups() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    write_backup_file(
+        &repo_name,
+        "backup-20240901-120000.txt",
+        "2024-09-01T12:00:00+00:00",
+        &["legacy-branch"],
+    );
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "migrate", "--repo", &repo_name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Migrated 1 backup"));
+
+    let backup_dir = get_backup_dir(&repo_name);
+    let migrated = fs::read_to_string(backup_dir.join("backup-20240901-120000.txt")).unwrap();
+    assert!(migrated.contains("# Format: 3"));
+    assert!(backup_dir.join("backup-20240901-120000.txt.bak").exists());
+
+    // The .bak copy is not listed as a backup, and a second run has nothing to do
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "migrate", "--repo", &repo_name])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("already use the current format"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_verify_detects_truncated_backup() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "checksummed");
+    make_branch_old(repo.path(), "checksummed", 45);
+    merge_branch(repo.path(), "checksummed");
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    let backup_dir = get_backup_dir(&repo_name);
+    let backup_path = fs::read_dir(&backup_dir)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let content = fs::read_to_string(&backup_path).unwrap();
+    assert!(content.lines().last().unwrap().starts_with("# SHA256: "));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "verify", "--current"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("valid"))
+        .stdout(predicate::str::contains("All 1 backup verified"));
+
+    // Simulate a full disk cutting the file short
+    fs::write(
+        &backup_path,
+        &content[..content.find("git branch").unwrap()],
+    )
+    .unwrap();
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "verify", "--current"])
+        .current_dir(&repo)
+        .assert()
+        .code(1)
+        .stdout(predicate::str::contains("truncated"))
+        .stderr(predicate::str::contains(
+            "1 of 1 backup failed verification",
+        ));
+
+    // The failure status doesn't cut off the --timing summary
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "verify", "--current", "--timing"])
+        .current_dir(&repo)
+        .assert()
+        .code(1)
+        .stderr(predicate::str::contains("Timing:"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "checksummed"])
+        .current_dir(&repo)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("corrupted"))
+        .stdout(predicate::str::contains("appears truncated"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_rejects_newer_format() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    let backup_dir = get_backup_dir(&repo_name);
+    fs::create_dir_all(&backup_dir).unwrap();
+    fs::write(
+        backup_dir.join("backup-20240901-120000.txt"),
+        "# deadbranch backup\n# Format: 99\n\ngit branch future abc1234\n",
+    )
+    .unwrap();
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "future"])
+        .current_dir(&repo)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("format version 99"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_clean_label_is_recorded_and_searchable() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "labelled-branch");
+    make_branch_old(repo.path(), "labelled-branch", 45);
+    merge_branch(repo.path(), "labelled-branch");
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--label", "pre-migration purge"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    // An older, unlabelled backup
+    write_backup_file(
+        &repo_name,
+        "backup-20240901-120000.txt",
+        "2024-09-01T12:00:00+00:00",
+        &["legacy-branch"],
+    );
+
+    let backup_dir = get_backup_dir(&repo_name);
+    let labelled = fs::read_dir(&backup_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .find(|p| !p.ends_with("backup-20240901-120000.txt"))
+        .unwrap();
+    let content = fs::read_to_string(&labelled).unwrap();
+    assert!(content.contains("# Label: pre-migration purge"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--current"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Label"))
+        .stdout(predicate::str::contains("pre-migration purge"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--current", "--label", "MIGRATION"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("pre-migration purge"))
+        .stdout(predicate::str::contains("backup-20240901-120000.txt").not());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "show"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Label: pre-migration purge"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_clean_label_rejects_newlines() {
+    let repo = create_test_repo();
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--label", "first line\nsecond line"])
+        .current_dir(&repo)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("single line"));
+}
+
 // ============================================================================
 // Tests for flag validation
 // ============================================================================
@@ -429,6 +810,53 @@ fn test_clean_creates_backup() {
     assert_eq!(backup_files.len(), 1);
 }
 
+#[test]
+#[allow(deprecated)]
+fn test_clean_prints_recovery_hint() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    for name in ["hint-a", "hint-b"] {
+        create_branch(repo.path(), name);
+        make_branch_old(repo.path(), name, 45);
+        merge_branch(repo.path(), name);
+    }
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--local"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "To undo: deadbranch backup restore <branch> --from backup-",
+        ));
+
+    create_branch(repo.path(), "hint-c");
+    make_branch_old(repo.path(), "hint-c", 45);
+    merge_branch(repo.path(), "hint-c");
+    let sha = StdCommand::new("git")
+        .args(["rev-parse", "--short", "hint-c"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    let sha = String::from_utf8_lossy(&sha.stdout).trim().to_string();
+
+    // Progress lines record the SHA and age; the backup path is plain when piped
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--local", "--no-hints"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("hint-c ({}, 45d)", sha)))
+        .stdout(predicate::str::contains("Backup: /"))
+        .stdout(predicate::str::contains("\x1b]8;;").not())
+        .stdout(predicate::str::contains("Deleted 1 local branch"))
+        .stdout(predicate::str::contains("backup restore").not());
+}
+
 #[test]
 #[allow(deprecated)]
 fn test_backup_contains_branch_restore_command() {
@@ -588,18 +1016,70 @@ fn test_backup_restore_basic() {
 
 #[test]
 #[allow(deprecated)]
-fn test_backup_restore_requires_git_repo() {
-    let temp_dir = TempDir::new().unwrap();
+fn test_backup_and_restore_ignore_same_named_tag_and_file() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "release");
+    make_branch_old(repo.path(), "release", 45);
+    let branch_sha = get_branch_sha(repo.path(), "refs/heads/release");
+    merge_branch(repo.path(), "release");
+
+    // A tag and an untracked file both called "release", the tag on another commit
+    StdCommand::new("git")
+        .args(["tag", "release", "main~1"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    fs::write(repo.path().join("release"), "not a ref").unwrap();
+    assert_ne!(get_branch_sha(repo.path(), "refs/tags/release"), branch_sha);
 
-    // Restore outside a git repo should fail
     Command::cargo_bin("deadbranch")
         .unwrap()
-        .args(["backup", "restore", "some-branch"])
-        .current_dir(&temp_dir)
+        .args(["clean", "-y"])
+        .current_dir(&repo)
         .assert()
-        .failure()
-        .code(1);
-}
+        .success();
+    assert!(!branch_exists(repo.path(), "release"));
+
+    let backup_dir = get_backup_dir(&repo_name);
+    let backup = fs::read_dir(&backup_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_name().to_string_lossy().starts_with("backup-"))
+        .expect("backup file should exist");
+    let content = fs::read_to_string(backup.path()).unwrap();
+    assert!(content.contains(&format!("git branch release {}", branch_sha)));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "release"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Restored branch"));
+
+    assert_eq!(
+        get_branch_sha(repo.path(), "refs/heads/release"),
+        branch_sha
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_requires_git_repo() {
+    let temp_dir = TempDir::new().unwrap();
+
+    // Restore outside a git repo should fail
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "some-branch"])
+        .current_dir(&temp_dir)
+        .assert()
+        .failure()
+        .code(1);
+}
 
 #[test]
 #[allow(deprecated)]
@@ -816,6 +1296,173 @@ fn test_backup_restore_from_specific_backup() {
     assert!(branch_exists(repo.path(), "first-backup-branch"));
 }
 
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_from_index_and_latest() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    for branch_name in ["indexed-older", "indexed-newer"] {
+        create_branch(repo.path(), branch_name);
+        make_branch_old(repo.path(), branch_name, 45);
+        merge_branch(repo.path(), branch_name);
+
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(["clean", "-y"])
+            .current_dir(&repo)
+            .assert()
+            .success();
+
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+    }
+
+    // #2 in `backup list --current` is the older backup
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "indexed-older", "--from", "2"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+    assert!(branch_exists(repo.path(), "indexed-older"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "indexed-newer", "--from", "latest"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+    assert!(branch_exists(repo.path(), "indexed-newer"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "indexed-missing", "--from", "7"])
+        .current_dir(&repo)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No backup #7"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_from_other_repo_requires_flag() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "foreign-branch");
+    make_branch_old(repo.path(), "foreign-branch", 45);
+    merge_branch(repo.path(), "foreign-branch");
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    let backup_file = fs::read_dir(get_backup_dir(&repo_name))
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .next()
+        .unwrap()
+        .path();
+
+    // A clone has the same commits but is a different repository
+    let clone_parent = TempDir::new().unwrap();
+    let clone = clone_parent.path().join("clone");
+    StdCommand::new("git")
+        .args(["clone", "--quiet"])
+        .arg(repo.path())
+        .arg(&clone)
+        .output()
+        .unwrap();
+    let _clone_guard = BackupCleanupGuard::new("clone".to_string());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "foreign-branch", "--from"])
+        .arg(&backup_file)
+        .current_dir(&clone)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("not the current repository"));
+    assert!(!branch_exists(&clone, "foreign-branch"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "restore",
+            "foreign-branch",
+            "--ignore-repo-mismatch",
+            "--from",
+        ])
+        .arg(&backup_file)
+        .current_dir(&clone)
+        .assert()
+        .success();
+    assert!(branch_exists(&clone, "foreign-branch"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_with_other_repo_name_requires_flag() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "shared-name");
+    make_branch_old(repo.path(), "shared-name", 45);
+    merge_branch(repo.path(), "shared-name");
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    let clone_parent = TempDir::new().unwrap();
+    let clone = clone_parent.path().join("clone");
+    StdCommand::new("git")
+        .args(["clone", "--quiet"])
+        .arg(repo.path())
+        .arg(&clone)
+        .output()
+        .unwrap();
+    let _clone_guard = BackupCleanupGuard::new("clone".to_string());
+
+    // --repo names another repository than the one we're in
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "shared-name", "--repo", &repo_name])
+        .current_dir(&clone)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Backup belongs to '{}', not the current repository 'clone'",
+            repo_name
+        )));
+    assert!(!branch_exists(&clone, "shared-name"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "restore",
+            "shared-name",
+            "--repo",
+            &repo_name,
+            "--allow-cross-repo",
+        ])
+        .current_dir(&clone)
+        .assert()
+        .success();
+    assert!(branch_exists(&clone, "shared-name"));
+}
+
 #[test]
 #[allow(deprecated)]
 fn test_backup_restore_shows_short_sha() {
@@ -848,6 +1495,117 @@ fn test_backup_restore_shows_short_sha() {
     assert!(output.contains("at commit"));
 }
 
+/// Helper to read a git config value (None if unset)
+fn git_config_get(repo_dir: &std::path::Path, key: &str) -> Option<String> {
+    let output = StdCommand::new("git")
+        .args(["config", "--get", key])
+        .current_dir(repo_dir)
+        .output()
+        .unwrap();
+    if output.status.success() {
+        Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+    } else {
+        None
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_round_trips_branch_description() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "described-branch");
+    make_branch_old(repo.path(), "described-branch", 45);
+    merge_branch(repo.path(), "described-branch");
+    StdCommand::new("git")
+        .args([
+            "config",
+            "branch.described-branch.description",
+            "Rework the login flow",
+        ])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    // git drops branch.<name>.* config along with the branch
+    assert!(git_config_get(repo.path(), "branch.described-branch.description").is_none());
+
+    // backup show surfaces the recorded description
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "show"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("described-branch"))
+        .stdout(predicate::str::contains("Rework the login flow"));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "described-branch"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Restored branch config: description",
+        ));
+
+    assert_eq!(
+        git_config_get(repo.path(), "branch.described-branch.description").as_deref(),
+        Some("Rework the login flow")
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_skips_upstream_that_no_longer_exists() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "tracking-branch");
+    make_branch_old(repo.path(), "tracking-branch", 45);
+    merge_branch(repo.path(), "tracking-branch");
+    for (key, value) in [
+        ("branch.tracking-branch.remote", "gone-remote"),
+        ("branch.tracking-branch.merge", "refs/heads/tracking-branch"),
+    ] {
+        StdCommand::new("git")
+            .args(["config", key, value])
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+    }
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--local"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "restore", "tracking-branch"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Skipped branch config"));
+
+    assert!(branch_exists(repo.path(), "tracking-branch"));
+    assert!(git_config_get(repo.path(), "branch.tracking-branch.remote").is_none());
+    assert!(git_config_get(repo.path(), "branch.tracking-branch.merge").is_none());
+}
+
 // ============================================================================
 // Tests for `deadbranch backup clean`
 // ============================================================================
@@ -971,6 +1729,127 @@ fn test_backup_clean_dry_run() {
     assert_eq!(backup_count_after, 3);
 }
 
+#[test]
+#[allow(deprecated)]
+fn test_backup_clean_keep_days_requires_both_criteria() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    let recent = chrono::Utc::now() - chrono::Duration::days(5);
+    let recent_file = recent.format("backup-%Y%m%d-%H%M%S.txt").to_string();
+    write_backup_file(
+        &repo_name,
+        "backup-20200101-120000.txt",
+        "2020-01-01T12:00:00+00:00",
+        &["ancient"],
+    );
+    write_backup_file(
+        &repo_name,
+        "backup-20200201-120000.txt",
+        "2020-02-01T12:00:00+00:00",
+        &["old"],
+    );
+    write_backup_file(&repo_name, &recent_file, &recent.to_rfc3339(), &["recent"]);
+
+    // With --keep 0 the age window alone protects the recent backup
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "clean",
+            "--current",
+            "--keep",
+            "0",
+            "--keep-days",
+            "90",
+            "--dry-run",
+        ])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "keeping 0 most recent and any from the last 90 days",
+        ))
+        .stdout(predicate::str::contains("backup-20200101-120000.txt"))
+        .stdout(predicate::str::contains("backup-20200201-120000.txt"))
+        .stdout(predicate::str::contains(recent_file.as_str()).not())
+        .stdout(predicate::str::contains("Would delete 2 backups"))
+        .stdout(predicate::str::contains(
+            "beyond the 0 most recent and older than 90 days",
+        ));
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "clean",
+            "--current",
+            "--keep",
+            "2",
+            "--keep-days",
+            "90",
+            "-y",
+        ])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Deleted 1 backup"));
+
+    // "old" is older than 90 days but still one of the 2 most recent
+    let backup_dir = get_backup_dir(&repo_name);
+    assert!(!backup_dir.join("backup-20200101-120000.txt").exists());
+    assert!(backup_dir.join("backup-20200201-120000.txt").exists());
+    assert!(backup_dir.join(&recent_file).exists());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_clean_interactive_cancel_deletes_nothing() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    write_backup_file(
+        &repo_name,
+        "backup-20200101-120000.txt",
+        "2020-01-01T12:00:00+00:00",
+        &["one"],
+    );
+    write_backup_file(
+        &repo_name,
+        "backup-20200201-120000.txt",
+        "2020-02-01T12:00:00+00:00",
+        &["two"],
+    );
+
+    // Without a terminal the checklist cannot be shown, which counts as cancelling
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "clean",
+            "--current",
+            "--keep",
+            "0",
+            "--interactive",
+        ])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Cancelled"));
+
+    assert_eq!(fs::read_dir(get_backup_dir(&repo_name)).unwrap().count(), 2);
+
+    // The checklist always asks; --yes cannot skip it
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "clean", "--current", "--interactive", "--yes"])
+        .current_dir(&repo)
+        .assert()
+        .failure();
+}
+
 #[test]
 #[allow(deprecated)]
 fn test_backup_clean_with_yes_flag() {
@@ -1240,15 +2119,14 @@ fn test_backup_stats_shows_repo_and_count() {
 
 #[test]
 #[allow(deprecated)]
-fn test_backup_stats_shows_row_number() {
+fn test_backup_stats_flags_orphaned_repos() {
     let repo = create_test_repo();
     let repo_name = get_repo_name(repo.path());
     let _guard = BackupCleanupGuard::new(repo_name.clone());
 
-    // Create a backup
-    create_branch(repo.path(), "stats-row-number-branch");
-    make_branch_old(repo.path(), "stats-row-number-branch", 45);
-    merge_branch(repo.path(), "stats-row-number-branch");
+    create_branch(repo.path(), "orphan-branch");
+    make_branch_old(repo.path(), "orphan-branch", 45);
+    merge_branch(repo.path(), "orphan-branch");
 
     Command::cargo_bin("deadbranch")
         .unwrap()
@@ -1257,7 +2135,75 @@ fn test_backup_stats_shows_row_number() {
         .assert()
         .success();
 
-    // Output should contain "1" as the row number in the # column
+    let backup_dir = get_backup_dir(&repo_name);
+    let backup_file = fs::read_dir(&backup_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .next()
+        .unwrap()
+        .path();
+    let content = fs::read_to_string(&backup_file).unwrap();
+    assert!(content.contains("# Repository path:"));
+
+    // Remove the repository itself; its backups stay behind
+    drop(repo);
+
+    let hint = format!("deadbranch backup clean --repo {} --keep 0", repo_name);
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("orphaned"))
+        .stdout(predicate::str::contains("Reclaimable from orphaned repos:"))
+        .stdout(predicate::str::contains(hint));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_stats_marks_legacy_backups_unknown() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    // Legacy backups carry no repository path
+    write_backup_file(
+        &repo_name,
+        "backup-20240901-120000.txt",
+        "2024-09-01T12:00:00+00:00",
+        &["legacy-branch"],
+    );
+
+    let hint = format!("deadbranch backup clean --repo {} --keep 0", repo_name);
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("unknown"))
+        .stdout(predicate::str::contains(hint).not());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_stats_shows_row_number() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    // Create a backup
+    create_branch(repo.path(), "stats-row-number-branch");
+    make_branch_old(repo.path(), "stats-row-number-branch", 45);
+    merge_branch(repo.path(), "stats-row-number-branch");
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    // Output should contain "1" as the row number in the # column
     Command::cargo_bin("deadbranch")
         .unwrap()
         .args(["backup", "stats"])
@@ -1266,3 +2212,760 @@ fn test_backup_stats_shows_row_number() {
         .stdout(predicate::str::contains("1"))
         .stdout(predicate::str::contains(&repo_name));
 }
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_list_and_stats_json() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    create_branch(repo.path(), "json-branch");
+    make_branch_old(repo.path(), "json-branch", 45);
+    merge_branch(repo.path(), "json-branch");
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--local"])
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    let output = Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list", "--repo", &repo_name, "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let list: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let backups = list.as_array().unwrap();
+    assert_eq!(backups.len(), 1);
+    let backup = &backups[0];
+    assert_eq!(backup["repo"], repo_name.as_str());
+    assert_eq!(backup["branch_count"], 1);
+    let filename = backup["filename"].as_str().unwrap();
+    assert!(filename.starts_with("backup-"));
+    assert!(backup["path"].as_str().unwrap().ends_with(filename));
+    assert!(chrono::DateTime::parse_from_rfc3339(backup["timestamp"].as_str().unwrap()).is_ok());
+
+    let output = Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "stats", "--format", "json"])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let stats: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let repo_stats = stats["repos"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|r| r["repo"] == repo_name.as_str())
+        .expect("repository missing from stats");
+    assert_eq!(repo_stats["backup_count"], 1);
+    assert!(repo_stats["total_bytes"].as_u64().unwrap() > 0);
+    assert!(stats["total_backups"].as_u64().unwrap() >= 1);
+    assert!(stats["total_bytes"].as_u64().unwrap() >= repo_stats["total_bytes"].as_u64().unwrap());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_clean_dry_run_json() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let _guard = BackupCleanupGuard::new(repo_name.clone());
+
+    for name in ["json-clean-a", "json-clean-b", "json-clean-c"] {
+        create_branch(repo.path(), name);
+        make_branch_old(repo.path(), name, 45);
+        merge_branch(repo.path(), name);
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(["clean", "-y", "--local"])
+            .current_dir(&repo)
+            .assert()
+            .success();
+    }
+
+    let output = Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup",
+            "clean",
+            "--repo",
+            &repo_name,
+            "--keep",
+            "1",
+            "--dry-run",
+            "--format",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let plan: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(plan["repo"], repo_name.as_str());
+    assert_eq!(plan["keep"], 1);
+    let entries = plan["to_delete"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    for entry in entries {
+        assert!(entry["filename"].as_str().unwrap().starts_with("backup-"));
+        assert_eq!(entry["branch_count"], 1);
+        assert!(entry["age"].is_string());
+    }
+    let sum: u64 = entries
+        .iter()
+        .map(|e| e["size_bytes"].as_u64().unwrap())
+        .sum();
+    assert_eq!(plan["total_bytes"].as_u64().unwrap(), sum);
+
+    // Nothing was deleted, and JSON without --dry-run is refused
+    assert_eq!(fs::read_dir(get_backup_dir(&repo_name)).unwrap().count(), 3);
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args([
+            "backup", "clean", "--repo", &repo_name, "--keep", "1", "-y", "--format", "json",
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("only available with --dry-run"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_template_renders_and_restores() {
+    let repo = create_test_repo();
+    let home = TempDir::new().unwrap();
+    create_branch(repo.path(), "old-feature");
+    make_branch_old(repo.path(), "old-feature", 45);
+    merge_branch(repo.path(), "old-feature");
+    StdCommand::new("git")
+        .args(["config", "branch.old-feature.remote", "upstream"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    let sha = String::from_utf8(
+        StdCommand::new("git")
+            .args(["rev-parse", "old-feature"])
+            .current_dir(&repo)
+            .output()
+            .unwrap()
+            .stdout,
+    )
+    .unwrap()
+    .trim()
+    .to_string();
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+
+    // A template without the marker line is refused
+    let template = home.path().join("template.sh");
+    fs::write(&template, "git branch {name} {sha}\n").unwrap();
+    deadbranch(&[
+        "config",
+        "set",
+        "backups.template",
+        template.to_str().unwrap(),
+    ])
+    .failure()
+    .stderr(predicate::str::contains("# deadbranch-entry: {name} {sha}"));
+
+    fs::write(
+        &template,
+        "set -e\n# deadbranch-entry: {name} {sha}\ngit branch {name} {sha}\ngit push {remote} {sha}:refs/heads/{name}\n",
+    )
+    .unwrap();
+    deadbranch(&[
+        "config",
+        "set",
+        "backups.template",
+        template.to_str().unwrap(),
+    ])
+    .success();
+    deadbranch(&["clean", "-y"]).success();
+
+    let backup_root = home.path().join(".deadbranch/backups");
+    let repo_dir = fs::read_dir(&backup_root).unwrap().next().unwrap().unwrap();
+    let backup = fs::read_dir(repo_dir.path())
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    let content = fs::read_to_string(backup.path()).unwrap();
+    assert!(content.contains(&format!(
+        "set -e\n# deadbranch-entry: old-feature {sha}\ngit branch old-feature {sha}\ngit push upstream {sha}:refs/heads/old-feature\n"
+    )));
+
+    deadbranch(&["backup", "list", "--current", "--format", "json"])
+        .success()
+        .stdout(predicate::str::contains("\"branch_count\": 1"));
+    deadbranch(&["backup", "restore", "old-feature"]).success();
+    let restored = StdCommand::new("git")
+        .args(["rev-parse", "refs/heads/old-feature"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert_eq!(String::from_utf8_lossy(&restored.stdout).trim(), sha);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_list_skips_oversized_and_binary_files() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let home = TempDir::new().unwrap();
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+
+    let backup_dir = home.path().join(".deadbranch/backups").join(&repo_name);
+    fs::create_dir_all(&backup_dir).unwrap();
+    fs::write(
+        backup_dir.join("backup-20240901-120000.txt"),
+        "# deadbranch backup\n# Created: 2024-09-01T12:00:00+00:00\n\ngit branch good abc1234\n",
+    )
+    .unwrap();
+    // Something copied into the backup directory by mistake
+    fs::write(
+        backup_dir.join("backup-20240902-120000.txt"),
+        b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0",
+    )
+    .unwrap();
+    let mut big = String::from("# deadbranch backup\n");
+    while big.len() <= 1024 * 1024 {
+        big.push_str("git branch filler abc1234\n");
+    }
+    fs::write(backup_dir.join("backup-20240903-120000.txt"), big).unwrap();
+
+    deadbranch(&["config", "set", "backups.max-file-size-mb", "1"]).success();
+    deadbranch(&["config", "set", "max-file-size-mb", "0"])
+        .failure()
+        .stderr(predicate::str::contains("Invalid max-file-size-mb"));
+
+    deadbranch(&["backup", "list", "--current"])
+        .success()
+        .stdout(predicate::str::contains("backup-20240901-120000.txt"))
+        .stdout(predicate::str::contains("backup-20240902-120000.txt").not())
+        .stdout(predicate::str::contains("backup-20240903-120000.txt").not())
+        .stderr(predicate::str::contains("not a text backup"))
+        .stderr(predicate::str::contains("larger than 1 MB"));
+
+    deadbranch(&["config", "set", "max-file-size-mb", ""]).success();
+    deadbranch(&["backup", "list", "--current"])
+        .success()
+        .stdout(predicate::str::contains("backup-20240903-120000.txt"));
+}
+
+#[cfg(unix)]
+#[test]
+#[allow(deprecated)]
+fn test_failed_backup_write_leaves_nothing_and_deletes_nothing() {
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let home = TempDir::new().unwrap();
+    let deadbranch_home = home.path().join(".deadbranch");
+
+    create_branch(repo.path(), "old-merged");
+    make_branch_old(repo.path(), "old-merged", 45);
+    merge_branch(repo.path(), "old-merged");
+
+    // Write the config up front; the run below can't write anything
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["config", "show"])
+        .env("DEADBRANCH_HOME", &deadbranch_home)
+        .current_dir(&repo)
+        .assert()
+        .success();
+
+    // A file size limit of zero makes every write fail, like a full disk
+    // (even for root, unlike a read-only directory)
+    let exe = assert_cmd::cargo::cargo_bin("deadbranch");
+    let output = StdCommand::new("sh")
+        .args(["-c", "trap '' XFSZ; ulimit -f 0; exec \"$0\" \"$@\""])
+        .arg(&exe)
+        .args(["clean", "--local", "-y"])
+        .env("DEADBRANCH_HOME", &deadbranch_home)
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("Failed to write"));
+
+    let branches = StdCommand::new("git")
+        .args(["branch", "--list", "old-merged"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&branches.stdout).contains("old-merged"));
+
+    let backup_dir = deadbranch_home.join("backups").join(&repo_name);
+    let leftovers: Vec<_> = fs::read_dir(&backup_dir)
+        .map(|entries| entries.map(|e| e.unwrap().file_name()).collect())
+        .unwrap_or_default();
+    assert!(leftovers.is_empty(), "left behind: {:?}", leftovers);
+}
+
+#[cfg(unix)]
+#[test]
+#[allow(deprecated)]
+fn test_clean_aborts_when_backup_dir_is_unwritable() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let repo = create_test_repo();
+    let repo_name = get_repo_name(repo.path());
+    let home = TempDir::new().unwrap();
+    let origin = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let out = StdCommand::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{:?}", out);
+    };
+    StdCommand::new("git")
+        .args(["init", "--bare", origin.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    git(&["remote", "add", "origin", origin.path().to_str().unwrap()]);
+    create_branch(repo.path(), "old-merged");
+    make_branch_old(repo.path(), "old-merged", 45);
+    merge_branch(repo.path(), "old-merged");
+    git(&["push", "origin", "main", "old-merged"]);
+    git(&["fetch", "origin"]);
+
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+    deadbranch(&["config", "show"]).success();
+
+    let backup_dir = home.path().join(".deadbranch/backups").join(&repo_name);
+    fs::create_dir_all(&backup_dir).unwrap();
+    fs::set_permissions(&backup_dir, fs::Permissions::from_mode(0o000)).unwrap();
+    if fs::write(backup_dir.join("probe"), "").is_ok() {
+        // Running as root, which ignores permissions: a file where the
+        // directory should be is just as unwritable
+        fs::remove_dir_all(&backup_dir).unwrap();
+        fs::write(&backup_dir, "").unwrap();
+    }
+
+    deadbranch(&["clean", "-y"])
+        .code(1)
+        .stderr(predicate::str::contains(
+            "Aborted before deleting any local branches",
+        ))
+        .stderr(predicate::str::contains("--no-backup"))
+        .stdout(predicate::str::contains("Deleting").not());
+
+    // The report still says what happened to each branch
+    let reports = TempDir::new().unwrap();
+    let report = reports.path().join("cleanup.txt");
+    deadbranch(&["clean", "-y", "--report", report.to_str().unwrap()]).code(1);
+    let text = fs::read_to_string(&report).unwrap();
+    assert!(text.contains("Branches: 0 deleted of 2"), "{}", text);
+    let line = text.lines().find(|l| l.contains("old-merged")).unwrap();
+    assert!(line.contains("failed: Aborted before deleting"), "{}", line);
+
+    let exists = |dir: &std::path::Path, branch: &str| {
+        StdCommand::new("git")
+            .args(["rev-parse", "--verify", &format!("refs/heads/{}", branch)])
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status
+            .success()
+    };
+    assert!(exists(repo.path(), "old-merged"));
+    assert!(exists(origin.path(), "old-merged"));
+
+    deadbranch(&["clean", "-y", "--no-backup"])
+        .success()
+        .stdout(predicate::str::contains("Not backing up 1 local branch"))
+        .stdout(predicate::str::contains("Deleted 1 local branch"))
+        .stdout(predicate::str::contains("Deleted 1 remote branch"));
+    assert!(!exists(repo.path(), "old-merged"));
+    assert!(!exists(origin.path(), "old-merged"));
+
+    if backup_dir.is_dir() {
+        fs::set_permissions(&backup_dir, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+}
+
+#[cfg(unix)]
+#[test]
+#[allow(deprecated)]
+fn test_clean_print_backup_path_is_the_only_stdout() {
+    let repo = create_test_repo();
+    let home = TempDir::new().unwrap();
+    for name in ["old-one", "old-two"] {
+        create_branch(repo.path(), name);
+        make_branch_old(repo.path(), name, 45);
+        merge_branch(repo.path(), name);
+    }
+
+    let output = Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--local", "--print-backup-path"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "{:?}", output);
+
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "stdout: {:?}", stdout);
+    assert!(!stdout.contains('\x1b'), "stdout is styled: {:?}", stdout);
+    assert!(stderr.contains("Deleted 2 local branches"), "{}", stderr);
+
+    let path = PathBuf::from(stdout.trim_end());
+    assert!(path.is_absolute(), "{}", path.display());
+    assert!(path.starts_with(home.path()), "{}", path.display());
+    let contents = fs::read_to_string(&path).unwrap();
+    assert!(contents.contains("old-one") && contents.contains("old-two"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_clean_print_backup_path_rejects_dry_run() {
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "--dry-run", "--print-backup-path"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_clean_report_records_deleted_branches() {
+    let repo = create_test_repo();
+    let home = TempDir::new().unwrap();
+    for name in ["old-one", "old-two"] {
+        create_branch(repo.path(), name);
+        make_branch_old(repo.path(), name, 45);
+        merge_branch(repo.path(), name);
+    }
+    let reports = TempDir::new().unwrap();
+    let clean = |report: &std::path::Path| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(["clean", "-y", "--local", "--report"])
+            .arg(report)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+            .success()
+    };
+
+    let text_path = reports.path().join("cleanup.txt");
+    clean(&text_path).stdout(predicate::str::contains("Report written to"));
+    let text = fs::read_to_string(&text_path).unwrap();
+    assert!(text.starts_with("deadbranch clean report"), "{}", text);
+    assert!(text.contains("Default branch: main"), "{}", text);
+    assert!(text.contains("Selection         merged only"), "{}", text);
+    assert!(text.contains("Branches: 2 deleted of 2"), "{}", text);
+    for name in ["old-one", "old-two"] {
+        let line = text.lines().find(|l| l.contains(name)).unwrap();
+        assert!(
+            line.contains("local") && line.ends_with("deleted"),
+            "{}",
+            line
+        );
+    }
+    let backup_dir = home
+        .path()
+        .join(".deadbranch/backups")
+        .join(get_repo_name(repo.path()));
+    assert!(text.contains(&*backup_dir.to_string_lossy()), "{}", text);
+
+    // Nothing left to delete: the report still documents the run, as HTML
+    let html_path = reports.path().join("cleanup.HTML");
+    clean(&html_path);
+    let html = fs::read_to_string(&html_path).unwrap();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("Branches: 0 deleted of 0"));
+
+    // JSON adds where the run's time went, for bug reports
+    let json_path = reports.path().join("cleanup.json");
+    clean(&json_path);
+    let json: serde_json::Value =
+        serde_json::from_str(&fs::read_to_string(&json_path).unwrap()).unwrap();
+    assert_eq!(json["default_branch"], "main");
+    assert_eq!(json["branches"].as_array().unwrap().len(), 0);
+    let performance = &json["performance"];
+    assert!(performance["git_calls"].as_u64().unwrap() > 0, "{}", json);
+    assert!(performance["commands"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .any(|c| c["command"] == "for-each-ref" && c["calls"].as_u64().unwrap() > 0));
+    assert!(performance["slowest"]["args"].is_array(), "{}", json);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_error_format_json() {
+    let repo = create_test_repo();
+    let home = TempDir::new().unwrap();
+    create_branch(repo.path(), "backed-up-branch");
+    make_branch_old(repo.path(), "backed-up-branch", 45);
+    merge_branch(repo.path(), "backed-up-branch");
+
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+    deadbranch(&["clean", "-y"]).success();
+
+    let output = deadbranch(&[
+        "backup",
+        "restore",
+        "not-in-backup",
+        "--error-format",
+        "json",
+    ])
+    .failure()
+    .code(13)
+    .get_output()
+    .clone();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let error: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+    assert_eq!(error["kind"], "BranchNotInBackup");
+    assert_eq!(error["error"], "Branch 'not-in-backup' not found in backup");
+    // No suggestions around the JSON
+    assert!(String::from_utf8(output.stdout).unwrap().trim().is_empty());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_current_outside_a_repo_error_format_json() {
+    let temp_dir = TempDir::new().unwrap();
+
+    for action in ["list", "verify", "clean"] {
+        let output = Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(["backup", action, "--current", "--error-format", "json"])
+            .current_dir(&temp_dir)
+            .assert()
+            .failure()
+            .code(3)
+            .get_output()
+            .clone();
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let error: serde_json::Value = serde_json::from_str(stderr.trim()).unwrap();
+        assert_eq!(error["kind"], "NotAGitRepository", "backup {}", action);
+        assert!(String::from_utf8(output.stdout).unwrap().is_empty());
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_push_returns_remote_branch_to_origin() {
+    let repo = create_test_repo();
+    let home = TempDir::new().unwrap();
+    let origin = TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let out = StdCommand::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        assert!(out.status.success(), "{:?}", out);
+    };
+    StdCommand::new("git")
+        .args(["init", "--bare", origin.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    git(&["remote", "add", "origin", origin.path().to_str().unwrap()]);
+    create_branch(repo.path(), "old-merged");
+    make_branch_old(repo.path(), "old-merged", 45);
+    merge_branch(repo.path(), "old-merged");
+    git(&["push", "origin", "main", "old-merged"]);
+    git(&["fetch", "origin"]);
+    git(&["branch", "-D", "old-merged"]);
+
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+    let on_origin = || {
+        StdCommand::new("git")
+            .args(["rev-parse", "--verify", "refs/heads/old-merged"])
+            .current_dir(origin.path())
+            .output()
+            .unwrap()
+            .status
+            .success()
+    };
+    deadbranch(&["clean", "-y", "--remote"])
+        .success()
+        .stdout(predicate::str::contains("Deleted 1 remote branch"));
+    assert!(!on_origin());
+
+    // Without --push (and no terminal to ask on) only the local branch comes back
+    deadbranch(&["backup", "restore", "old-merged"])
+        .success()
+        .stdout(predicate::str::contains(
+            "'old-merged' was deleted from origin",
+        ))
+        .stdout(predicate::str::contains("rerun with --push"));
+    assert!(branch_exists(repo.path(), "old-merged"));
+    assert!(!on_origin());
+
+    // The server refuses: the local restore stands
+    let hook = origin.path().join("hooks/pre-receive");
+    fs::write(&hook, "#!/bin/sh\necho 'pushes are frozen' >&2\nexit 1\n").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)).unwrap();
+    }
+    deadbranch(&["backup", "restore", "old-merged", "--force", "--push"])
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Restored branch 'old-merged'"))
+        .stderr(predicate::str::contains(
+            "Restored 'old-merged' locally, but pushing it to origin failed",
+        ))
+        .stderr(predicate::str::contains("pushes are frozen"));
+    assert!(branch_exists(repo.path(), "old-merged"));
+    assert!(!on_origin());
+
+    fs::remove_file(&hook).unwrap();
+    deadbranch(&["backup", "restore", "old-merged", "--force", "--push"])
+        .success()
+        .stdout(predicate::str::contains(
+            "Pushed 'old-merged' to origin/old-merged",
+        ));
+    assert!(on_origin());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_restore_matching_restores_a_family_of_branches() {
+    let repo = create_test_repo();
+    let home = TempDir::new().unwrap();
+    for branch in ["release/2.0", "release/2.1", "release/3.0", "feature/x"] {
+        create_branch(repo.path(), branch);
+        make_branch_old(repo.path(), branch, 45);
+        merge_branch(repo.path(), branch);
+    }
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+    deadbranch(&["clean", "-y"])
+        .success()
+        .stdout(predicate::str::contains("Deleted 4 local branches"));
+
+    // A typo lists what the backup holds and fails
+    deadbranch(&["backup", "restore", "--matching", "relase/*"])
+        .failure()
+        .code(1)
+        .stderr(predicate::str::contains(
+            "No branches in backup match 'relase/*'",
+        ))
+        .stdout(predicate::str::contains("release/2.0"))
+        .stdout(predicate::str::contains("feature/x"));
+    deadbranch(&[
+        "--error-format",
+        "json",
+        "backup",
+        "restore",
+        "--matching",
+        "relase/*",
+    ])
+    .failure()
+    .stderr(predicate::str::contains(r#""kind":"NoBranchesMatch""#));
+    assert!(!branch_exists(repo.path(), "release/2.0"));
+
+    // Without a terminal to confirm on, nothing is restored and the run fails
+    deadbranch(&["backup", "restore", "--matching", "release/2.*"])
+        .code(1)
+        .stdout(predicate::str::contains("2 branches in backup-"))
+        .stderr(predicate::str::contains("pass --yes"));
+    assert!(!branch_exists(repo.path(), "release/2.0"));
+
+    deadbranch(&["backup", "restore", "--matching", "release/2.*", "--yes"])
+        .success()
+        .stdout(predicate::str::contains("Restored branch 'release/2.0'"))
+        .stdout(predicate::str::contains("Restored branch 'release/2.1'"))
+        .stdout(predicate::str::contains(
+            "Restored 2 branches matching 'release/2.*'",
+        ));
+    assert!(branch_exists(repo.path(), "release/2.0"));
+    assert!(branch_exists(repo.path(), "release/2.1"));
+    assert!(!branch_exists(repo.path(), "release/3.0"));
+    assert!(!branch_exists(repo.path(), "feature/x"));
+
+    // Existing branches fail one by one, the rest are still restored
+    deadbranch(&["backup", "restore", "--matching", "release/*", "-y"])
+        .failure()
+        .code(1)
+        .stdout(predicate::str::contains("Restored branch 'release/3.0'"))
+        .stdout(predicate::str::contains("rerun with --force"))
+        .stderr(predicate::str::contains(
+            "Branch 'release/2.0' already exists",
+        ))
+        .stderr(predicate::str::contains(
+            "Restored 1 of 3 branches matching 'release/*'",
+        ));
+    assert!(branch_exists(repo.path(), "release/3.0"));
+
+    // --force applies to every selected branch
+    deadbranch(&[
+        "backup",
+        "restore",
+        "--matching",
+        "release/*",
+        "-y",
+        "--force",
+    ])
+    .success()
+    .stdout(predicate::str::contains("overwrote existing"))
+    .stdout(predicate::str::contains(
+        "Restored 3 branches matching 'release/*'",
+    ));
+
+    // --as names one branch, so it can't go with --matching
+    deadbranch(&["backup", "restore", "--matching", "release/*", "--as", "x"])
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    deadbranch(&[
+        "backup",
+        "restore",
+        "release/2.0",
+        "--matching",
+        "release/*",
+    ])
+    .failure();
+    deadbranch(&["backup", "restore"]).failure();
+}
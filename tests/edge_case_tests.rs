@@ -429,3 +429,526 @@ fn test_clean_deletes_rebase_merged_branch() {
         "rebase-merged branch should have been deleted"
     );
 }
+
+#[test]
+#[allow(deprecated)]
+fn test_repository_without_commits() {
+    let repo = tempfile::TempDir::new().unwrap();
+    let home = tempfile::TempDir::new().unwrap();
+    StdCommand::new("git")
+        .args(["init"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+            .success()
+    };
+
+    for args in [
+        &["list"][..],
+        &["clean", "--dry-run"],
+        &["clean", "-y"],
+        &["stats"],
+    ] {
+        deadbranch(args)
+            .stdout(predicate::str::contains(
+                "Repository has no commits yet — nothing to do",
+            ))
+            .stdout(predicate::str::contains("Could not resolve").not())
+            .stderr(predicate::str::is_empty());
+    }
+    deadbranch(&["list", "--exit-code"]).stdout(predicate::str::is_empty());
+
+    // Commands that don't read branches are unaffected
+    deadbranch(&["config", "show"]).stdout(predicate::str::contains("default_days"));
+    deadbranch(&["backup", "list"]).stdout(predicate::str::contains("No backups found"));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_submodule_backups_are_filed_under_superproject() {
+    let home = tempfile::TempDir::new().unwrap();
+    let parent = create_test_repo();
+    let upstream = create_test_repo();
+    common::create_branch(upstream.path(), "old-sub");
+    common::make_branch_old(upstream.path(), "old-sub", 45);
+    StdCommand::new("git")
+        .args(["merge", "--no-ff", "old-sub", "-m", "Merge old-sub"])
+        .current_dir(&upstream)
+        .output()
+        .unwrap();
+
+    let status = StdCommand::new("git")
+        .args(["-c", "protocol.file.allow=always", "submodule", "add"])
+        .arg(upstream.path())
+        .arg("vendor")
+        .current_dir(&parent)
+        .output()
+        .unwrap()
+        .status;
+    assert!(status.success());
+    let vendor = parent.path().join("vendor");
+    StdCommand::new("git")
+        .args(["branch", "old-sub", "origin/old-sub"])
+        .current_dir(&vendor)
+        .output()
+        .unwrap();
+
+    let parent_name = parent.path().file_name().unwrap().to_str().unwrap();
+    let repo_name = format!("{}/vendor", parent_name);
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y", "--local"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&vendor)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Inside a submodule of '{}'",
+            parent_name
+        )))
+        .stdout(predicate::str::contains("old-sub"));
+
+    // The submodule's branch is gone; the superproject is untouched
+    let branches = StdCommand::new("git")
+        .args(["branch", "--list", "old-sub"])
+        .current_dir(&vendor)
+        .output()
+        .unwrap();
+    assert!(String::from_utf8_lossy(&branches.stdout).trim().is_empty());
+
+    let backup_dir = home
+        .path()
+        .join(".deadbranch/backups")
+        .join(parent_name)
+        .join("vendor");
+    assert_eq!(fs::read_dir(&backup_dir).unwrap().count(), 1);
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "list"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(repo_name.as_str()));
+}
+
+#[test]
+#[allow(deprecated)]
+#[cfg(unix)]
+fn test_symlinked_repo_path_shares_backup_dir() {
+    let home = tempfile::TempDir::new().unwrap();
+    let links = tempfile::TempDir::new().unwrap();
+    let repo = create_test_repo();
+    let link = links.path().join("app-link");
+    std::os::unix::fs::symlink(repo.path(), &link).unwrap();
+
+    common::create_branch(repo.path(), "old-feature");
+    common::make_branch_old(repo.path(), "old-feature", 45);
+    StdCommand::new("git")
+        .args(["merge", "--no-ff", "old-feature", "-m", "Merge old-feature"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+
+    // Backups split off under the symlink's name, one of them from another repository
+    let backups = home.path().join(".deadbranch/backups");
+    let split = backups.join("app-link");
+    fs::create_dir_all(&split).unwrap();
+    let backup = |repo_path: &std::path::Path| {
+        format!(
+            "# deadbranch backup\n# Created: 2024-01-01T00:00:00+00:00\n# Repository: app-link\n# Repository path: {}\n\n# gone\ngit branch gone abc1234\n",
+            repo_path.display()
+        )
+    };
+    fs::write(split.join("backup-20240101-000000.txt"), backup(&link)).unwrap();
+    fs::write(
+        split.join("backup-20240102-000000.txt"),
+        backup(std::path::Path::new("/elsewhere/app-link")),
+    )
+    .unwrap();
+
+    let canonical = repo.path().canonicalize().unwrap();
+    let repo_name = canonical.file_name().unwrap().to_str().unwrap();
+
+    // Deleting branches never touches other backup directories
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&link)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Moved").not());
+    assert!(split.join("backup-20240101-000000.txt").exists());
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "migrate", "--current"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&link)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Moved 1 backup of this repository from 'app-link' to '{}'",
+            repo_name
+        )));
+
+    let mut ours: Vec<_> = fs::read_dir(backups.join(repo_name))
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .filter(|name| name.ends_with(".txt"))
+        .collect();
+    ours.sort();
+    assert_eq!(ours.len(), 2);
+    assert_eq!(ours[0], "backup-20240101-000000.txt");
+    assert!(split.join("backup-20240102-000000.txt").exists());
+    assert!(!split.join("backup-20240101-000000.txt").exists());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_backup_dir_case_collision_warns() {
+    let home = tempfile::TempDir::new().unwrap();
+    let repo = create_test_repo();
+    let repo_name = repo
+        .path()
+        .canonicalize()
+        .unwrap()
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let mut other = repo_name.to_uppercase();
+    if other == repo_name {
+        other = repo_name.to_lowercase();
+    }
+    // Another repository's backups, named like this one apart from case. On a
+    // case-insensitive filesystem this is the directory this repository uses too.
+    let backups = home.path().join(".deadbranch/backups");
+    fs::create_dir_all(backups.join(&other)).unwrap();
+
+    let clean = |repo_dir: &std::path::Path, branch: &str| {
+        common::create_branch(repo_dir, branch);
+        common::make_branch_old(repo_dir, branch, 45);
+        StdCommand::new("git")
+            .args(["merge", "--no-ff", branch, "-m", "Merge"])
+            .current_dir(repo_dir)
+            .output()
+            .unwrap();
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(["clean", "-y"])
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(repo_dir)
+            .assert()
+            .success()
+    };
+
+    clean(repo.path(), "old-one")
+        .stdout(predicate::str::contains(format!(
+            "Backups for '{}' and '{}' differ only by case",
+            repo_name, other
+        )))
+        .stdout(predicate::str::contains("deadbranch config set repo-name"));
+
+    // A distinct name keeps the backups apart and silences the warning;
+    // `backup migrate` moves the backup taken under the old name over
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["config", "set", "repo-name", "renamed-repo"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&repo)
+        .assert()
+        .success();
+    clean(repo.path(), "old-two").stdout(predicate::str::contains("differ only by case").not());
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["backup", "migrate", "--current"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Moved 1 backup of this repository from '{}' to 'renamed-repo'",
+            repo_name
+        )));
+    assert_eq!(
+        fs::read_dir(backups.join("renamed-repo")).unwrap().count(),
+        2
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_refs_pointing_at_tag_objects_are_skipped() {
+    let home = tempfile::TempDir::new().unwrap();
+    let repo = create_test_repo();
+    let git = |args: &[&str]| {
+        let out = StdCommand::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        String::from_utf8_lossy(&out.stdout).trim().to_string()
+    };
+    git(&["tag", "-a", "v1", "-m", "Release v1"]);
+    let tag = git(&["rev-parse", "v1"]);
+    // Newer git refuses to point a branch at a tag object, so fall back to
+    // writing the loose ref by hand
+    git(&["update-ref", "refs/heads/weird", &tag]);
+    if git(&["for-each-ref", "--format=%(objecttype)", "refs/heads/weird"]) != "tag" {
+        fs::write(
+            repo.path().join(".git/refs/heads/weird"),
+            format!("{}\n", tag),
+        )
+        .unwrap();
+    }
+    assert_eq!(
+        git(&["for-each-ref", "--format=%(objecttype)", "refs/heads/weird"]),
+        "tag"
+    );
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .current_dir(&repo)
+            .assert()
+    };
+
+    deadbranch(&["list", "--days", "0", "--verbose"])
+        .success()
+        .stdout(predicate::str::contains("weird").not())
+        .stderr(predicate::str::contains(
+            "Skipping 'weird': it points at a tag, not a commit",
+        ));
+    deadbranch(&["clean", "--days", "0", "--dry-run"])
+        .success()
+        .stdout(predicate::str::contains("weird").not());
+
+    // Restoring a backup entry that names the tag object says what it is
+    let repo_name = repo
+        .path()
+        .canonicalize()
+        .unwrap()
+        .file_name()
+        .unwrap()
+        .to_str()
+        .unwrap()
+        .to_string();
+    let backups = home.path().join(".deadbranch/backups").join(repo_name);
+    fs::create_dir_all(&backups).unwrap();
+    fs::write(
+        backups.join("backup-20240101-000000.txt"),
+        format!(
+            "# deadbranch backup\n# Created: 2024-01-01T00:00:00+00:00\n\n# tagged\ngit branch tagged {}\n",
+            tag
+        ),
+    )
+    .unwrap();
+    deadbranch(&["backup", "restore", "tagged"])
+        .failure()
+        .stderr(predicate::str::contains(format!(
+            "Cannot restore 'tagged': {} is a tag, not a commit",
+            &tag[..8]
+        )));
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_git_dir_env_names_the_target_repository() {
+    let home = tempfile::TempDir::new().unwrap();
+    let elsewhere = tempfile::TempDir::new().unwrap();
+    let repo = create_test_repo();
+    common::create_branch(repo.path(), "old-feature");
+    common::make_branch_old(repo.path(), "old-feature", 45);
+    StdCommand::new("git")
+        .args(["merge", "--no-ff", "old-feature", "-m", "Merge old-feature"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    let canonical = repo.path().canonicalize().unwrap();
+    let repo_name = canonical.file_name().unwrap().to_str().unwrap();
+    let git_dir = repo.path().join(".git");
+    let deadbranch = |args: &[&str]| {
+        Command::cargo_bin("deadbranch")
+            .unwrap()
+            .args(args)
+            .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+            .env("GIT_DIR", &git_dir)
+            .current_dir(&elsewhere)
+            .assert()
+    };
+
+    // Backups are filed under the repository GIT_DIR points at, not the
+    // directory deadbranch happened to run in
+    deadbranch(&["clean", "-y"]).success();
+    let backups = home.path().join(".deadbranch/backups");
+    let dirs: Vec<_> = fs::read_dir(&backups)
+        .unwrap()
+        .map(|e| e.unwrap().file_name().into_string().unwrap())
+        .collect();
+    assert_eq!(dirs, vec![repo_name.to_string()]);
+    let backup = fs::read_dir(backups.join(repo_name))
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(fs::read_to_string(backup.path())
+        .unwrap()
+        .contains(&format!("# Repository path: {}\n", canonical.display())));
+
+    deadbranch(&["backup", "restore", "old-feature"]).success();
+    assert!(StdCommand::new("git")
+        .args(["rev-parse", "--verify", "refs/heads/old-feature"])
+        .current_dir(&repo)
+        .output()
+        .unwrap()
+        .status
+        .success());
+
+    // A GIT_DIR that isn't a repository is named in the error
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["list"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .env("GIT_DIR", elsewhere.path().join("missing"))
+        .current_dir(&elsewhere)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("GIT_DIR is set to"));
+}
+
+#[test]
+#[cfg(unix)]
+#[allow(deprecated)]
+fn test_non_utf8_branch_names_are_skipped_not_mangled() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let home = tempfile::TempDir::new().unwrap();
+    let repo = create_test_repo();
+    common::create_branch(repo.path(), "old-feature");
+    common::make_branch_old(repo.path(), "old-feature", 45);
+    StdCommand::new("git")
+        .args(["merge", "--no-ff", "old-feature", "-m", "Merge old-feature"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    // "café" in latin-1, pointing at the same stale, merged commit
+    let latin1 = std::ffi::OsStr::from_bytes(b"refs/heads/caf\xe9");
+    let created = StdCommand::new("git")
+        .arg("update-ref")
+        .arg(latin1)
+        .arg("old-feature")
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert!(created.status.success(), "{:?}", created);
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["clean", "-y"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            "Skipping local branch 'caf\\xe9': its name is not valid UTF-8",
+        ))
+        .stdout(predicate::str::contains("Deleted 1 local branch"));
+
+    let still_there = StdCommand::new("git")
+        .args(["rev-parse", "--verify", "--quiet"])
+        .arg(latin1)
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+    assert!(still_there.status.success());
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_clean_retries_while_a_ref_lock_is_held() {
+    let home = tempfile::TempDir::new().unwrap();
+    let repo = create_test_repo();
+    common::create_branch(repo.path(), "old-feature");
+    common::make_branch_old(repo.path(), "old-feature", 45);
+    StdCommand::new("git")
+        .args(["merge", "--no-ff", "old-feature", "-m", "Merge old-feature"])
+        .current_dir(&repo)
+        .output()
+        .unwrap();
+
+    // Another git process (say, an IDE's) holds the ref's lock for a moment
+    let lock = repo.path().join(".git/refs/heads/old-feature.lock");
+    fs::write(&lock, "").unwrap();
+    let release = {
+        let lock = lock.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(500));
+            fs::remove_file(lock).unwrap();
+        })
+    };
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["--verbose", "clean", "-y"])
+        .env("DEADBRANCH_HOME", home.path().join(".deadbranch"))
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "found a lock held by another git process; retrying",
+        ))
+        .stdout(predicate::str::contains("Deleted 1 local branch"));
+    release.join().unwrap();
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_merge_check_uses_remote_only_default_branch() {
+    let repo = create_test_repo();
+    let origin = tempfile::TempDir::new().unwrap();
+    let git = |args: &[&str]| {
+        let output = StdCommand::new("git")
+            .args(args)
+            .current_dir(&repo)
+            .output()
+            .unwrap();
+        assert!(output.status.success(), "git {:?} failed", args);
+    };
+
+    common::create_branch(repo.path(), "old-merged");
+    common::make_branch_old(repo.path(), "old-merged", 45);
+    git(&["merge", "old-merged", "--no-ff", "-m", "Merge old-merged"]);
+
+    StdCommand::new("git")
+        .args(["init", "--bare", origin.path().to_str().unwrap()])
+        .output()
+        .unwrap();
+    git(&["remote", "add", "origin", origin.path().to_str().unwrap()]);
+    git(&["push", "origin", "main"]);
+    git(&["remote", "set-head", "origin", "main"]);
+
+    // Leave `main` only as `origin/main`, as in a single-branch checkout
+    git(&["checkout", "-b", "work"]);
+    git(&["branch", "-D", "main"]);
+
+    Command::cargo_bin("deadbranch")
+        .unwrap()
+        .args(["list", "--local", "--merged"])
+        .current_dir(&repo)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("old-merged"));
+}